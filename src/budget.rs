@@ -0,0 +1,181 @@
+//! Spending budgets from `config.json`, checked against each day's recorded cost.
+//!
+//! ```json
+//! { "budgets": {
+//!     "monthly": 200, "daily": 20,
+//!     "projects": { "~/work/api": { "monthly": 80 } },
+//!     "providers": { "anthropic": { "monthly": 150 } },
+//!     "highlight_heatmap": true
+//! } }
+//! ```
+//!
+//! Days and months are local calendar ones. Provider spend sums each model's
+//! recorded cost on the days in question.
+
+use crate::config::BudgetLimits;
+use crate::stats::{DayStat, ModelUsage};
+use chrono::{Datelike, NaiveDate};
+use rustc_hash::FxHashMap;
+
+/// Share of a limit that raises the first warning.
+const WARN_RATIO: f64 = 0.8;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Period {
+    Day,
+    Month,
+}
+
+impl Period {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Day => "today",
+            Self::Month => "this month",
+        }
+    }
+}
+
+/// What a budget covers.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Scope {
+    All,
+    /// Project key as written in the config.
+    Project(String),
+    Provider(String),
+}
+
+impl Scope {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::All => "All",
+            Self::Project(key) => key.rsplit('/').find(|s| !s.is_empty()).unwrap_or(key),
+            Self::Provider(id) => id,
+        }
+    }
+}
+
+/// Spend against one configured limit.
+#[derive(Clone)]
+pub struct BudgetStatus {
+    pub scope: Scope,
+    pub period: Period,
+    pub spent: f64,
+    pub limit: f64,
+}
+
+impl BudgetStatus {
+    pub fn ratio(&self) -> f64 {
+        if self.limit > 0.0 {
+            self.spent / self.limit
+        } else if self.spent > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        }
+    }
+
+    /// Highest warning threshold reached, in percent: 0, 80 or 100.
+    pub fn level(&self) -> u8 {
+        match self.ratio() {
+            r if r >= 1.0 => 100,
+            r if r >= WARN_RATIO => 80,
+            _ => 0,
+        }
+    }
+}
+
+/// Every configured limit with its spend over the day or month of `today`.
+/// Pass unfiltered stats: budgets ignore the date range and source filter.
+pub fn evaluate(
+    per_day: &FxHashMap<String, DayStat>,
+    models: &[ModelUsage],
+    today: NaiveDate,
+) -> Vec<BudgetStatus> {
+    let config = &crate::config::get().budgets;
+    let day_key = today.format("%Y-%m-%d").to_string();
+    let month_prefix = today.format("%Y-%m-").to_string();
+    let in_period = |day: &str, period: Period| match period {
+        Period::Day => day == day_key,
+        Period::Month => day.starts_with(month_prefix.as_str()),
+    };
+
+    let mut statuses = Vec::new();
+    let mut push = |scope: Scope, limits: &BudgetLimits, spent: &dyn Fn(Period) -> f64| {
+        for (period, limit) in [(Period::Day, limits.daily), (Period::Month, limits.monthly)] {
+            if let Some(limit) = limit {
+                statuses.push(BudgetStatus {
+                    scope: scope.clone(),
+                    period,
+                    spent: spent(period),
+                    limit,
+                });
+            }
+        }
+    };
+
+    push(Scope::All, &config.global, &|period| {
+        per_day
+            .iter()
+            .filter(|(day, _)| in_period(day, period))
+            .map(|(_, stat)| stat.display_cost())
+            .sum()
+    });
+    for (project, limits) in &config.projects {
+        let root = crate::config::expand_home(project);
+        let root = root.to_string_lossy();
+        let root = root.trim_end_matches('/');
+        push(Scope::Project(project.clone()), limits, &|period| {
+            per_day
+                .iter()
+                .filter(|(day, _)| in_period(day, period))
+                .flat_map(|(_, stat)| stat.sessions.values())
+                .filter(|s| project_matches(root, &s.path_root))
+                .map(|s| s.display_cost())
+                .sum()
+        });
+    }
+    for (provider, limits) in &config.providers {
+        push(Scope::Provider(provider.clone()), limits, &|period| {
+            models
+                .iter()
+                .filter(|m| m.provider.eq_ignore_ascii_case(provider))
+                .map(|m| model_spend(m, |day| in_period(day, period)))
+                .sum()
+        });
+    }
+    statuses
+}
+
+/// A project key matches its root directory or the directory name alone.
+fn project_matches(key: &str, path_root: &str) -> bool {
+    let path_root = path_root.trim_end_matches('/');
+    !path_root.is_empty() && (path_root == key || path_root.rsplit('/').next() == Some(key))
+}
+
+/// A model's recorded cost on the days `include` accepts.
+fn model_spend(m: &ModelUsage, include: impl Fn(&str) -> bool) -> f64 {
+    m.daily
+        .iter()
+        .filter(|(day, _)| include(day))
+        .map(|(_, d)| d.cost)
+        .sum()
+}
+
+/// Daily limit used to mark over-budget days in the heatmap, when enabled:
+/// the global daily limit, else the monthly one spread over that month.
+pub fn heatmap_day_limit(date: NaiveDate) -> Option<f64> {
+    let config = &crate::config::get().budgets;
+    if !config.highlight_heatmap {
+        return None;
+    }
+    config.global.daily.or_else(|| {
+        let monthly = config.global.monthly?;
+        let next_month = if date.month() == 12 {
+            NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+        }?;
+        let days = next_month.pred_opt()?.day();
+        Some(monthly / days as f64)
+    })
+}
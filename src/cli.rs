@@ -0,0 +1,8 @@
+//! Argument helpers shared by the headless subcommands.
+
+use std::io;
+
+/// Error for a bad command-line argument.
+pub(crate) fn invalid_arg(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.to_string())
+}
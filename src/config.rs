@@ -0,0 +1,199 @@
+//! User configuration loaded from `$XDG_CONFIG_HOME/opencode-stats-tui/config.json`.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Env var listing extra storage roots, separated like `PATH`.
+pub const ROOTS_ENV: &str = "OPENCODE_STATS_ROOTS";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Extra storage roots, as `"PATH"`, `"LABEL=PATH"` or `{"path", "label"}`.
+    pub roots: Vec<RootEntry>,
+    /// Where model prices for estimated costs come from.
+    pub pricing: PricingConfig,
+    /// Spending limits checked against recorded cost.
+    pub budgets: BudgetConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// Limits on all usage.
+    #[serde(flatten)]
+    pub global: BudgetLimits,
+    /// Limits per project, keyed by its root directory or the directory name.
+    pub projects: BTreeMap<String, BudgetLimits>,
+    /// Limits per provider id, e.g. `anthropic`.
+    pub providers: BTreeMap<String, BudgetLimits>,
+    /// Mark days over the daily budget in the yearly activity heatmap.
+    pub highlight_heatmap: bool,
+}
+
+/// USD limits; either may be left out.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct BudgetLimits {
+    /// Per calendar day.
+    pub daily: Option<f64>,
+    /// Per calendar month.
+    pub monthly: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PricingConfig {
+    pub source: PricingCatalog,
+    /// Fetch the catalog from this URL instead, e.g. a mirror on the local network.
+    pub url: Option<String>,
+    /// Catalog file for the `file` source, in OpenRouter or models.dev format.
+    pub path: Option<String>,
+}
+
+/// Pricing catalog, named as in the config file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum PricingCatalog {
+    #[default]
+    #[serde(rename = "openrouter")]
+    OpenRouter,
+    /// The catalog opencode itself prices messages from.
+    #[serde(rename = "models.dev")]
+    ModelsDev,
+    #[serde(rename = "file")]
+    File,
+}
+
+impl PricingCatalog {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::OpenRouter => "OpenRouter",
+            Self::ModelsDev => "models.dev",
+            Self::File => "Local",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RootEntry {
+    Spec(String),
+    Labeled {
+        path: String,
+        #[serde(default)]
+        label: Option<String>,
+    },
+}
+
+/// An extra storage root, optionally labelled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootSpec {
+    pub label: Option<String>,
+    pub path: PathBuf,
+}
+
+impl RootSpec {
+    /// Parse `PATH` or `LABEL=PATH`. A `=` after a path separator belongs to the path.
+    pub fn parse(s: &str) -> Self {
+        match s.split_once('=') {
+            Some((label, path)) if !label.is_empty() && !label.contains(['/', '\\']) => Self {
+                label: Some(label.to_string()),
+                path: expand_home(path),
+            },
+            _ => Self {
+                label: None,
+                path: expand_home(s),
+            },
+        }
+    }
+
+    /// Strip repeatable `--root` flags from `args`.
+    pub fn take_from_args(args: &mut Vec<String>) -> Result<Vec<Self>, String> {
+        let mut roots = Vec::new();
+        let mut i = 0;
+        while i < args.len() {
+            let value = if args[i] == "--root" {
+                args.remove(i);
+                if i >= args.len() {
+                    return Err("--root expects a path".to_string());
+                }
+                args.remove(i)
+            } else if let Some(v) = args[i].strip_prefix("--root=") {
+                let v = v.to_string();
+                args.remove(i);
+                v
+            } else {
+                i += 1;
+                continue;
+            };
+            if value.is_empty() {
+                return Err("--root expects a path".to_string());
+            }
+            roots.push(Self::parse(&value));
+        }
+        Ok(roots)
+    }
+}
+
+#[inline]
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => {
+            PathBuf::from(env::var("HOME").unwrap_or_else(|_| ".".to_string())).join(rest)
+        }
+        None => PathBuf::from(path),
+    }
+}
+
+/// Path of the config file.
+pub fn config_path() -> PathBuf {
+    let base = env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| {
+        let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        format!("{}/.config", home)
+    });
+    PathBuf::from(base)
+        .join("opencode-stats-tui")
+        .join("config.json")
+}
+
+/// Loaded config; a missing file yields the defaults.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        let path = config_path();
+        let Ok(bytes) = fs::read(&path) else {
+            return Config::default();
+        };
+        serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            eprintln!("warning: ignoring {}: {}", path.display(), e);
+            Config::default()
+        })
+    })
+}
+
+/// Extra roots from the CLI, then `OPENCODE_STATS_ROOTS`, then the config file.
+pub fn extra_roots(cli: Vec<RootSpec>) -> Vec<RootSpec> {
+    let mut roots = cli;
+    if let Some(value) = env::var_os(ROOTS_ENV) {
+        for path in env::split_paths(&value) {
+            if let Some(s) = path.to_str().filter(|s| !s.is_empty()) {
+                roots.push(RootSpec::parse(s));
+            }
+        }
+    }
+    for entry in &get().roots {
+        roots.push(match entry {
+            RootEntry::Spec(s) => RootSpec::parse(s),
+            RootEntry::Labeled { path, label } => RootSpec {
+                label: label.clone(),
+                path: expand_home(path),
+            },
+        });
+    }
+    roots
+}
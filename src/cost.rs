@@ -1,21 +1,117 @@
-//! OpenRouter pricing lookup for cost estimation.
+//! Model pricing lookup for cost estimation, from OpenRouter, models.dev or a
+//! local catalog file.
 
+mod history;
+mod overrides;
+
+use overrides::find_alias;
+pub use overrides::{find_override, init_overrides, pin_alias, OverrideKind};
+
+use crate::config::PricingCatalog;
+use crate::stats::MessageUsage;
+use chrono::{DateTime, Local, NaiveDate};
 use rustc_hash::FxHashMap;
 use std::cell::RefCell;
 use std::path::PathBuf;
 use std::sync::OnceLock;
+use std::time::SystemTime;
 
 /// Pricing rates for a model.
-#[derive(Clone, Copy)]
+#[derive(Clone, PartialEq)]
 pub struct ModelPricing {
     pub prompt: f64,
     pub completion: f64,
     pub reasoning: f64,
     pub input_cache_read: f64,
     pub input_cache_write: f64,
+    /// Cache writes kept for an hour instead of five minutes.
+    pub input_cache_write_1h: f64,
+    /// Higher rates for long prompts, by ascending threshold.
+    pub tiers: Vec<PricingTier>,
+}
+
+/// Rates that replace the base ones once a message's prompt (input plus
+/// cached tokens) exceeds `above_prompt_tokens`.
+#[derive(Clone, PartialEq)]
+pub struct PricingTier {
+    pub above_prompt_tokens: u64,
+    pub rates: ModelPricing,
 }
 
-static PRICING_CACHE: OnceLock<FxHashMap<String, ModelPricing>> = OnceLock::new();
+impl ModelPricing {
+    /// Rates for a message with `prompt_tokens` of input and cache.
+    pub fn for_prompt(&self, prompt_tokens: u64) -> &ModelPricing {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|t| prompt_tokens > t.above_prompt_tokens)
+            .map_or(self, |t| &t.rates)
+    }
+}
+
+/// OpenRouter `/models` response trimmed to ids and pricing, used when neither
+/// the API nor the disk cache is available.
+const BUNDLED_PRICING: &str = include_str!("../assets/openrouter-pricing.json");
+
+/// Where the pricing table was loaded from.
+#[derive(Clone, Copy)]
+pub enum PricingSource {
+    /// Fetched from OpenRouter during this run.
+    Live,
+    /// Read from the disk cache written at `fetched`.
+    DiskCache { fetched: SystemTime },
+    /// Read from the catalog file set in the config, last changed at `modified`.
+    File { modified: SystemTime },
+    /// Snapshot embedded in the binary.
+    Bundled,
+}
+
+impl PricingSource {
+    /// Short description, e.g. `live`, `cached 3h ago` or `bundled 2025-10-01`.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Live => "live".into(),
+            Self::DiskCache { fetched } => {
+                let secs = fetched.elapsed().map_or(0, |age| age.as_secs());
+                let age = match secs {
+                    0..=3599 => format!("{}m", secs / 60),
+                    3600..=86399 => format!("{}h", secs / 3600),
+                    _ => format!("{}d", secs / 86400),
+                };
+                format!("cached {} ago", age)
+            }
+            Self::File { .. } => "file".into(),
+            Self::Bundled => format!("bundled {}", bundled_snapshot_date()),
+        }
+    }
+
+    /// Catalog the rates came from; the bundled snapshot is from OpenRouter.
+    pub fn catalog_name(&self) -> &'static str {
+        match self {
+            Self::Bundled => PricingCatalog::OpenRouter.name(),
+            _ => catalog().name(),
+        }
+    }
+
+    /// Day the rates were current.
+    fn date(&self) -> Option<NaiveDate> {
+        match self {
+            Self::Live => Some(Local::now().date_naive()),
+            Self::DiskCache { fetched } | Self::File { modified: fetched } => {
+                Some(DateTime::<Local>::from(*fetched).date_naive())
+            }
+            Self::Bundled => NaiveDate::parse_from_str(bundled_snapshot_date(), "%Y-%m-%d").ok(),
+        }
+    }
+}
+
+/// Pricing map with the source it came from.
+struct PricingTable {
+    map: FxHashMap<String, ModelPricing>,
+    source: PricingSource,
+}
+
+static PRICING_CACHE: OnceLock<PricingTable> = OnceLock::new();
 
 // Thread-local scratch buffer for fuzzy matching to avoid allocations per call
 thread_local! {
@@ -27,89 +123,301 @@ pub fn init_pricing() {
     PRICING_CACHE.get_or_init(fetch_pricing);
 }
 
-/// Look up pricing for a model name.
+/// Source of the loaded pricing table, or `None` while it is still loading.
+pub fn pricing_source() -> Option<PricingSource> {
+    PRICING_CACHE.get().map(|t| t.source)
+}
+
+/// How a model name was matched to a catalog entry, in the order tried.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchStrategy {
+    /// Priced by the override file.
+    Override,
+    /// Pinned to a catalog key with `same_as` in the override file.
+    Pinned,
+    ExactId,
+    /// Same model under the same provider, date suffix ignored.
+    Provider,
+    Slug,
+    /// Bare model name without its date suffix.
+    DateStripped,
+    /// Closest model name under the same provider.
+    ProviderFuzzy,
+    /// Closest bare model name from any provider.
+    Fuzzy,
+    Unmatched,
+}
+
+impl MatchStrategy {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Override => "override",
+            Self::Pinned => "pinned",
+            Self::ExactId => "exact id",
+            Self::Provider => "provider",
+            Self::Slug => "slug",
+            Self::DateStripped => "date stripped",
+            Self::ProviderFuzzy => "provider fuzzy",
+            Self::Fuzzy => "fuzzy",
+            Self::Unmatched => "unmatched",
+        }
+    }
+}
+
+/// Outcome of a pricing lookup, for diagnostics.
+#[derive(Clone)]
+pub struct PriceMatch {
+    pub strategy: MatchStrategy,
+    /// Catalog key or override pattern; for an unmatched model the closest key.
+    pub key: Option<String>,
+    /// Name similarity in percent, 100 for exact matches.
+    pub score: u8,
+    /// Rates of `key`.
+    pub pricing: Option<ModelPricing>,
+}
+
+impl PriceMatch {
+    const UNMATCHED: Self = Self {
+        strategy: MatchStrategy::Unmatched,
+        key: None,
+        score: 0,
+        pricing: None,
+    };
+
+    fn exact(strategy: MatchStrategy, key: &str, pricing: &ModelPricing) -> Self {
+        Self {
+            strategy,
+            key: Some(key.to_string()),
+            score: 100,
+            pricing: Some(pricing.clone()),
+        }
+    }
+
+    /// Rates to price with; an unmatched model's closest key doesn't count.
+    fn matched(self) -> Option<ModelPricing> {
+        if self.strategy == MatchStrategy::Unmatched {
+            return None;
+        }
+        self.pricing
+    }
+}
+
+/// Explain which entry prices a model, without fetching again on a miss.
+pub fn explain_pricing(model_name: &str) -> PriceMatch {
+    if let Some(o) = find_override(model_name) {
+        return PriceMatch {
+            strategy: MatchStrategy::Override,
+            key: Some(o.pattern),
+            score: 100,
+            pricing: Some(o.pricing),
+        };
+    }
+    let cache = PRICING_CACHE.get_or_init(fetch_pricing);
+    let Some(alias) = find_alias(model_name) else {
+        return match_in_map(&cache.map, model_name);
+    };
+    let mut found = match_in_map(&cache.map, &alias);
+    if found.strategy != MatchStrategy::Unmatched {
+        found.strategy = MatchStrategy::Pinned;
+    }
+    found
+}
+
+/// How a `same_as` key resolves in the loaded catalog.
+fn match_catalog_key(key: &str) -> PriceMatch {
+    let cache = PRICING_CACHE.get_or_init(fetch_pricing);
+    match_in_map(&cache.map, key)
+}
+
+/// Look up pricing for a model name, preferring the user's override file.
 pub fn lookup_pricing(model_name: &str) -> Option<ModelPricing> {
+    if let Some(o) = find_override(model_name) {
+        return Some(o.pricing);
+    }
+    let alias = find_alias(model_name);
+    let model_name = alias.as_deref().unwrap_or(model_name);
     let cache = PRICING_CACHE.get_or_init(fetch_pricing);
-    let found = lookup_in_map(cache, model_name);
+    let found = lookup_in_map(&cache.map, model_name);
     if found.is_some() {
         return found;
     }
 
-    // On miss, try a one-time live fetch for newly added models
+    // On miss, try a one-time live fetch for newly added models. A bundled
+    // table means the network was already unreachable, so don't retry.
+    if matches!(
+        cache.source,
+        PricingSource::Bundled | PricingSource::File { .. }
+    ) {
+        return None;
+    }
     let live = fetch_pricing();
-    if live.is_empty() {
+    if matches!(live.source, PricingSource::Bundled) {
         return None;
     }
-    lookup_in_map(&live, model_name)
+    lookup_in_map(&live.map, model_name)
 }
 
 fn lookup_in_map(map: &FxHashMap<String, ModelPricing>, model_name: &str) -> Option<ModelPricing> {
+    match_in_map(map, model_name).matched()
+}
+
+fn match_in_map(map: &FxHashMap<String, ModelPricing>, model_name: &str) -> PriceMatch {
     if map.is_empty() {
-        return None;
+        return PriceMatch::UNMATCHED;
     }
 
     let input = model_name.trim().to_ascii_lowercase();
     let slug = input.rsplit('/').next().unwrap_or(&input).to_string();
+    let stripped = strip_date_suffix(&slug);
+    // Same as `ModelUsage::provider`
+    let provider = input.split_once('/').map(|(p, _)| p);
 
     // Exact full-id match
-    if let Some(p) = map.get(input.as_str()) {
-        return Some(*p);
+    if let Some((key, p)) = map.get_key_value(input.as_str()) {
+        return PriceMatch::exact(MatchStrategy::ExactId, key, p);
+    }
+
+    // The same model under the same provider
+    if let Some(provider) = provider {
+        for candidate in [slug.as_str(), stripped] {
+            let id = format!("{}/{}", provider, candidate);
+            if let Some((key, p)) = map.get_key_value(id.as_str()) {
+                return PriceMatch::exact(MatchStrategy::Provider, key, p);
+            }
+        }
     }
 
     // Exact slug match
-    if let Some(p) = map.get(slug.as_str()) {
-        return Some(*p);
+    if let Some((key, p)) = map.get_key_value(slug.as_str()) {
+        return PriceMatch::exact(MatchStrategy::Slug, key, p);
     }
 
     // Strip date suffix and retry
-    let stripped = strip_date_suffix(&slug);
     if stripped != slug {
-        if let Some(p) = map.get(stripped) {
-            return Some(*p);
+        if let Some((key, p)) = map.get_key_value(stripped) {
+            return PriceMatch::exact(MatchStrategy::DateStripped, key, p);
         }
     }
 
-    // Fuzzy match
+    // Fuzzy matches only once no exact name fits; the closest candidate seen
+    // is reported when nothing matches
+    let mut nearest = PriceMatch::UNMATCHED;
     let local_norm = normalize(stripped);
+    if let Some(provider) = provider.filter(|_| !local_norm.is_empty()) {
+        let prefix = format!("{}/", provider);
+        let scoped = map
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix.as_str()));
+        let found = fuzzy_best(scoped, &local_norm, MatchStrategy::ProviderFuzzy);
+        if found.strategy != MatchStrategy::Unmatched {
+            return found;
+        }
+        nearest = found;
+    }
+
+    // Fuzzy match on slugs from every provider
     if local_norm.is_empty() {
-        return None;
+        return nearest;
+    }
+    let slugs = map.iter().filter(|(key, _)| !key.contains('/'));
+    let found = fuzzy_best(slugs, &local_norm, MatchStrategy::Fuzzy);
+    if found.strategy != MatchStrategy::Unmatched || found.score > nearest.score {
+        return found;
     }
+    nearest
+}
+
+/// Closest entry to a normalized slug, compared on the last id segment. An
+/// entry below 60% similarity comes back as the unmatched closest key.
+fn fuzzy_best<'a>(
+    entries: impl Iterator<Item = (&'a String, &'a ModelPricing)>,
+    local_norm: &str,
+    strategy: MatchStrategy,
+) -> PriceMatch {
     let mut best_score: usize = 0;
-    let mut best: Option<ModelPricing> = None;
+    let mut best: Option<(&String, &ModelPricing)> = None;
 
-    for (key, pricing) in map.iter() {
-        if key.contains('/') {
-            continue;
-        }
-        let key_norm = normalize(strip_date_suffix(key));
-        let s = fuzzy_score(&local_norm, &key_norm);
+    for (key, pricing) in entries {
+        let name = key.rsplit('/').next().unwrap_or(key);
+        let key_norm = normalize(strip_date_suffix(name));
+        let s = fuzzy_score(local_norm, &key_norm);
         if s > best_score {
             best_score = s;
-            best = Some(*pricing);
+            best = Some((key, pricing));
         }
     }
+    let Some((key, pricing)) = best else {
+        return PriceMatch::UNMATCHED;
+    };
 
     // Require minimum 60% match
-    if best_score > 0 {
-        let min_required = (local_norm.len().max(3) * 6) / 10;
-        if best_score >= min_required {
-            return best;
-        }
+    let len = local_norm.len().max(3);
+    let min_required = (len * 6) / 10;
+    PriceMatch {
+        strategy: if best_score >= min_required {
+            strategy
+        } else {
+            MatchStrategy::Unmatched
+        },
+        key: Some(key.clone()),
+        score: (best_score * 100 / len).min(100) as u8,
+        pricing: Some(pricing.clone()),
     }
-
-    None
 }
 
 /// Estimate cost from model name and token usage
 pub fn estimate_cost(model_name: &str, tokens: &crate::stats::Tokens) -> Option<f64> {
-    let p = lookup_pricing(model_name)?;
-    Some(
-        tokens.input as f64 * p.prompt
-            + tokens.output as f64 * p.completion
-            + tokens.reasoning as f64 * p.reasoning
-            + tokens.cache_read as f64 * p.input_cache_read
-            + tokens.cache_write as f64 * p.input_cache_write,
-    )
+    lookup_pricing(model_name).map(|p| price_tokens(&p, tokens))
+}
+
+/// Estimate cost of usage on a `YYYY-MM-DD` day at the rates of that time.
+/// `messages` are the long-prompt or 1h-cache messages within `tokens`; each
+/// is priced on its own tier, the rest at base rates.
+pub fn estimate_cost_on(
+    model_name: &str,
+    tokens: &crate::stats::Tokens,
+    messages: &[MessageUsage],
+    day: &str,
+) -> Option<f64> {
+    history::pricing_on(model_name, day).map(|p| price_usage(&p, tokens, messages))
+}
+
+/// Estimate cost of a model's usage, each day at the rates of that time.
+/// Days no snapshot can price are left out.
+pub fn estimate_model_cost(m: &crate::stats::ModelUsage) -> Option<f64> {
+    if m.daily_split.is_empty() {
+        return estimate_cost(&m.name, &m.tokens);
+    }
+    m.daily_split
+        .iter()
+        .filter_map(|(day, tokens)| {
+            let messages = m.daily_messages.get(day).map_or(&[][..], Vec::as_slice);
+            estimate_cost_on(&m.name, tokens, messages, day)
+        })
+        .fold(None, |sum, cost| Some(sum.unwrap_or(0.0) + cost))
+}
+
+/// Price `messages` one by one and the remainder of `tokens` at base rates.
+fn price_usage(p: &ModelPricing, tokens: &crate::stats::Tokens, messages: &[MessageUsage]) -> f64 {
+    let mut rest = *tokens;
+    let mut cost = 0.0;
+    for msg in messages {
+        let rates = p.for_prompt(msg.prompt_tokens());
+        let cache_write_1h = msg.cache_write_1h.min(msg.tokens.cache_write);
+        cost += price_tokens(rates, &msg.tokens)
+            + cache_write_1h as f64 * (rates.input_cache_write_1h - rates.input_cache_write);
+        crate::date_range::sub_tokens(&mut rest, &msg.tokens);
+    }
+    cost + price_tokens(p, &rest)
+}
+
+#[inline]
+fn price_tokens(p: &ModelPricing, tokens: &crate::stats::Tokens) -> f64 {
+    tokens.input as f64 * p.prompt
+        + tokens.output as f64 * p.completion
+        + tokens.reasoning as f64 * p.reasoning
+        + tokens.cache_read as f64 * p.input_cache_read
+        + tokens.cache_write as f64 * p.input_cache_write
 }
 
 /// Normalize slug for comparison
@@ -199,13 +507,27 @@ fn looks_like_yyyymmdd(tail: &str) -> bool {
     (2020..=2100).contains(&yyyy) && (1..=12).contains(&mm) && (1..=31).contains(&dd)
 }
 
+/// Catalog chosen in the config file.
+fn catalog() -> PricingCatalog {
+    crate::config::get().pricing.source
+}
+
+/// File name stem of the catalog's cache and snapshots.
+fn catalog_slug() -> &'static str {
+    match catalog() {
+        PricingCatalog::OpenRouter => "openrouter",
+        PricingCatalog::ModelsDev => "models-dev",
+        PricingCatalog::File => "file",
+    }
+}
+
 /// Returns the path to the cache file.
 fn cache_path() -> PathBuf {
     let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
     PathBuf::from(home)
         .join(".cache")
         .join("opencode-stats-tui")
-        .join("openrouter-pricing.json")
+        .join(format!("{}-pricing.json", catalog_slug()))
 }
 
 /// Returns true if the cache file exists and is less than 24 hours.
@@ -219,10 +541,25 @@ fn cache_is_fresh() -> bool {
         .is_some_and(|age| age < std::time::Duration::from_secs(86400))
 }
 
+/// Pricing keyed by full id and by slug, from either catalog format.
 fn parse_body(body: &serde_json::Value) -> FxHashMap<String, ModelPricing> {
-    let Some(data) = body.get("data").and_then(|d| d.as_array()) else {
-        return FxHashMap::default();
-    };
+    match body.get("data").and_then(|d| d.as_array()) {
+        Some(data) => parse_openrouter(data),
+        None => parse_models_dev(body),
+    }
+}
+
+/// First entry wins, so an exact id is never shadowed by another's slug.
+fn insert_pricing(map: &mut FxHashMap<String, ModelPricing>, id: &str, pricing: ModelPricing) {
+    let slug = id.rsplit('/').next().unwrap_or(id).to_ascii_lowercase();
+    let full = id.to_ascii_lowercase();
+
+    map.entry(full).or_insert_with(|| pricing.clone());
+    map.entry(slug).or_insert(pricing);
+}
+
+/// OpenRouter `/models`: a `data` list of models with per-token `pricing`.
+fn parse_openrouter(data: &[serde_json::Value]) -> FxHashMap<String, ModelPricing> {
     let mut map = FxHashMap::default();
     for m in data {
         let Some(id) = m.get("id").and_then(|v| v.as_str()) else {
@@ -231,55 +568,178 @@ fn parse_body(body: &serde_json::Value) -> FxHashMap<String, ModelPricing> {
         let Some(pr) = m.get("pricing").and_then(|v| v.as_object()) else {
             continue;
         };
-        let p = |k: &str| -> f64 {
-            pr.get(k)
-                .and_then(|v| {
-                    v.as_str()
-                        .and_then(|s| s.parse().ok())
-                        .or_else(|| v.as_f64())
+        let mut pricing = parse_rates(pr, None);
+        // Tiers list only the rates that change from the base ones
+        if let Some(tiers) = pr.get("tiers").and_then(|v| v.as_array()) {
+            pricing.tiers = tiers
+                .iter()
+                .filter_map(|t| {
+                    let t = t.as_object()?;
+                    Some(PricingTier {
+                        above_prompt_tokens: t.get("above_prompt_tokens")?.as_u64()?,
+                        rates: parse_rates(t, Some(&pricing)),
+                    })
                 })
-                .unwrap_or(0.0)
-                .max(0.0)
+                .collect();
+            pricing
+                .tiers
+                .sort_unstable_by_key(|t| t.above_prompt_tokens);
+        }
+        insert_pricing(&mut map, id, pricing);
+    }
+    map
+}
+
+/// models.dev `api.json`: providers keyed by id, each with `models` whose
+/// `cost` is in USD per million tokens and may have a `context_over_200k` tier.
+fn parse_models_dev(body: &serde_json::Value) -> FxHashMap<String, ModelPricing> {
+    let Some(providers) = body.as_object() else {
+        return FxHashMap::default();
+    };
+    // Same rates under OpenRouter's names and units
+    let per_token = |cost: &serde_json::Map<String, serde_json::Value>| {
+        [
+            ("input", "prompt"),
+            ("output", "completion"),
+            ("reasoning", "reasoning"),
+            ("cache_read", "input_cache_read"),
+            ("cache_write", "input_cache_write"),
+        ]
+        .into_iter()
+        .filter_map(|(from, to)| {
+            let usd_per_m = cost.get(from)?.as_f64()?;
+            Some((to.to_string(), serde_json::json!(usd_per_m / 1_000_000.0)))
+        })
+        .collect::<serde_json::Map<String, serde_json::Value>>()
+    };
+    let mut map = FxHashMap::default();
+    for (provider_id, provider) in providers {
+        let Some(models) = provider.get("models").and_then(|m| m.as_object()) else {
+            continue;
         };
-        let prompt = p("prompt");
-        let completion = p("completion");
-        let reasoning = {
-            let r = p("reasoning");
-            if r == 0.0 {
-                completion
+        for (model_id, m) in models {
+            let Some(cost) = m.get("cost").and_then(|c| c.as_object()) else {
+                continue;
+            };
+            let mut pricing = parse_rates(&per_token(cost), None);
+            if let Some(over) = cost.get("context_over_200k").and_then(|c| c.as_object()) {
+                let rates = parse_rates(&per_token(over), Some(&pricing));
+                pricing.tiers.push(PricingTier {
+                    above_prompt_tokens: 200_000,
+                    rates,
+                });
+            }
+            insert_pricing(&mut map, &format!("{}/{}", provider_id, model_id), pricing);
+        }
+    }
+    map
+}
+
+/// Per-token rates from an OpenRouter `pricing` object, or from a tier entry
+/// whose missing rates fall back to `base`.
+fn parse_rates(
+    pr: &serde_json::Map<String, serde_json::Value>,
+    base: Option<&ModelPricing>,
+) -> ModelPricing {
+    let p = |k: &str| -> Option<f64> {
+        pr.get(k)
+            .and_then(|v| {
+                v.as_str()
+                    .and_then(|s| s.parse().ok())
+                    .or_else(|| v.as_f64())
+            })
+            .map(|v: f64| v.max(0.0))
+    };
+    let or_base = |k: &str, f: fn(&ModelPricing) -> f64| p(k).or(base.map(f)).unwrap_or(0.0);
+    let prompt = or_base("prompt", |b| b.prompt);
+    let completion = or_base("completion", |b| b.completion);
+    let reasoning = match p("reasoning") {
+        Some(r) if r > 0.0 => r,
+        _ => completion,
+    };
+    let input_cache_write = or_base("input_cache_write", |b| b.input_cache_write);
+    let input_cache_write_1h = match p("input_cache_write_1h") {
+        Some(r) if r > 0.0 => r,
+        _ => base.map_or(input_cache_write, |b| {
+            // Keep the base ratio between 1h and 5m writes
+            if b.input_cache_write > 0.0 {
+                input_cache_write * b.input_cache_write_1h / b.input_cache_write
             } else {
-                r
+                b.input_cache_write_1h
             }
-        };
-        let pricing = ModelPricing {
-            prompt,
-            completion,
-            reasoning,
-            input_cache_read: p("input_cache_read"),
-            input_cache_write: p("input_cache_write"),
-        };
+        }),
+    };
+    ModelPricing {
+        prompt,
+        completion,
+        reasoning,
+        input_cache_read: or_base("input_cache_read", |b| b.input_cache_read),
+        input_cache_write,
+        input_cache_write_1h,
+        tiers: Vec::new(),
+    }
+}
+
+/// Date the bundled snapshot was taken.
+fn bundled_snapshot_date() -> &'static str {
+    static DATE: OnceLock<String> = OnceLock::new();
+    DATE.get_or_init(|| {
+        serde_json::from_str::<serde_json::Value>(BUNDLED_PRICING)
+            .ok()
+            .and_then(|b| b.get("snapshot_date")?.as_str().map(String::from))
+            .unwrap_or_default()
+    })
+}
 
-        let slug = id.rsplit('/').next().unwrap_or(id).to_ascii_lowercase();
-        let full = id.to_ascii_lowercase();
+/// Pricing from the disk cache, regardless of age.
+fn read_disk_cache(path: &std::path::Path) -> Option<PricingTable> {
+    let bytes = std::fs::read(path).ok()?;
+    let body = serde_json::from_slice::<serde_json::Value>(&bytes).ok()?;
+    let map = parse_body(&body);
+    if map.is_empty() {
+        return None;
+    }
+    let fetched = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(PricingTable {
+        map,
+        source: PricingSource::DiskCache { fetched },
+    })
+}
 
-        map.entry(full).or_insert(pricing);
-        map.entry(slug).or_insert(pricing);
+/// Pricing from the catalog file set in the config.
+fn read_pricing_file(path: &str) -> Option<PricingTable> {
+    let path = crate::config::expand_home(path);
+    let bytes = std::fs::read(&path).ok()?;
+    let body = serde_json::from_slice::<serde_json::Value>(&bytes).ok()?;
+    let map = parse_body(&body);
+    if map.is_empty() {
+        return None;
     }
-    map
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    Some(PricingTable {
+        map,
+        source: PricingSource::File { modified },
+    })
 }
 
-fn fetch_pricing() -> FxHashMap<String, ModelPricing> {
+fn fetch_pricing() -> PricingTable {
+    let config = &crate::config::get().pricing;
+    if config.source == PricingCatalog::File {
+        return config
+            .path
+            .as_deref()
+            .and_then(read_pricing_file)
+            .unwrap_or_else(|| PricingTable {
+                map: bundled_map(),
+                source: PricingSource::Bundled,
+            });
+    }
     let path = cache_path();
 
     // Use disk cache if fresh (< 1 day old)
     if cache_is_fresh() {
-        if let Ok(bytes) = std::fs::read(&path) {
-            if let Ok(body) = serde_json::from_slice::<serde_json::Value>(&bytes) {
-                let map = parse_body(&body);
-                if !map.is_empty() {
-                    return map;
-                }
-            }
+        if let Some(table) = read_disk_cache(&path) {
+            return table;
         }
     }
 
@@ -287,7 +747,10 @@ fn fetch_pricing() -> FxHashMap<String, ModelPricing> {
     let body = ureq::AgentBuilder::new()
         .timeout(std::time::Duration::from_secs(30))
         .build()
-        .get("https://openrouter.ai/api/v1/models")
+        .get(config.url.as_deref().unwrap_or(match config.source {
+            PricingCatalog::ModelsDev => "https://models.dev/api.json",
+            _ => "https://openrouter.ai/api/v1/models",
+        }))
         .call()
         .ok()
         .and_then(|r| r.into_json::<serde_json::Value>().ok());
@@ -299,19 +762,28 @@ fn fetch_pricing() -> FxHashMap<String, ModelPricing> {
                 let _ = std::fs::create_dir_all(parent);
             }
             let _ = std::fs::write(&path, serde_json::to_string(b).unwrap_or_default());
-            return map;
+            history::record_snapshot(b);
+            return PricingTable {
+                map,
+                source: PricingSource::Live,
+            };
         }
     }
 
     // Stale cache fallback
-    if let Ok(bytes) = std::fs::read(&path) {
-        if let Ok(b) = serde_json::from_slice::<serde_json::Value>(&bytes) {
-            let map = parse_body(&b);
-            if !map.is_empty() {
-                return map;
-            }
-        }
+    if let Some(table) = read_disk_cache(&path) {
+        return table;
+    }
+
+    // Bundled snapshot for machines that have never reached the catalog
+    PricingTable {
+        map: bundled_map(),
+        source: PricingSource::Bundled,
     }
+}
 
-    FxHashMap::default()
+fn bundled_map() -> FxHashMap<String, ModelPricing> {
+    serde_json::from_str::<serde_json::Value>(BUNDLED_PRICING)
+        .map(|b| parse_body(&b))
+        .unwrap_or_default()
 }
@@ -0,0 +1,179 @@
+//! Dated pricing snapshots, so past usage is priced at the rates of its time.
+//!
+//! Every successful catalog fetch whose rates differ from the latest snapshot
+//! is saved, trimmed to ids and pricing, as `openrouter-pricing-YYYY-MM-DD.json`
+//! (or `models-dev-pricing-…`) beside the main cache file. The
+//! bundled snapshot and the currently loaded table are dated too, so there is
+//! always at least one snapshot to pick from.
+
+use super::{cache_path, lookup_in_map, parse_body, ModelPricing, PricingSource};
+use chrono::{Local, NaiveDate};
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+struct Snapshot {
+    date: NaiveDate,
+    map: FxHashMap<String, ModelPricing>,
+}
+
+static HISTORY: OnceLock<Vec<Snapshot>> = OnceLock::new();
+
+/// Pricing per (snapshot index, model name).
+type Resolved = FxHashMap<(usize, Box<str>), Option<ModelPricing>>;
+
+/// Fuzzy matching is too slow to repeat for every day on every render.
+static RESOLVED: OnceLock<Mutex<Resolved>> = OnceLock::new();
+
+fn snapshot_prefix() -> String {
+    format!("{}-pricing-", super::catalog_slug())
+}
+
+fn snapshot_path(date: NaiveDate) -> PathBuf {
+    cache_path().with_file_name(format!(
+        "{}{}.json",
+        snapshot_prefix(),
+        date.format("%Y-%m-%d")
+    ))
+}
+
+/// Dated snapshot files on disk, oldest first.
+fn snapshot_files() -> Vec<(NaiveDate, PathBuf)> {
+    let Some(dir) = cache_path().parent().map(PathBuf::from) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let prefix = snapshot_prefix();
+    let mut files: Vec<(NaiveDate, PathBuf)> = entries
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            let date = name.strip_prefix(prefix.as_str())?.strip_suffix(".json")?;
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            Some((date, e.path()))
+        })
+        .collect();
+    files.sort_unstable_by_key(|(date, _)| *date);
+    files
+}
+
+fn read_snapshot(path: &std::path::Path) -> Option<FxHashMap<String, ModelPricing>> {
+    let bytes = std::fs::read(path).ok()?;
+    let body = serde_json::from_slice::<serde_json::Value>(&bytes).ok()?;
+    let map = parse_body(&body);
+    (!map.is_empty()).then_some(map)
+}
+
+/// A catalog response without everything but ids and prices.
+fn trim_body(body: &serde_json::Value) -> serde_json::Value {
+    if let Some(data) = body.get("data").and_then(|d| d.as_array()) {
+        return serde_json::json!({
+            "data": data
+                .iter()
+                .filter_map(|m| Some(serde_json::json!({
+                    "id": m.get("id")?,
+                    "pricing": m.get("pricing")?,
+                })))
+                .collect::<Vec<_>>(),
+        });
+    }
+    // models.dev: provider -> models -> model -> cost
+    let providers = body.as_object().into_iter().flatten();
+    serde_json::Value::Object(
+        providers
+            .filter_map(|(id, provider)| {
+                let models = provider.get("models")?.as_object()?;
+                let models: serde_json::Map<String, serde_json::Value> = models
+                    .iter()
+                    .filter_map(|(id, m)| {
+                        Some((id.clone(), serde_json::json!({ "cost": m.get("cost")? })))
+                    })
+                    .collect();
+                Some((id.clone(), serde_json::json!({ "models": models })))
+            })
+            .collect(),
+    )
+}
+
+/// Save today's rates from a live catalog response unless they match the
+/// latest snapshot.
+pub(super) fn record_snapshot(body: &serde_json::Value) {
+    let trimmed = trim_body(body);
+    let map = parse_body(&trimmed);
+    if map.is_empty() {
+        return;
+    }
+    let unchanged = snapshot_files()
+        .last()
+        .and_then(|(_, path)| read_snapshot(path))
+        .is_some_and(|latest| latest == map);
+    if unchanged {
+        return;
+    }
+    let _ = std::fs::write(
+        snapshot_path(Local::now().date_naive()),
+        serde_json::to_string(&trimmed).unwrap_or_default(),
+    );
+}
+
+/// Saved snapshots plus the loaded table and the bundled one, oldest first.
+/// A saved file wins over the others on the same date.
+fn history() -> &'static [Snapshot] {
+    HISTORY.get_or_init(|| {
+        let mut by_date: FxHashMap<NaiveDate, FxHashMap<String, ModelPricing>> =
+            FxHashMap::default();
+        for (date, path) in snapshot_files() {
+            if let Some(map) = read_snapshot(&path) {
+                by_date.insert(date, map);
+            }
+        }
+        let current = super::PRICING_CACHE.get_or_init(super::fetch_pricing);
+        if let Some(date) = current.source.date() {
+            by_date.entry(date).or_insert_with(|| current.map.clone());
+        }
+        if let Some(date) = PricingSource::Bundled.date() {
+            by_date.entry(date).or_insert_with(super::bundled_map);
+        }
+        let mut snapshots: Vec<Snapshot> = by_date
+            .into_iter()
+            .filter(|(_, map)| !map.is_empty())
+            .map(|(date, map)| Snapshot { date, map })
+            .collect();
+        snapshots.sort_unstable_by_key(|s| s.date);
+        snapshots
+    })
+}
+
+/// Pricing for a model on a `YYYY-MM-DD` day, from the snapshot closest to
+/// it that knows the model. Overrides apply to every day.
+pub fn pricing_on(model_name: &str, day: &str) -> Option<ModelPricing> {
+    if let Some(o) = super::find_override(model_name) {
+        return Some(o.pricing);
+    }
+    let alias = super::find_alias(model_name);
+    let model_name = alias.as_deref().unwrap_or(model_name);
+    let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") else {
+        return super::lookup_pricing(model_name);
+    };
+    let snapshots = history();
+    // Nearest first; on a tie the earlier snapshot was in effect
+    let mut order: Vec<usize> = (0..snapshots.len()).collect();
+    order.sort_by_key(|&i| {
+        (
+            (snapshots[i].date - date).num_days().abs(),
+            snapshots[i].date,
+        )
+    });
+
+    let resolved = RESOLVED.get_or_init(|| Mutex::new(FxHashMap::default()));
+    let mut resolved = resolved.lock();
+    order.into_iter().find_map(|i| {
+        resolved
+            .entry((i, model_name.into()))
+            .or_insert_with(|| lookup_in_map(&snapshots[i].map, model_name))
+            .clone()
+    })
+}
@@ -0,0 +1,366 @@
+//! User pricing overrides from `$XDG_CONFIG_HOME/opencode-stats-tui/pricing.json`.
+//!
+//! ```json
+//! { "models": {
+//!     "anthropic/claude-sonnet-4*": { "prompt": 2.4, "completion": 12, "cache_read": 0.24 },
+//!     "google/gemini-2.5-pro": {
+//!         "prompt": 1.25, "completion": 10,
+//!         "tiers": [{ "above": 200000, "prompt": 2.5, "completion": 15 }]
+//!     },
+//!     "my-llama-70b": { "prompt": 0.5, "completion": 0.5 },
+//!     "corp-proxy/sonnet": { "same_as": "anthropic/claude-sonnet-4" },
+//!     "ollama/*": "free",
+//!     "github-copilot/*": "subscription"
+//! } }
+//! ```
+//!
+//! Keys are model ids or globs (`*`, `?`) matched case-insensitively against
+//! both the `provider/model` id and the bare model slug. Rates are USD per
+//! million tokens; `reasoning` defaults to `completion`, cache rates to zero
+//! and `cache_write_1h` to `cache_write`. A tier applies to messages whose
+//! prompt (input plus cached tokens) exceeds `above`; rates it leaves out are
+//! the base ones. `same_as` prices a model as the given catalog entry.
+
+use super::{ModelPricing, PricingTier};
+use parking_lot::RwLock;
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Loaded rules; cleared when a mapping is pinned so the file is read again.
+static OVERRIDES: RwLock<Option<Arc<Vec<Rule>>>> = RwLock::new(None);
+
+/// How an override prices a model.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OverrideKind {
+    /// Custom per-token rates.
+    Rates,
+    /// Never billed, e.g. a self-hosted model.
+    Free,
+    /// Covered by a flat subscription.
+    Subscription,
+}
+
+impl OverrideKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Rates => "override",
+            Self::Free => "free",
+            Self::Subscription => "subscription",
+        }
+    }
+}
+
+/// Pricing taken from the override file.
+#[derive(Clone)]
+pub struct PriceOverride {
+    pub pricing: ModelPricing,
+    pub kind: OverrideKind,
+    /// Key in the override file that matched.
+    pub pattern: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct OverrideFile {
+    models: FxHashMap<String, OverrideEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OverrideEntry {
+    Covered(Coverage),
+    Rates(OverrideRates),
+    Alias { same_as: String },
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Coverage {
+    Free,
+    Subscription,
+}
+
+/// Rates in USD per million tokens.
+#[derive(Deserialize)]
+struct OverrideRates {
+    prompt: f64,
+    completion: f64,
+    #[serde(default)]
+    reasoning: Option<f64>,
+    #[serde(default)]
+    cache_read: f64,
+    #[serde(default)]
+    cache_write: f64,
+    #[serde(default)]
+    cache_write_1h: Option<f64>,
+    #[serde(default)]
+    tiers: Vec<OverrideTier>,
+}
+
+/// Long-prompt rates; unset ones keep the base rate.
+#[derive(Deserialize)]
+struct OverrideTier {
+    above: u64,
+    prompt: Option<f64>,
+    completion: Option<f64>,
+    reasoning: Option<f64>,
+    cache_read: Option<f64>,
+    cache_write: Option<f64>,
+    cache_write_1h: Option<f64>,
+}
+
+struct Rule {
+    pattern: String,
+    is_glob: bool,
+    target: RuleTarget,
+}
+
+enum RuleTarget {
+    Price(PriceOverride),
+    /// Catalog key to price the model as.
+    Alias(String),
+}
+
+impl Rule {
+    fn new(pattern: &str, entry: &OverrideEntry) -> Self {
+        let pattern = pattern.trim().to_ascii_lowercase();
+        let per_token = |usd_per_m: f64| usd_per_m.max(0.0) / 1_000_000.0;
+        let target = match entry {
+            OverrideEntry::Alias { same_as } => RuleTarget::Alias(same_as.trim().to_string()),
+            OverrideEntry::Covered(coverage) => RuleTarget::Price(PriceOverride {
+                pricing: ModelPricing {
+                    prompt: 0.0,
+                    completion: 0.0,
+                    reasoning: 0.0,
+                    input_cache_read: 0.0,
+                    input_cache_write: 0.0,
+                    input_cache_write_1h: 0.0,
+                    tiers: Vec::new(),
+                },
+                kind: match coverage {
+                    Coverage::Free => OverrideKind::Free,
+                    Coverage::Subscription => OverrideKind::Subscription,
+                },
+                pattern: pattern.clone(),
+            }),
+            OverrideEntry::Rates(r) => {
+                let mut pricing = ModelPricing {
+                    prompt: per_token(r.prompt),
+                    completion: per_token(r.completion),
+                    reasoning: per_token(r.reasoning.unwrap_or(r.completion)),
+                    input_cache_read: per_token(r.cache_read),
+                    input_cache_write: per_token(r.cache_write),
+                    input_cache_write_1h: per_token(r.cache_write_1h.unwrap_or(r.cache_write)),
+                    tiers: Vec::new(),
+                };
+                let mut tiers: Vec<PricingTier> = r
+                    .tiers
+                    .iter()
+                    .map(|t| {
+                        let or = |v: Option<f64>, base: f64| v.map_or(base, per_token);
+                        let completion = or(t.completion, pricing.completion);
+                        PricingTier {
+                            above_prompt_tokens: t.above,
+                            rates: ModelPricing {
+                                prompt: or(t.prompt, pricing.prompt),
+                                completion,
+                                reasoning: or(t.reasoning.or(t.completion), pricing.reasoning),
+                                input_cache_read: or(t.cache_read, pricing.input_cache_read),
+                                input_cache_write: or(t.cache_write, pricing.input_cache_write),
+                                input_cache_write_1h: or(
+                                    t.cache_write_1h,
+                                    pricing.input_cache_write_1h,
+                                ),
+                                tiers: Vec::new(),
+                            },
+                        }
+                    })
+                    .collect();
+                tiers.sort_unstable_by_key(|t| t.above_prompt_tokens);
+                pricing.tiers = tiers;
+                RuleTarget::Price(PriceOverride {
+                    pricing,
+                    kind: OverrideKind::Rates,
+                    pattern: pattern.clone(),
+                })
+            }
+        };
+        Self {
+            is_glob: pattern.contains(['*', '?']),
+            pattern,
+            target,
+        }
+    }
+
+    /// Characters that must match literally; more means more specific.
+    fn literal_len(&self) -> usize {
+        self.pattern
+            .chars()
+            .filter(|c| !matches!(c, '*' | '?'))
+            .count()
+    }
+}
+
+/// Path of the override file.
+pub fn overrides_path() -> PathBuf {
+    crate::config::config_path().with_file_name("pricing.json")
+}
+
+/// Load the override file; call before the TUI takes over the terminal so a
+/// parse warning stays readable.
+pub fn init_overrides() {
+    rules();
+}
+
+/// Exact ids first, then globs from most to least specific.
+fn rules() -> Arc<Vec<Rule>> {
+    if let Some(rules) = OVERRIDES.read().as_ref() {
+        return Arc::clone(rules);
+    }
+    let rules = Arc::new(load_rules());
+    *OVERRIDES.write() = Some(Arc::clone(&rules));
+    rules
+}
+
+fn load_rules() -> Vec<Rule> {
+    let path = overrides_path();
+    let Ok(bytes) = std::fs::read(&path) else {
+        return Vec::new();
+    };
+    let file: OverrideFile = serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+        eprintln!("warning: ignoring {}: {}", path.display(), e);
+        OverrideFile::default()
+    });
+    let mut rules: Vec<Rule> = file
+        .models
+        .iter()
+        .map(|(pattern, entry)| Rule::new(pattern, entry))
+        .collect();
+    rules.sort_by(|a, b| {
+        a.is_glob
+            .cmp(&b.is_glob)
+            .then_with(|| b.literal_len().cmp(&a.literal_len()))
+            .then_with(|| a.pattern.cmp(&b.pattern))
+    });
+    rules
+}
+
+/// First rule matching a model id, on the full id or its slug.
+fn find_rule<T>(model_name: &str, f: impl FnOnce(&Rule) -> T) -> Option<T> {
+    let rules = rules();
+    if rules.is_empty() {
+        return None;
+    }
+    let input = model_name.trim().to_ascii_lowercase();
+    let slug = input.rsplit('/').next().unwrap_or(&input);
+    rules
+        .iter()
+        .find(|r| glob_match(&r.pattern, &input) || glob_match(&r.pattern, slug))
+        .map(f)
+}
+
+/// Override for a model id, matched on the full id or its slug.
+pub fn find_override(model_name: &str) -> Option<PriceOverride> {
+    find_rule(model_name, |r| match &r.target {
+        RuleTarget::Price(price) => Some(price.clone()),
+        RuleTarget::Alias(_) => None,
+    })
+    .flatten()
+}
+
+/// Catalog key a model is pinned to with `same_as`.
+pub fn find_alias(model_name: &str) -> Option<String> {
+    find_rule(model_name, |r| match &r.target {
+        RuleTarget::Alias(key) => Some(key.clone()),
+        RuleTarget::Price(_) => None,
+    })
+    .flatten()
+}
+
+/// Pin a model to a catalog key by adding a `same_as` entry to the override
+/// file, keeping everything else in it. An existing alias for the model is
+/// replaced; rates, `free` or `subscription` written for it are left alone and
+/// the pin is refused, as is a key the loaded catalog cannot resolve.
+pub fn pin_alias(model_name: &str, key: &str) -> std::io::Result<PathBuf> {
+    use std::io::{Error, ErrorKind};
+    // Resolved the way pricing resolves it, so a typo fails here and not later
+    let found = super::match_catalog_key(key);
+    if found.strategy == super::MatchStrategy::Unmatched {
+        let hint = found
+            .key
+            .map(|k| format!(" (closest: {})", k))
+            .unwrap_or_default();
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("unknown catalog key: {}{}", key, hint),
+        ));
+    }
+    let path = overrides_path();
+    let mut doc = match std::fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice::<serde_json::Value>(&bytes)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?,
+        Err(e) if e.kind() == ErrorKind::NotFound => serde_json::json!({}),
+        Err(e) => return Err(e),
+    };
+    let invalid = || Error::new(ErrorKind::InvalidData, "expected an object");
+    let models = doc
+        .as_object_mut()
+        .ok_or_else(invalid)?
+        .entry("models")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(invalid)?;
+    // Keys match case-insensitively, so an entry may be spelled differently
+    let existing = models
+        .iter()
+        .find(|(k, _)| k.trim().eq_ignore_ascii_case(model_name.trim()))
+        .map(|(k, v)| (k.clone(), v.get("same_as").is_some()));
+    let entry_key = match existing {
+        Some((k, true)) => k,
+        Some((k, false)) => {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} already has its own price in {}", k, path.display()),
+            ))
+        }
+        None => model_name.to_string(),
+    };
+    models.insert(entry_key, serde_json::json!({ "same_as": key }));
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Write beside the file and rename so a failed write keeps the old rules
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, serde_json::to_string_pretty(&doc)? + "\n")?;
+    std::fs::rename(&tmp, &path)?;
+    *OVERRIDES.write() = None;
+    Ok(path)
+}
+
+/// `*` matches any run of characters, `?` exactly one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            // Let the last `*` swallow one more character
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
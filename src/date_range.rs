@@ -0,0 +1,424 @@
+//! Date-range filtering over aggregated stats.
+
+use crate::stats::{DayStat, ModelDay, ModelUsage, Stats, Tokens, Totals};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Inclusive window of local dates. `None` bounds are open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+/// Named ranges offered by the TUI picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangePreset {
+    AllTime,
+    Today,
+    Last7Days,
+    Last30Days,
+    ThisMonth,
+    LastMonth,
+    ThisQuarter,
+    LastQuarter,
+    ThisYear,
+}
+
+impl RangePreset {
+    pub const ALL: [RangePreset; 9] = [
+        RangePreset::AllTime,
+        RangePreset::Today,
+        RangePreset::Last7Days,
+        RangePreset::Last30Days,
+        RangePreset::ThisMonth,
+        RangePreset::LastMonth,
+        RangePreset::ThisQuarter,
+        RangePreset::LastQuarter,
+        RangePreset::ThisYear,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RangePreset::AllTime => "All time",
+            RangePreset::Today => "Today",
+            RangePreset::Last7Days => "Last 7 days",
+            RangePreset::Last30Days => "Last 30 days",
+            RangePreset::ThisMonth => "This month",
+            RangePreset::LastMonth => "Last month",
+            RangePreset::ThisQuarter => "This quarter",
+            RangePreset::LastQuarter => "Last quarter",
+            RangePreset::ThisYear => "This year",
+        }
+    }
+
+    /// Resolve the preset relative to `today`.
+    pub fn range(self, today: NaiveDate) -> DateRange {
+        let month_start = |y: i32, m: u32| NaiveDate::from_ymd_opt(y, m, 1);
+        let prev_month = |y: i32, m: u32| if m == 1 { (y - 1, 12) } else { (y, m - 1) };
+        let (y, m) = (today.year(), today.month());
+        let q_month = (m - 1) / 3 * 3 + 1;
+        let (since, until) = match self {
+            RangePreset::AllTime => (None, None),
+            RangePreset::Today => (Some(today), Some(today)),
+            RangePreset::Last7Days => (Some(today - Duration::days(6)), Some(today)),
+            RangePreset::Last30Days => (Some(today - Duration::days(29)), Some(today)),
+            RangePreset::ThisMonth => (month_start(y, m), Some(today)),
+            RangePreset::LastMonth => {
+                let (py, pm) = prev_month(y, m);
+                let this = month_start(y, m);
+                (month_start(py, pm), this.map(|d| d - Duration::days(1)))
+            }
+            RangePreset::ThisQuarter => (month_start(y, q_month), Some(today)),
+            RangePreset::LastQuarter => {
+                let this = month_start(y, q_month);
+                let (py, pm) = if q_month == 1 {
+                    (y - 1, 10)
+                } else {
+                    (y, q_month - 3)
+                };
+                (month_start(py, pm), this.map(|d| d - Duration::days(1)))
+            }
+            RangePreset::ThisYear => (month_start(y, 1), Some(today)),
+        };
+        DateRange { since, until }
+    }
+}
+
+impl DateRange {
+    #[inline]
+    pub fn is_all(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    /// True if the `YYYY-MM-DD` day key falls inside the window.
+    pub fn contains(&self, day: &str) -> bool {
+        if self.is_all() {
+            return true;
+        }
+        let Ok(d) = NaiveDate::parse_from_str(day, "%Y-%m-%d") else {
+            return false;
+        };
+        self.since.is_none_or(|s| d >= s) && self.until.is_none_or(|u| d <= u)
+    }
+
+    /// Human readable window, e.g. `2025-07-01 → 2025-09-30`.
+    pub fn label(&self) -> String {
+        let fmt = |d: Option<NaiveDate>| d.map(|d| d.format("%Y-%m-%d").to_string());
+        match (fmt(self.since), fmt(self.until)) {
+            (None, None) => "All time".to_string(),
+            (Some(s), Some(u)) if s == u => s,
+            (Some(s), Some(u)) => format!("{} → {}", s, u),
+            (Some(s), None) => format!("since {}", s),
+            (None, Some(u)) => format!("until {}", u),
+        }
+    }
+
+    /// Parse a `since..until` expression where either side may be empty.
+    pub fn parse_span(s: &str) -> Option<Self> {
+        let (since, until) = s.split_once("..").unwrap_or((s, s));
+        let side = |v: &str| -> Option<Option<NaiveDate>> {
+            let v = v.trim();
+            if v.is_empty() {
+                Some(None)
+            } else {
+                parse_date(v).map(Some)
+            }
+        };
+        let range = DateRange {
+            since: side(since)?,
+            until: side(until)?,
+        };
+        range.is_valid().then_some(range)
+    }
+
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        match (self.since, self.until) {
+            (Some(s), Some(u)) => s <= u,
+            _ => true,
+        }
+    }
+
+    /// Strip `--since`/`--until` from `args` and build the range.
+    pub fn take_from_args(args: &mut Vec<String>) -> Result<Self, String> {
+        let mut range = DateRange::default();
+        let mut i = 0;
+        while i < args.len() {
+            let (flag, inline) = match args[i].split_once('=') {
+                Some((f, v)) => (f.to_string(), Some(v.to_string())),
+                None => (args[i].clone(), None),
+            };
+            if flag != "--since" && flag != "--until" {
+                i += 1;
+                continue;
+            }
+            args.remove(i);
+            let value = match inline {
+                Some(v) => v,
+                None if i < args.len() => args.remove(i),
+                None => return Err(format!("{} expects a date", flag)),
+            };
+            let date = parse_date(&value).ok_or_else(|| {
+                format!(
+                    "invalid date for {}: {} (use YYYY-MM-DD, today or Nd)",
+                    flag, value
+                )
+            })?;
+            if flag == "--since" {
+                range.since = Some(date);
+            } else {
+                range.until = Some(date);
+            }
+        }
+        if !range.is_valid() {
+            return Err("--since must not be after --until".to_string());
+        }
+        Ok(range)
+    }
+}
+
+/// Parse `YYYY-MM-DD`, `today`, `yesterday` or `Nd` (N days ago).
+pub fn parse_date(s: &str) -> Option<NaiveDate> {
+    let s = s.trim();
+    let today = Local::now().date_naive();
+    match s {
+        "today" => return Some(today),
+        "yesterday" => return Some(today - Duration::days(1)),
+        _ => {}
+    }
+    if let Some(n) = s.strip_suffix('d').and_then(|n| n.parse::<i64>().ok()) {
+        return Duration::try_days(n).and_then(|d| today.checked_sub_signed(d));
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+}
+
+// ============================================================================
+// Filtering
+// ============================================================================
+
+/// Subtract `src` from `dst`, stopping at zero.
+pub(crate) fn sub_tokens(dst: &mut Tokens, src: &Tokens) {
+    dst.input = dst.input.saturating_sub(src.input);
+    dst.output = dst.output.saturating_sub(src.output);
+    dst.reasoning = dst.reasoning.saturating_sub(src.reasoning);
+    dst.cache_read = dst.cache_read.saturating_sub(src.cache_read);
+    dst.cache_write = dst.cache_write.saturating_sub(src.cache_write);
+}
+
+/// Add one model day's counts into `dst`.
+pub(crate) fn add_model_day(dst: &mut ModelDay, src: &ModelDay) {
+    dst.messages += src.messages;
+    dst.cost += src.cost;
+    for (dst, src) in [
+        (&mut dst.tools, &src.tools),
+        (&mut dst.tool_errors, &src.tool_errors),
+        (&mut dst.agents, &src.agents),
+    ] {
+        for (k, v) in src {
+            *dst.entry(k.clone()).or_insert(0) += v;
+        }
+    }
+}
+
+/// Restrict `per_day` to the window.
+pub fn filter_days(
+    per_day: &FxHashMap<String, DayStat>,
+    range: &DateRange,
+) -> FxHashMap<String, DayStat> {
+    per_day
+        .iter()
+        .filter(|(day, _)| range.contains(day))
+        .map(|(day, stat)| (day.clone(), stat.clone()))
+        .collect()
+}
+
+/// Recompute totals from the (already filtered) per-day data.
+pub fn totals_from_days(per_day: &FxHashMap<String, DayStat>) -> Totals {
+    let mut totals = Totals::default();
+    for day in per_day.values() {
+        totals.messages += day.messages;
+        totals.prompts += day.prompts;
+        totals.cost += day.cost;
+        totals.tokens += day.tokens;
+        totals.diffs.additions += day.diffs.additions;
+        totals.diffs.deletions += day.diffs.deletions;
+        for (id, session) in &day.sessions {
+            if !totals.sessions.contains(id.as_str()) {
+                totals.sessions.insert(id.as_str().into());
+            }
+            for (tool, count) in &session.tools {
+                *totals.tools.entry(tool.clone()).or_insert(0) += count;
+            }
+            for (tool, count) in &session.tool_errors {
+                *totals.tool_errors.entry(tool.clone()).or_insert(0) += count;
+            }
+            for (tool, samples) in &session.tool_durations {
+                totals
+                    .tool_durations
+                    .entry(tool.clone())
+                    .or_default()
+                    .extend_from_slice(samples);
+            }
+        }
+    }
+    totals
+}
+
+/// Restrict model usage to the window by summing the days inside it.
+pub fn filter_models(
+    model_usage: &[ModelUsage],
+    per_day: &FxHashMap<String, DayStat>,
+    range: &DateRange,
+) -> Vec<ModelUsage> {
+    let mut models: Vec<ModelUsage> = model_usage
+        .iter()
+        .filter_map(|m| filter_model(m, per_day, range))
+        .collect();
+    models.sort_unstable_by_key(|m| std::cmp::Reverse(m.tokens.total()));
+    models
+}
+
+/// One model's usage inside the window, or `None` when it has none there.
+fn filter_model(
+    m: &ModelUsage,
+    per_day: &FxHashMap<String, DayStat>,
+    range: &DateRange,
+) -> Option<ModelUsage> {
+    let all: u64 = m.daily_tokens.values().sum();
+    let daily_tokens: FxHashMap<String, u64> = m
+        .daily_tokens
+        .iter()
+        .filter(|(day, _)| range.contains(day))
+        .map(|(day, v)| (day.clone(), *v))
+        .collect();
+    let window: u64 = daily_tokens.values().sum();
+    if all == 0 || window == 0 {
+        return None;
+    }
+    let daily: FxHashMap<String, ModelDay> = m
+        .daily
+        .iter()
+        .filter(|(day, _)| range.contains(day))
+        .map(|(day, d)| (day.clone(), d.clone()))
+        .collect();
+    let mut sum = ModelDay::default();
+    for d in daily.values() {
+        add_model_day(&mut sum, d);
+    }
+    let daily_split: FxHashMap<String, Tokens> = m
+        .daily_split
+        .iter()
+        .filter(|(day, _)| range.contains(day))
+        .map(|(day, t)| (day.clone(), *t))
+        .collect();
+
+    let mut sessions: FxHashSet<Box<str>> = FxHashSet::default();
+    for day in per_day.values() {
+        for (id, s) in &day.sessions {
+            if s.models.contains(&m.name) && !sessions.contains(id.as_str()) {
+                sessions.insert(id.as_str().into());
+            }
+        }
+    }
+
+    Some(ModelUsage {
+        name: m.name.clone(),
+        short_name: m.short_name.clone(),
+        provider: m.provider.clone(),
+        display_name: m.display_name.clone(),
+        messages: sum.messages,
+        sessions,
+        tokens: daily_split.values().fold(Tokens::default(), |mut acc, t| {
+            acc += *t;
+            acc
+        }),
+        tools: sum.tools,
+        tool_errors: sum.tool_errors,
+        agents: sum.agents,
+        daily_last_hour: m
+            .daily_last_hour
+            .iter()
+            .filter(|(day, _)| range.contains(day))
+            .map(|(day, h)| (day.clone(), *h))
+            .collect(),
+        daily_speed: m
+            .daily_speed
+            .iter()
+            .filter(|(day, _)| range.contains(day))
+            .map(|(day, samples)| (day.clone(), samples.clone()))
+            .collect(),
+        daily_messages: m
+            .daily_messages
+            .iter()
+            .filter(|(day, _)| range.contains(day))
+            .map(|(day, usage)| (day.clone(), usage.clone()))
+            .collect(),
+        daily_tokens,
+        daily_split,
+        daily,
+        by_source: m
+            .by_source
+            .iter()
+            .filter_map(|(source, part)| {
+                let mut filtered = filter_model(part, per_day, range)?;
+                filtered.sessions.retain(|id| part.sessions.contains(id));
+                Some((*source, filtered))
+            })
+            .collect(),
+        cost: sum.cost,
+    })
+}
+
+/// Apply the window to totals, per-day data and model usage.
+pub fn apply(
+    totals: &Totals,
+    per_day: &FxHashMap<String, DayStat>,
+    model_usage: &[ModelUsage],
+    range: &DateRange,
+) -> (Totals, FxHashMap<String, DayStat>, Vec<ModelUsage>) {
+    if range.is_all() {
+        return (totals.clone(), per_day.clone(), model_usage.to_vec());
+    }
+    let days = filter_days(per_day, range);
+    let totals = totals_from_days(&days);
+    let models = filter_models(model_usage, &days, range);
+    (totals, days, models)
+}
+
+/// Apply the window to a loaded `Stats` in place.
+pub fn apply_to_stats(stats: &mut Stats, range: &DateRange) {
+    if range.is_all() {
+        return;
+    }
+    let (totals, per_day, model_usage) =
+        apply(&stats.totals, &stats.per_day, &stats.model_usage, range);
+    stats.totals = totals;
+    stats.per_day = per_day;
+    stats.model_usage = model_usage;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::get_day;
+    use crate::storage::tests::{assert_close, at, collect, memory_backend, GPT};
+
+    #[test]
+    fn date_window_sums_recorded_days() {
+        let stats = collect(memory_backend());
+        let day2 = NaiveDate::parse_from_str(&get_day(Some(at(2, 0))), "%Y-%m-%d").unwrap();
+        let range = DateRange {
+            since: Some(day2),
+            until: Some(day2),
+        };
+        let (totals, days, models) =
+            apply(&stats.totals, &stats.per_day, &stats.model_usage, &range);
+        assert_eq!(totals.messages, 1);
+        assert_eq!(days.len(), 1);
+        assert_eq!(models.len(), 1);
+        assert_eq!(&*models[0].name, GPT);
+        assert_eq!(models[0].messages, 1);
+        assert_close(models[0].cost, 0.1);
+    }
+}
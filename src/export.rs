@@ -0,0 +1,701 @@
+//! Versioned JSON and CSV export of collected statistics.
+//!
+//! The export schema is decoupled from the internal (bincode-cached) types so
+//! that internal refactors do not break downstream consumers. Any breaking
+//! change to the layout below must bump `SCHEMA_VERSION`.
+
+use crate::cli::invalid_arg;
+use crate::date_range::DateRange;
+use crate::stats::{
+    format_active_duration, DayStat, ModelUsage, SessionStat, Stats, Tokens, Totals,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Version of the JSON export layout.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Borrowed view over the stats consumed by exporters.
+pub struct StatsView<'a> {
+    pub totals: &'a Totals,
+    pub per_day: &'a FxHashMap<String, DayStat>,
+    pub session_titles: &'a FxHashMap<Box<str>, String>,
+    pub parent_map: &'a FxHashMap<Box<str>, Box<str>>,
+    pub model_usage: &'a [ModelUsage],
+    pub session_message_files: &'a FxHashMap<String, FxHashSet<PathBuf>>,
+}
+
+impl<'a> StatsView<'a> {
+    pub fn from_stats(stats: &'a Stats) -> Self {
+        Self {
+            totals: &stats.totals,
+            per_day: &stats.per_day,
+            session_titles: &stats.session_titles,
+            parent_map: &stats.parent_map,
+            model_usage: &stats.model_usage,
+            session_message_files: &stats.session_message_files,
+        }
+    }
+}
+
+// ============================================================================
+// Export Schema
+// ============================================================================
+
+/// Root document of a JSON export.
+#[derive(Serialize)]
+pub struct ExportDocument {
+    /// Layout version, see `SCHEMA_VERSION`.
+    pub schema_version: u32,
+    /// Local time the export was produced (RFC 3339).
+    pub generated_at: String,
+    pub totals: ExportTotals,
+    /// One entry per active day, sorted ascending by date.
+    pub days: Vec<ExportDay>,
+    /// One entry per session (merged across days), sorted by first activity.
+    pub sessions: Vec<ExportSession>,
+    /// One entry per model, sorted by total tokens descending.
+    pub models: Vec<ExportModel>,
+    /// One entry per agent name (merged across sessions), sorted by messages descending.
+    pub agents: Vec<ExportAgent>,
+}
+
+/// Token counts by type.
+#[derive(Serialize, Clone, Copy)]
+pub struct ExportTokens {
+    pub input: u64,
+    pub output: u64,
+    pub reasoning: u64,
+    pub cache_read: u64,
+    pub cache_write: u64,
+    pub total: u64,
+}
+
+#[derive(Serialize)]
+pub struct ExportTotals {
+    pub sessions: u64,
+    pub messages: u64,
+    pub prompts: u64,
+    pub tokens: ExportTokens,
+    pub lines_added: u64,
+    pub lines_removed: u64,
+    /// Cost in USD.
+    pub cost: f64,
+    /// Tool name -> call count.
+    pub tools: BTreeMap<String, u64>,
+}
+
+#[derive(Serialize)]
+pub struct ExportDay {
+    /// Local date, `YYYY-MM-DD`.
+    pub date: String,
+    pub messages: u64,
+    pub prompts: u64,
+    pub tokens: ExportTokens,
+    pub lines_added: u64,
+    pub lines_removed: u64,
+    pub cost: f64,
+    /// Ids of sessions active on this day.
+    pub session_ids: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExportSession {
+    pub id: String,
+    pub title: String,
+    /// Tool that recorded the session (`opencode`, `claude-code`).
+    pub source: &'static str,
+    /// Parent session id for subagent sessions.
+    pub parent_id: Option<String>,
+    /// Project root (`path.root`), empty when unknown.
+    pub project: String,
+    pub cwd: String,
+    /// Unix epoch milliseconds, `null` when unknown.
+    pub first_activity_ms: Option<i64>,
+    pub last_activity_ms: Option<i64>,
+    pub active_duration_ms: i64,
+    pub messages: u64,
+    pub prompts: u64,
+    pub tokens: ExportTokens,
+    pub lines_added: u64,
+    pub lines_removed: u64,
+    pub cost: f64,
+    pub models: Vec<String>,
+    pub tools: BTreeMap<String, u64>,
+    /// Local dates the session was active on.
+    pub days: Vec<String>,
+    pub agents: Vec<ExportSessionAgent>,
+}
+
+#[derive(Serialize)]
+pub struct ExportSessionAgent {
+    pub name: String,
+    pub is_main: bool,
+    pub models: Vec<String>,
+    pub messages: u64,
+    pub tokens: ExportTokens,
+    pub active_duration_ms: i64,
+}
+
+#[derive(Serialize)]
+pub struct ExportModel {
+    /// Full `provider/model` id.
+    pub id: String,
+    pub provider: String,
+    pub name: String,
+    pub messages: u64,
+    pub sessions: u64,
+    pub tokens: ExportTokens,
+    pub cost: f64,
+    pub tools: BTreeMap<String, u64>,
+    /// Agent name -> assistant message count.
+    pub agents: BTreeMap<String, u64>,
+    /// Local date -> total tokens.
+    pub daily_tokens: BTreeMap<String, u64>,
+}
+
+#[derive(Serialize)]
+pub struct ExportAgent {
+    pub name: String,
+    pub messages: u64,
+    pub sessions: u64,
+    pub tokens: ExportTokens,
+    pub models: Vec<String>,
+    pub active_duration_ms: i64,
+}
+
+impl From<&Tokens> for ExportTokens {
+    fn from(t: &Tokens) -> Self {
+        Self {
+            input: t.input,
+            output: t.output,
+            reasoning: t.reasoning,
+            cache_read: t.cache_read,
+            cache_write: t.cache_write,
+            total: t.total(),
+        }
+    }
+}
+
+// ============================================================================
+// Session Merging
+// ============================================================================
+
+/// A session merged across all days it was active on.
+pub(crate) struct MergedSession {
+    pub stat: SessionStat,
+    pub days: Vec<String>,
+}
+
+/// Merge per-day session slices into one entry per session id.
+pub(crate) fn merge_sessions(per_day: &FxHashMap<String, DayStat>) -> Vec<MergedSession> {
+    let mut dates: Vec<&String> = per_day.keys().collect();
+    dates.sort_unstable();
+
+    let mut merged: FxHashMap<&str, MergedSession> = FxHashMap::default();
+    for date in dates {
+        for (id, s) in &per_day[date].sessions {
+            let Some(entry) = merged.get_mut(id.as_str()) else {
+                let mut stat = s.as_ref().clone();
+                stat.original_session_id = None;
+                stat.is_continuation = false;
+                merged.insert(
+                    id.as_str(),
+                    MergedSession {
+                        stat,
+                        days: vec![date.clone()],
+                    },
+                );
+                continue;
+            };
+            entry.days.push(date.clone());
+            let m = &mut entry.stat;
+            m.messages += s.messages;
+            m.prompts += s.prompts;
+            m.cost += s.cost;
+            m.tokens += s.tokens;
+            m.diffs.additions += s.diffs.additions;
+            m.diffs.deletions += s.diffs.deletions;
+            m.models.extend(s.models.iter().cloned());
+            for (tool, count) in &s.tools {
+                *m.tools.entry(tool.clone()).or_insert(0) += count;
+            }
+            m.first_activity = m.first_activity.min(s.first_activity);
+            m.last_activity = m.last_activity.max(s.last_activity);
+            m.active_duration_ms += s.active_duration_ms;
+            if m.path_root.is_empty() {
+                m.path_root = s.path_root.clone();
+            }
+            if m.path_cwd.is_empty() {
+                m.path_cwd = s.path_cwd.clone();
+            }
+            for agent in &s.agents {
+                if let Some(a) = m.agents.iter_mut().find(|a| a.name == agent.name) {
+                    a.messages += agent.messages;
+                    a.tokens += agent.tokens;
+                    a.models.extend(agent.models.iter().cloned());
+                    a.first_activity = a.first_activity.min(agent.first_activity);
+                    a.last_activity = a.last_activity.max(agent.last_activity);
+                    a.active_duration_ms += agent.active_duration_ms;
+                } else {
+                    m.agents.push(agent.clone());
+                }
+            }
+        }
+    }
+
+    let mut sessions: Vec<MergedSession> = merged.into_values().collect();
+    sessions.sort_unstable_by(|a, b| {
+        a.stat
+            .first_activity
+            .cmp(&b.stat.first_activity)
+            .then_with(|| a.stat.id.cmp(&b.stat.id))
+    });
+    sessions
+}
+
+#[inline]
+fn activity_ms(ts: i64) -> Option<i64> {
+    (ts > 0 && ts != i64::MAX).then_some(ts)
+}
+
+fn sorted_strings<'a>(items: impl Iterator<Item = &'a Box<str>>) -> Vec<String> {
+    let mut v: Vec<String> = items.map(|s| s.to_string()).collect();
+    v.sort_unstable();
+    v
+}
+
+fn sorted_counts(map: &FxHashMap<Box<str>, u64>) -> BTreeMap<String, u64> {
+    map.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+}
+
+// ============================================================================
+// Document Building
+// ============================================================================
+
+/// Build the export document from loaded stats.
+pub fn build_document(view: &StatsView) -> ExportDocument {
+    let StatsView {
+        totals,
+        per_day,
+        session_titles,
+        parent_map,
+        model_usage,
+        session_message_files,
+    } = *view;
+    let export_totals = ExportTotals {
+        sessions: totals.sessions.len() as u64,
+        messages: totals.messages,
+        prompts: totals.prompts,
+        tokens: ExportTokens::from(&totals.tokens),
+        lines_added: totals.diffs.additions,
+        lines_removed: totals.diffs.deletions,
+        cost: totals.display_cost(),
+        tools: sorted_counts(&totals.tools),
+    };
+
+    let mut days: Vec<ExportDay> = per_day
+        .iter()
+        .map(|(date, d)| {
+            let mut session_ids: Vec<String> = d.sessions.keys().cloned().collect();
+            session_ids.sort_unstable();
+            ExportDay {
+                date: date.clone(),
+                messages: d.messages,
+                prompts: d.prompts,
+                tokens: ExportTokens::from(&d.tokens),
+                lines_added: d.diffs.additions,
+                lines_removed: d.diffs.deletions,
+                cost: d.display_cost(),
+                session_ids,
+            }
+        })
+        .collect();
+    days.sort_unstable_by(|a, b| a.date.cmp(&b.date));
+
+    let merged = merge_sessions(per_day);
+
+    let mut agents: FxHashMap<Box<str>, (ExportAgentAcc, Vec<Box<str>>)> = FxHashMap::default();
+    let sessions: Vec<ExportSession> = merged
+        .into_iter()
+        .map(|MergedSession { stat: s, days }| {
+            for a in &s.agents {
+                let (acc, models) = agents.entry(a.name.clone()).or_default();
+                acc.messages += a.messages;
+                acc.sessions += 1;
+                acc.tokens += a.tokens;
+                acc.active_duration_ms += a.active_duration_ms;
+                models.extend(a.models.iter().cloned());
+            }
+            ExportSession {
+                title: session_titles
+                    .get(s.id.as_ref())
+                    .cloned()
+                    .unwrap_or_default(),
+                source: crate::import::session_source(session_message_files, &s.id).label(),
+                parent_id: parent_map.get(s.id.as_ref()).map(|p| p.to_string()),
+                project: s.path_root.to_string(),
+                cwd: s.path_cwd.to_string(),
+                first_activity_ms: activity_ms(s.first_activity),
+                last_activity_ms: activity_ms(s.last_activity),
+                active_duration_ms: s.active_duration_ms,
+                messages: s.messages,
+                prompts: s.prompts,
+                tokens: ExportTokens::from(&s.tokens),
+                lines_added: s.diffs.additions,
+                lines_removed: s.diffs.deletions,
+                cost: s.display_cost(),
+                models: sorted_strings(s.models.iter()),
+                tools: sorted_counts(&s.tools),
+                days,
+                agents: s
+                    .agents
+                    .iter()
+                    .map(|a| ExportSessionAgent {
+                        name: a.name.to_string(),
+                        is_main: a.is_main,
+                        models: sorted_strings(a.models.iter()),
+                        messages: a.messages,
+                        tokens: ExportTokens::from(&a.tokens),
+                        active_duration_ms: a.active_duration_ms,
+                    })
+                    .collect(),
+                id: s.id.into_string(),
+            }
+        })
+        .collect();
+
+    let mut models: Vec<ExportModel> = model_usage
+        .iter()
+        .map(|m| ExportModel {
+            id: m.name.to_string(),
+            provider: m.provider.to_string(),
+            name: m.short_name.to_string(),
+            messages: m.messages,
+            sessions: m.sessions.len() as u64,
+            tokens: ExportTokens::from(&m.tokens),
+            cost: m.cost,
+            tools: sorted_counts(&m.tools),
+            agents: sorted_counts(&m.agents),
+            daily_tokens: m
+                .daily_tokens
+                .iter()
+                .map(|(k, v)| (k.clone(), *v))
+                .collect(),
+        })
+        .collect();
+    models.sort_unstable_by(|a, b| {
+        b.tokens
+            .total
+            .cmp(&a.tokens.total)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    let mut agents: Vec<ExportAgent> = agents
+        .into_iter()
+        .map(|(name, (acc, mut models))| {
+            models.sort_unstable();
+            models.dedup();
+            ExportAgent {
+                name: name.into_string(),
+                messages: acc.messages,
+                sessions: acc.sessions,
+                tokens: ExportTokens::from(&acc.tokens),
+                models: models.into_iter().map(|m| m.into_string()).collect(),
+                active_duration_ms: acc.active_duration_ms,
+            }
+        })
+        .collect();
+    agents.sort_unstable_by(|a, b| {
+        b.messages
+            .cmp(&a.messages)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    ExportDocument {
+        schema_version: SCHEMA_VERSION,
+        generated_at: chrono::Local::now().to_rfc3339(),
+        totals: export_totals,
+        days,
+        sessions,
+        models,
+        agents,
+    }
+}
+
+#[derive(Default)]
+struct ExportAgentAcc {
+    messages: u64,
+    sessions: u64,
+    tokens: Tokens,
+    active_duration_ms: i64,
+}
+
+/// Serialize the document as pretty-printed JSON.
+pub fn write_json(out: &mut dyn Write, doc: &ExportDocument) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, doc)?;
+    writeln!(out)
+}
+
+/// Default file name for an export written from the TUI.
+pub fn default_export_path(suffix: &str) -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    PathBuf::from(format!("opencode-stats-{}{}", stamp, suffix))
+}
+
+/// Create `path` (and its parent directories) and hand a buffered writer to `f`.
+fn write_file(path: &Path, f: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+    f(&mut file)?;
+    file.flush()
+}
+
+/// Write the document to `path`, creating parent directories as needed.
+pub fn write_json_file(path: &Path, doc: &ExportDocument) -> io::Result<()> {
+    write_file(path, |out| write_json(out, doc))
+}
+
+// ============================================================================
+// CSV Export
+// ============================================================================
+
+/// Tables available as CSV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvTable {
+    Days,
+    Sessions,
+    Models,
+    Tools,
+}
+
+impl CsvTable {
+    pub const ALL: [CsvTable; 4] = [
+        CsvTable::Days,
+        CsvTable::Sessions,
+        CsvTable::Models,
+        CsvTable::Tools,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CsvTable::Days => "days",
+            CsvTable::Sessions => "sessions",
+            CsvTable::Models => "models",
+            CsvTable::Tools => "tools",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == s)
+    }
+}
+
+/// Quote a CSV field per RFC 4180 when it contains a delimiter, quote or newline.
+fn csv_field(s: &str) -> std::borrow::Cow<'_, str> {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\"")).into()
+    } else {
+        s.into()
+    }
+}
+
+fn write_csv_row<S: AsRef<str>>(out: &mut dyn Write, fields: &[S]) -> io::Result<()> {
+    for (i, f) in fields.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        out.write_all(csv_field(f.as_ref()).as_bytes())?;
+    }
+    out.write_all(b"\r\n")
+}
+
+fn token_fields(t: &Tokens) -> [String; 6] {
+    [
+        t.input.to_string(),
+        t.output.to_string(),
+        t.reasoning.to_string(),
+        t.cache_read.to_string(),
+        t.cache_write.to_string(),
+        t.total().to_string(),
+    ]
+}
+
+const TOKEN_HEADERS: [&str; 6] = [
+    "input",
+    "output",
+    "reasoning",
+    "cache_read",
+    "cache_write",
+    "total_tokens",
+];
+
+/// Write one table as CSV with a header row.
+pub fn write_csv(out: &mut dyn Write, table: CsvTable, view: &StatsView) -> io::Result<()> {
+    match table {
+        CsvTable::Days => {
+            let mut header = vec!["date", "messages", "prompts"];
+            header.extend(TOKEN_HEADERS);
+            header.extend(["cost", "additions", "deletions"]);
+            write_csv_row(out, &header)?;
+
+            let mut dates: Vec<&String> = view.per_day.keys().collect();
+            dates.sort_unstable();
+            for date in dates {
+                let d = &view.per_day[date];
+                let mut row = vec![date.clone(), d.messages.to_string(), d.prompts.to_string()];
+                row.extend(token_fields(&d.tokens));
+                row.extend([
+                    format!("{:.4}", d.display_cost()),
+                    d.diffs.additions.to_string(),
+                    d.diffs.deletions.to_string(),
+                ]);
+                write_csv_row(out, &row)?;
+            }
+        }
+        CsvTable::Sessions => {
+            write_csv_row(
+                out,
+                &[
+                    "id",
+                    "title",
+                    "project",
+                    "models",
+                    "duration_ms",
+                    "duration",
+                    "cost",
+                ],
+            )?;
+            for MergedSession { stat: s, .. } in merge_sessions(view.per_day) {
+                let title = view
+                    .session_titles
+                    .get(s.id.as_ref())
+                    .map(String::as_str)
+                    .unwrap_or("");
+                let models = sorted_strings(s.models.iter()).join(";");
+                write_csv_row(
+                    out,
+                    &[
+                        s.id.as_ref(),
+                        title,
+                        s.path_root.as_ref(),
+                        models.as_str(),
+                        s.active_duration_ms.to_string().as_str(),
+                        format_active_duration(s.active_duration_ms).as_str(),
+                        format!("{:.4}", s.display_cost()).as_str(),
+                    ],
+                )?;
+            }
+        }
+        CsvTable::Models => {
+            let mut header = vec!["model", "provider", "messages", "sessions"];
+            header.extend(TOKEN_HEADERS);
+            header.push("cost");
+            write_csv_row(out, &header)?;
+
+            let mut models: Vec<&ModelUsage> = view.model_usage.iter().collect();
+            models.sort_unstable_by_key(|m| std::cmp::Reverse(m.tokens.total()));
+            for m in models {
+                let mut row = vec![
+                    m.name.to_string(),
+                    m.provider.to_string(),
+                    m.messages.to_string(),
+                    m.sessions.len().to_string(),
+                ];
+                row.extend(token_fields(&m.tokens));
+                row.push(format!("{:.4}", m.cost));
+                write_csv_row(out, &row)?;
+            }
+        }
+        CsvTable::Tools => {
+            write_csv_row(out, &["tool", "calls"])?;
+            let mut tools: Vec<(&Box<str>, &u64)> = view.totals.tools.iter().collect();
+            tools.sort_unstable_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            for (name, count) in tools {
+                write_csv_row(out, &[name.as_ref(), count.to_string().as_str()])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write one table as a CSV file.
+pub fn write_csv_file(path: &Path, table: CsvTable, view: &StatsView) -> io::Result<()> {
+    write_file(path, |out| write_csv(out, table, view))
+}
+
+/// Write every CSV table next to each other using the default naming scheme.
+pub fn write_all_csv_files(view: &StatsView) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::with_capacity(CsvTable::ALL.len());
+    for table in CsvTable::ALL {
+        let path = default_export_path(&format!("-{}.csv", table.name()));
+        write_csv_file(&path, table, view)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+// ============================================================================
+// CLI
+// ============================================================================
+
+/// Run the `export` subcommand.
+pub fn run(args: &[String], range: &DateRange) -> io::Result<()> {
+    let mut output: Option<PathBuf> = None;
+    let mut csv: Option<CsvTable> = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--csv" => {
+                csv = Some(
+                    iter.next()
+                        .and_then(|t| CsvTable::parse(t))
+                        .ok_or_else(|| {
+                            invalid_arg("--csv expects one of: days, sessions, models, tools")
+                        })?,
+                );
+            }
+            "-o" | "--output" => {
+                output = Some(
+                    iter.next()
+                        .map(PathBuf::from)
+                        .ok_or_else(|| invalid_arg("--output expects a path"))?,
+                );
+            }
+            "-h" | "--help" => {
+                println!(
+                    "Usage: opencode-stats-tui export [--csv days|sessions|models|tools] [--output FILE] [--since DATE] [--until DATE]"
+                );
+                println!(
+                    "Writes a JSON export (schema version {}) or one CSV table to FILE or stdout.",
+                    SCHEMA_VERSION
+                );
+                return Ok(());
+            }
+            other => return Err(invalid_arg(&format!("unknown argument: {}", other))),
+        }
+    }
+
+    let mut stats = crate::stats_cache::load_stats();
+    crate::date_range::apply_to_stats(&mut stats, range);
+    let view = StatsView::from_stats(&stats);
+    match (csv, output) {
+        (Some(table), Some(path)) => write_csv_file(&path, table, &view),
+        (Some(table), None) => {
+            let mut out = io::stdout().lock();
+            write_csv(&mut out, table, &view)?;
+            out.flush()
+        }
+        (None, Some(path)) => write_json_file(&path, &build_document(&view)),
+        (None, None) => {
+            let mut out = io::stdout().lock();
+            write_json(&mut out, &build_document(&view))?;
+            out.flush()
+        }
+    }
+}
@@ -0,0 +1,552 @@
+//! Transcripts from other coding agents, mapped onto opencode messages.
+//!
+//! Each importer turns its log files into the same `Message` and `PartData`
+//! records the opencode loaders produce. They are kept in a `MemoryBackend`
+//! under virtual `import://<source>/<message id>` paths and registered as one
+//! more storage backend, so `collect_stats`, the chat loaders and the live
+//! watcher treat them like any other message.
+
+mod claude;
+mod codex;
+mod gemini;
+
+use crate::stats::{
+    DayStat, FileDiff, LenientString, Message, ModelData, ModelUsage, PartData, ToolState, Totals,
+};
+use crate::storage::{MemoryBackend, SessionRecord, StorageBackend};
+use parking_lot::RwLock;
+use rayon::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const IMPORT_PREFIX: &str = "import://";
+
+static STORE: OnceLock<RwLock<Store>> = OnceLock::new();
+
+/// Tool that produced a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Source {
+    OpenCode,
+    ClaudeCode,
+    Codex,
+    Gemini,
+}
+
+impl Source {
+    /// Imported sources, in display order.
+    pub const IMPORTED: [Source; 3] = [Source::ClaudeCode, Source::Codex, Source::Gemini];
+
+    /// Every source, opencode first.
+    pub const ALL: [Source; 4] = [
+        Source::OpenCode,
+        Source::ClaudeCode,
+        Source::Codex,
+        Source::Gemini,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Source::OpenCode => "opencode",
+            Source::ClaudeCode => "claude-code",
+            Source::Codex => "codex",
+            Source::Gemini => "gemini-cli",
+        }
+    }
+
+    /// Short tag for dense lists.
+    pub fn badge(self) -> &'static str {
+        match self {
+            Source::OpenCode => "OC",
+            Source::ClaudeCode => "CC",
+            Source::Codex => "CX",
+            Source::Gemini => "GM",
+        }
+    }
+
+    /// Path segment used in virtual message paths.
+    fn key(self) -> &'static str {
+        match self {
+            Source::OpenCode => "opencode",
+            Source::ClaudeCode => "claude",
+            Source::Codex => "codex",
+            Source::Gemini => "gemini",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::IMPORTED.into_iter().find(|s| s.key() == key)
+    }
+
+    /// Directory holding this tool's transcripts, if it exists.
+    fn dir(self) -> Option<PathBuf> {
+        let dir = match self {
+            Source::OpenCode => return None,
+            Source::ClaudeCode => claude::transcript_dir(),
+            Source::Codex => codex::session_dir(),
+            Source::Gemini => gemini::chat_dir(),
+        };
+        dir.is_dir().then_some(dir)
+    }
+
+    /// Source of a message path; anything not imported is opencode's own.
+    pub fn of_path(path: &Path) -> Self {
+        path.to_str()
+            .and_then(|p| p.strip_prefix(IMPORT_PREFIX))
+            .and_then(|rest| rest.split_once('/'))
+            .and_then(|(key, _)| Self::from_key(key))
+            .unwrap_or(Source::OpenCode)
+    }
+}
+
+/// Source of a session, taken from any of its message files.
+pub fn session_source(
+    session_message_files: &FxHashMap<String, FxHashSet<PathBuf>>,
+    session_id: &str,
+) -> Source {
+    session_message_files
+        .get(session_id)
+        .and_then(|files| files.iter().next())
+        .map(|p| Source::of_path(p))
+        .unwrap_or(Source::OpenCode)
+}
+
+// ============================================================================
+// Store
+// ============================================================================
+
+/// One imported message with its parts.
+pub(crate) struct ImportedMessage {
+    pub(crate) msg: Message,
+    pub(crate) parts: Vec<PartData>,
+}
+
+/// Messages and title parsed from one transcript file.
+#[derive(Default)]
+pub(crate) struct ParsedFile {
+    pub(crate) messages: Vec<ImportedMessage>,
+    pub(crate) title: Option<(Box<str>, String)>,
+}
+
+struct FileEntry {
+    mtime: u64,
+    size: u64,
+    ids: Vec<Box<str>>,
+    /// Session this file supplied the title for.
+    session: Option<Box<str>>,
+}
+
+#[derive(Default)]
+struct Store {
+    /// Set by the first `refresh`; stats loaded from the cache skip it.
+    scanned: bool,
+    files: FxHashMap<PathBuf, FileEntry>,
+    data: MemoryBackend,
+}
+
+impl Store {
+    fn remove_file(&mut self, path: &Path) {
+        let Some(entry) = self.files.remove(path) else {
+            return;
+        };
+        for id in entry.ids {
+            self.data.remove_message(&id);
+        }
+        if let Some(session) = entry.session {
+            self.data.remove_session(&session);
+        }
+    }
+}
+
+#[inline]
+fn store() -> &'static RwLock<Store> {
+    STORE.get_or_init(|| RwLock::new(Store::default()))
+}
+
+/// Populate the store on first use by the chat loaders.
+#[inline]
+fn ensure_scanned() {
+    if !store().read().scanned {
+        refresh();
+    }
+}
+
+#[inline]
+fn home() -> PathBuf {
+    PathBuf::from(env::var("HOME").unwrap_or_else(|_| ".".to_string()))
+}
+
+/// Existing transcript directories, for the live watcher.
+fn watch_dirs() -> Vec<PathBuf> {
+    Source::IMPORTED.iter().filter_map(|s| s.dir()).collect()
+}
+
+/// True for a transcript file inside one of the import directories.
+fn is_transcript(path: &Path) -> bool {
+    Source::IMPORTED
+        .iter()
+        .any(|&s| s.dir().is_some_and(|dir| path.starts_with(&dir)) && transcript_matches(s, path))
+}
+
+fn transcript_matches(source: Source, path: &Path) -> bool {
+    match source {
+        Source::OpenCode => false,
+        Source::ClaudeCode => path.extension().is_some_and(|e| e == "jsonl"),
+        Source::Codex => {
+            path.extension().is_some_and(|e| e == "jsonl")
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("rollout-"))
+        }
+        Source::Gemini => {
+            path.extension().is_some_and(|e| e == "json")
+                && path
+                    .parent()
+                    .and_then(|d| d.file_name())
+                    .is_some_and(|d| d == "chats")
+        }
+    }
+}
+
+/// All transcript files, for cache validation.
+fn list_transcripts() -> Vec<(Source, PathBuf)> {
+    let mut out = Vec::new();
+    for source in Source::IMPORTED {
+        let Some(dir) = source.dir() else {
+            continue;
+        };
+        let mut stack = vec![(dir, 0)];
+        while let Some((dir, depth)) = stack.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    if depth < 4 {
+                        stack.push((path, depth + 1));
+                    }
+                } else if transcript_matches(source, &path) {
+                    out.push((source, path));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn file_meta(path: &Path) -> Option<(u64, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    Some((mtime, meta.len()))
+}
+
+fn parse(source: Source, path: &Path) -> ParsedFile {
+    let mut file = match source {
+        Source::OpenCode => ParsedFile::default(),
+        Source::ClaudeCode => claude::parse_file(path),
+        Source::Codex => codex::parse_file(path),
+        Source::Gemini => gemini::parse_file(path),
+    };
+    credit_prompt_models(&mut file.messages);
+    file
+}
+
+/// Prompts carry no model; credit each to the next reply by the same agent.
+fn credit_prompt_models(messages: &mut [ImportedMessage]) {
+    let mut reply_models: FxHashMap<String, ModelData> = FxHashMap::default();
+    for m in messages.iter_mut().rev() {
+        let msg = &mut m.msg;
+        let agent = msg.agent.as_ref().map(|a| a.0.clone()).unwrap_or_default();
+        match msg.role.as_ref().map(|r| r.as_str()) {
+            Some("assistant") => {
+                reply_models.insert(
+                    agent,
+                    ModelData {
+                        provider_id: msg.provider_id.clone(),
+                        model_id: msg.model_id.clone(),
+                    },
+                );
+            }
+            Some("user") if msg.model.is_none() => msg.model = reply_models.get(&agent).cloned(),
+            _ => {}
+        }
+    }
+}
+
+// ============================================================================
+// Shared parsing helpers
+// ============================================================================
+
+const TITLE_MAX_CHARS: usize = 80;
+
+#[inline]
+fn lenient(s: &str) -> Option<LenientString> {
+    Some(LenientString(s.to_string()))
+}
+
+fn parse_timestamp(ts: Option<&LenientString>) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(ts?.as_str())
+        .ok()
+        .map(|dt| dt.timestamp_millis())
+}
+
+fn text_part(text: &str) -> PartData {
+    PartData {
+        part_type: Some("text".to_string()),
+        text: Some(text.to_string()),
+        ..PartData::default()
+    }
+}
+
+fn thinking_part(thought: String) -> PartData {
+    PartData {
+        part_type: Some("thinking".to_string()),
+        thought: Some(thought),
+        ..PartData::default()
+    }
+}
+
+fn tool_part(name: &str, input: Option<&serde_json::Value>) -> PartData {
+    PartData {
+        part_type: Some("tool".to_string()),
+        tool: Some(name.to_string()),
+        state: input.and_then(tool_state),
+        ..PartData::default()
+    }
+}
+
+/// Rename tool input keys to the ones opencode uses.
+fn tool_state(input: &serde_json::Value) -> Option<ToolState> {
+    let serde_json::Value::Object(map) = input else {
+        return None;
+    };
+    let renamed: serde_json::Map<String, serde_json::Value> = map
+        .iter()
+        .map(|(k, v)| {
+            let key = match k.as_str() {
+                "file_path" | "notebook_path" | "absolute_path" => "filePath",
+                "old_string" => "oldStr",
+                "new_string" => "newStr",
+                other => other,
+            };
+            (key.to_string(), v.clone())
+        })
+        .collect();
+    Some(ToolState {
+        input: serde_json::from_value(serde_json::Value::Object(renamed)).ok(),
+        ..ToolState::default()
+    })
+}
+
+/// First line of the first text part, for a session title.
+fn first_line_title(parts: &[PartData]) -> Option<String> {
+    let text = parts.iter().find_map(|p| p.text.as_deref())?;
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+    // Slash commands and injected context are logged as XML-ish wrappers
+    if line.starts_with('<') {
+        return None;
+    }
+    Some(line.chars().take(TITLE_MAX_CHARS).collect())
+}
+
+#[inline]
+fn message_path(source: Source, id: &str) -> PathBuf {
+    PathBuf::from(format!("{}{}/{}", IMPORT_PREFIX, source.key(), id))
+}
+
+/// Re-read changed transcripts and return the virtual path of every imported message.
+fn refresh() -> Vec<PathBuf> {
+    let found = list_transcripts();
+    let mut store = store().write();
+    store.scanned = true;
+
+    let live: FxHashSet<&PathBuf> = found.iter().map(|(_, p)| p).collect();
+    let gone: Vec<PathBuf> = store
+        .files
+        .keys()
+        .filter(|p| !live.contains(p))
+        .cloned()
+        .collect();
+    for path in gone {
+        store.remove_file(&path);
+    }
+
+    let changed: Vec<(Source, PathBuf, u64, u64)> = found
+        .into_iter()
+        .filter_map(|(source, path)| {
+            let (mtime, size) = file_meta(&path)?;
+            let fresh = store
+                .files
+                .get(&path)
+                .is_some_and(|e| e.mtime == mtime && e.size == size);
+            (!fresh).then_some((source, path, mtime, size))
+        })
+        .collect();
+    let mut parsed: Vec<_> = changed
+        .into_par_iter()
+        .map(|(source, path, mtime, size)| {
+            let file = parse(source, &path);
+            (source, path, mtime, size, file)
+        })
+        .collect();
+    // Resumed sessions repeat earlier messages; the first file to claim an id keeps it
+    parsed.sort_unstable_by(|a, b| a.1.cmp(&b.1));
+    for (source, path, mtime, size, file) in parsed {
+        store.remove_file(&path);
+        let ids: Vec<Box<str>> = file
+            .messages
+            .into_iter()
+            .filter_map(|m| {
+                let id = m.msg.id.as_ref()?.as_str();
+                store
+                    .data
+                    .insert_message(message_path(source, id), m.msg, m.parts)
+            })
+            .collect();
+        let session = file.title.and_then(|(id, title)| {
+            let inserted = store.data.insert_session(SessionRecord {
+                id: id.clone(),
+                title,
+                parent_id: None,
+            });
+            inserted.then_some(id)
+        });
+        store.files.insert(
+            path,
+            FileEntry {
+                mtime,
+                size,
+                ids,
+                session,
+            },
+        );
+    }
+
+    store.data.list_messages()
+}
+
+/// Imported transcripts as a storage backend.
+///
+/// Transcripts are parsed whole, so any change to one means a rescan.
+pub(crate) struct ImportBackend;
+
+impl StorageBackend for ImportBackend {
+    fn list_sessions(&self) -> Vec<SessionRecord> {
+        ensure_scanned();
+        store().read().data.list_sessions()
+    }
+
+    fn list_messages(&self) -> Vec<PathBuf> {
+        refresh()
+    }
+
+    fn owns_message(&self, path: &Path) -> bool {
+        path.to_str().is_some_and(|p| p.starts_with(IMPORT_PREFIX))
+    }
+
+    fn load_message(&self, path: &Path) -> Option<Message> {
+        ensure_scanned();
+        store().read().data.load_message(path)
+    }
+
+    fn load_parts(
+        &self,
+        message_ids: &[&str],
+        detail: crate::storage::PartDetail,
+    ) -> FxHashMap<Box<str>, Vec<PartData>> {
+        if !Source::IMPORTED.iter().any(|s| s.dir().is_some()) {
+            return FxHashMap::default();
+        }
+        ensure_scanned();
+        let store = store().read();
+        if store.data.is_empty() {
+            return FxHashMap::default();
+        }
+        store.data.load_parts(message_ids, detail)
+    }
+
+    fn load_diffs(&self) -> FxHashMap<String, Vec<FileDiff>> {
+        FxHashMap::default()
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        watch_dirs()
+    }
+
+    fn tracked_files(&self) -> Vec<PathBuf> {
+        list_transcripts().into_iter().map(|(_, p)| p).collect()
+    }
+
+    fn owns_change(&self, path: &Path) -> bool {
+        is_transcript(path)
+    }
+}
+
+// ============================================================================
+// Source filter
+// ============================================================================
+
+/// Sources that recorded at least one session, in `Source::ALL` order.
+pub fn present_sources(
+    session_message_files: &FxHashMap<String, FxHashSet<PathBuf>>,
+) -> Vec<Source> {
+    let found: FxHashSet<Source> = session_message_files
+        .keys()
+        .map(|id| session_source(session_message_files, id))
+        .collect();
+    Source::ALL
+        .into_iter()
+        .filter(|s| found.contains(s))
+        .collect()
+}
+
+/// Keep only the sessions recorded by `source`; `None` keeps everything.
+///
+/// Day figures are rebuilt from the kept sessions, and model usage is the
+/// share each model recorded for `source` during aggregation.
+pub fn filter_by_source(
+    totals: &Totals,
+    per_day: &FxHashMap<String, DayStat>,
+    model_usage: &[ModelUsage],
+    session_message_files: &FxHashMap<String, FxHashSet<PathBuf>>,
+    source: Option<Source>,
+) -> (Totals, FxHashMap<String, DayStat>, Vec<ModelUsage>) {
+    let Some(source) = source else {
+        return (totals.clone(), per_day.clone(), model_usage.to_vec());
+    };
+    let keep = |id: &str| session_source(session_message_files, id) == source;
+
+    let mut days: FxHashMap<String, DayStat> = FxHashMap::default();
+    for (day, stat) in per_day {
+        let mut kept = DayStat::default();
+        for (id, s) in stat.sessions.iter().filter(|(id, _)| keep(id)) {
+            kept.messages += s.messages;
+            kept.prompts += s.prompts;
+            kept.cost += s.cost;
+            kept.tokens += s.tokens;
+            kept.diffs.additions += s.diffs.additions;
+            kept.diffs.deletions += s.diffs.deletions;
+            kept.sessions.insert(id.clone(), s.clone());
+        }
+        if !kept.sessions.is_empty() {
+            days.insert(day.clone(), kept);
+        }
+    }
+    let totals = crate::date_range::totals_from_days(&days);
+
+    let mut models: Vec<ModelUsage> = model_usage
+        .iter()
+        .filter_map(|m| m.by_source.get(&source).cloned())
+        .collect();
+    models.sort_unstable_by_key(|m| std::cmp::Reverse(m.tokens.total()));
+    (totals, days, models)
+}
@@ -0,0 +1,259 @@
+//! Claude Code transcripts (`~/.claude/projects/<project>/<session>.jsonl`).
+//!
+//! Each line is one event. An assistant reply is streamed as several lines
+//! sharing `message.id`, one per content block, each repeating the usage, so
+//! lines are merged per id. Tool results arrive as user lines and are skipped.
+
+use super::{
+    first_line_title, lenient, parse_timestamp, text_part, thinking_part, tool_part,
+    ImportedMessage, ParsedFile,
+};
+use crate::stats::{
+    CacheData, LenientF64, LenientString, LenientU64, Message, PartData, PathData, TimeData,
+    TokensData,
+};
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PROVIDER: &str = "anthropic";
+const MAIN_AGENT: &str = "claude-code";
+const SIDECHAIN_AGENT: &str = "subagent";
+
+/// `$CLAUDE_CONFIG_DIR/projects`, defaulting to `~/.claude/projects`.
+pub(super) fn transcript_dir() -> PathBuf {
+    std::env::var_os("CLAUDE_CONFIG_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| super::home().join(".claude"))
+        .join("projects")
+}
+
+#[derive(Deserialize)]
+struct Line {
+    #[serde(rename = "type")]
+    kind: Option<LenientString>,
+    #[serde(rename = "sessionId")]
+    session_id: Option<LenientString>,
+    uuid: Option<LenientString>,
+    timestamp: Option<LenientString>,
+    cwd: Option<String>,
+    #[serde(rename = "isSidechain")]
+    is_sidechain: Option<bool>,
+    #[serde(rename = "isMeta")]
+    is_meta: Option<bool>,
+    #[serde(rename = "costUSD")]
+    cost_usd: Option<LenientF64>,
+    summary: Option<String>,
+    message: Option<LineMessage>,
+}
+
+#[derive(Deserialize)]
+struct LineMessage {
+    id: Option<LenientString>,
+    model: Option<LenientString>,
+    content: Option<serde_json::Value>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    input_tokens: Option<LenientU64>,
+    output_tokens: Option<LenientU64>,
+    cache_creation_input_tokens: Option<LenientU64>,
+    cache_read_input_tokens: Option<LenientU64>,
+    /// Split of `cache_creation_input_tokens` by cache lifetime.
+    cache_creation: Option<CacheCreation>,
+}
+
+#[derive(Deserialize)]
+struct CacheCreation {
+    ephemeral_1h_input_tokens: Option<LenientU64>,
+}
+
+/// Convert message content into parts. Tool results are dropped.
+fn content_parts(content: &serde_json::Value) -> Vec<PartData> {
+    match content {
+        serde_json::Value::String(s) if !s.trim().is_empty() => vec![text_part(s)],
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| {
+                let text = |key: &str| block.get(key).and_then(|v| v.as_str());
+                match block.get("type")?.as_str()? {
+                    "text" => text("text").filter(|t| !t.trim().is_empty()).map(text_part),
+                    "thinking" => Some(thinking_part(
+                        text("thinking").unwrap_or_default().to_string(),
+                    )),
+                    "tool_use" => Some(tool_part(
+                        &text("name")?.to_ascii_lowercase(),
+                        block.get("input"),
+                    )),
+                    _ => None,
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Parse one transcript; unreadable lines are skipped.
+pub(super) fn parse_file(path: &Path) -> ParsedFile {
+    let Ok(data) = fs::read_to_string(path) else {
+        return ParsedFile::default();
+    };
+
+    let mut messages: Vec<ImportedMessage> = Vec::new();
+    let mut by_id: FxHashMap<String, usize> = FxHashMap::default();
+    let mut summary: Option<String> = None;
+    let mut first_prompt: Option<String> = None;
+    let mut session: Option<String> = None;
+
+    for raw in data.lines() {
+        let Ok(line) = serde_json::from_str::<Line>(raw) else {
+            continue;
+        };
+        let kind = line.kind.as_ref().map(|k| k.as_str()).unwrap_or("");
+        if kind == "summary" {
+            summary = summary.or(line.summary);
+            continue;
+        }
+        if (kind != "user" && kind != "assistant") || line.is_meta == Some(true) {
+            continue;
+        }
+        let Some(session_id) = line.session_id.as_ref().filter(|s| !s.is_empty()) else {
+            continue;
+        };
+        let Some(message) = &line.message else {
+            continue;
+        };
+        let is_sidechain = line.is_sidechain == Some(true);
+        let created = parse_timestamp(line.timestamp.as_ref());
+        let parts = message
+            .content
+            .as_ref()
+            .map(content_parts)
+            .unwrap_or_default();
+        session.get_or_insert_with(|| session_id.0.clone());
+
+        if kind == "user" {
+            // Tool results and subagent prompts are not user prompts
+            if parts.is_empty() || is_sidechain {
+                continue;
+            }
+            if first_prompt.is_none() {
+                first_prompt = first_line_title(&parts);
+            }
+            let Some(uuid) = line.uuid.as_ref().filter(|u| !u.is_empty()) else {
+                continue;
+            };
+            messages.push(ImportedMessage {
+                msg: Message {
+                    id: lenient(uuid),
+                    session_id: Some(session_id.clone()),
+                    role: lenient("user"),
+                    agent: lenient(MAIN_AGENT),
+                    time: Some(TimeData {
+                        created: created.map(crate::stats::LenientI64),
+                        completed: None,
+                    }),
+                    path: line.cwd.as_ref().map(|cwd| PathData {
+                        cwd: Some(cwd.clone()),
+                        root: Some(cwd.clone()),
+                    }),
+                    ..Message::default()
+                },
+                parts,
+            });
+            continue;
+        }
+
+        let model = message.model.as_ref().map(|m| m.as_str()).unwrap_or("");
+        if model.is_empty() || model == "<synthetic>" {
+            continue;
+        }
+        let Some(id) = message
+            .id
+            .as_ref()
+            .or(line.uuid.as_ref())
+            .filter(|id| !id.is_empty())
+        else {
+            continue;
+        };
+        let usage = message.usage.as_ref();
+        let tokens = TokensData {
+            input: usage.and_then(|u| u.input_tokens),
+            output: usage.and_then(|u| u.output_tokens),
+            reasoning: None,
+            cache: Some(CacheData {
+                read: usage.and_then(|u| u.cache_read_input_tokens),
+                write: usage.and_then(|u| u.cache_creation_input_tokens),
+                write_1h: usage
+                    .and_then(|u| u.cache_creation.as_ref())
+                    .and_then(|c| c.ephemeral_1h_input_tokens),
+            }),
+        };
+
+        if let Some(&idx) = by_id.get(id.as_str()) {
+            // Later blocks of the same reply: keep the largest usage seen
+            let existing = &mut messages[idx];
+            let msg = &mut existing.msg;
+            if let Some(t) = msg.tokens.as_mut() {
+                let max = |a: Option<LenientU64>, b: Option<LenientU64>| match (a, b) {
+                    (Some(a), Some(b)) => Some(LenientU64(a.0.max(b.0))),
+                    (a, b) => a.or(b),
+                };
+                t.input = max(t.input, tokens.input);
+                t.output = max(t.output, tokens.output);
+                if let (Some(c), Some(n)) = (t.cache.as_mut(), tokens.cache.as_ref()) {
+                    c.read = max(c.read, n.read);
+                    c.write = max(c.write, n.write);
+                    c.write_1h = max(c.write_1h, n.write_1h);
+                }
+            }
+            if let Some(cost) = line.cost_usd {
+                let old = msg.cost.map(|c| c.0).unwrap_or(0.0);
+                msg.cost = Some(LenientF64(old.max(cost.0)));
+            }
+            if let (Some(time), Some(ts)) = (msg.time.as_mut(), created) {
+                time.completed = Some(crate::stats::LenientI64(ts));
+            }
+            existing.parts.extend(parts);
+            continue;
+        }
+
+        by_id.insert(id.0.clone(), messages.len());
+        messages.push(ImportedMessage {
+            msg: Message {
+                id: Some(id.clone()),
+                session_id: Some(session_id.clone()),
+                role: lenient("assistant"),
+                agent: lenient(if is_sidechain {
+                    SIDECHAIN_AGENT
+                } else {
+                    MAIN_AGENT
+                }),
+                provider_id: lenient(PROVIDER),
+                model_id: lenient(model),
+                time: Some(TimeData {
+                    created: created.map(crate::stats::LenientI64),
+                    completed: None,
+                }),
+                tokens: Some(tokens),
+                path: line.cwd.as_ref().map(|cwd| PathData {
+                    cwd: Some(cwd.clone()),
+                    root: Some(cwd.clone()),
+                }),
+                cost: line.cost_usd,
+                ..Message::default()
+            },
+            parts,
+        });
+    }
+
+    ParsedFile {
+        title: session
+            .zip(summary.or(first_prompt))
+            .map(|(session, title)| (session.into_boxed_str(), title)),
+        messages,
+    }
+}
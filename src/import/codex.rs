@@ -0,0 +1,357 @@
+//! Codex CLI rollouts (`~/.codex/sessions/YYYY/MM/DD/rollout-*.jsonl`).
+//!
+//! Each line wraps a `payload` tagged by `type`: `session_meta` and
+//! `turn_context` carry the session id, cwd and model, `response_item`s are
+//! the reasoning, tool calls and replies of a model call, and `event_msg`s
+//! carry the typed prompts and per-call `token_count`s. Calls are not
+//! delimited otherwise, so each token count closes one assistant message.
+
+use super::{
+    first_line_title, lenient, parse_timestamp, text_part, thinking_part, tool_part,
+    ImportedMessage, ParsedFile,
+};
+use crate::stats::{
+    CacheData, LenientI64, LenientString, LenientU64, Message, PartData, PathData, TimeData,
+    TokensData,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PROVIDER: &str = "openai";
+const AGENT: &str = "codex";
+
+/// `$CODEX_HOME/sessions`, defaulting to `~/.codex/sessions`.
+pub(super) fn session_dir() -> PathBuf {
+    std::env::var_os("CODEX_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| super::home().join(".codex"))
+        .join("sessions")
+}
+
+#[derive(Deserialize)]
+struct Line {
+    timestamp: Option<LenientString>,
+    #[serde(rename = "type")]
+    kind: Option<LenientString>,
+    payload: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct Context {
+    id: Option<LenientString>,
+    cwd: Option<String>,
+    model: Option<LenientString>,
+}
+
+#[derive(Deserialize)]
+struct TokenCount {
+    info: Option<TokenInfo>,
+}
+
+#[derive(Deserialize)]
+struct TokenInfo {
+    total_token_usage: Option<Usage>,
+    last_token_usage: Option<Usage>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct Usage {
+    input_tokens: Option<LenientU64>,
+    cached_input_tokens: Option<LenientU64>,
+    output_tokens: Option<LenientU64>,
+    reasoning_output_tokens: Option<LenientU64>,
+}
+
+impl Usage {
+    fn get(v: Option<LenientU64>) -> u64 {
+        v.map(|v| v.0).unwrap_or(0)
+    }
+
+    fn sum(&self) -> u64 {
+        Self::get(self.input_tokens) + Self::get(self.output_tokens)
+    }
+
+    /// OpenAI counts cached input inside input and reasoning inside output.
+    fn tokens(&self) -> TokensData {
+        let cached = Self::get(self.cached_input_tokens);
+        let reasoning = Self::get(self.reasoning_output_tokens);
+        TokensData {
+            input: Some(LenientU64(
+                Self::get(self.input_tokens).saturating_sub(cached),
+            )),
+            output: Some(LenientU64(
+                Self::get(self.output_tokens).saturating_sub(reasoning),
+            )),
+            reasoning: Some(LenientU64(reasoning)),
+            cache: Some(CacheData {
+                read: Some(LenientU64(cached)),
+                write: None,
+                write_1h: None,
+            }),
+        }
+    }
+}
+
+/// Map Codex tool names onto opencode's where they do the same job.
+fn tool_name(name: &str) -> &str {
+    match name {
+        "shell" | "local_shell" | "exec_command" => "bash",
+        "update_plan" => "todowrite",
+        other => other,
+    }
+}
+
+/// `["bash", "-lc", "script"]` becomes `script`; other argv lists are joined.
+fn command_text(argv: &[Value]) -> String {
+    let args: Vec<&str> = argv.iter().filter_map(Value::as_str).collect();
+    match args.as_slice() {
+        [_, flag, script] if flag.starts_with('-') && flag.ends_with('c') => script.to_string(),
+        _ => args.join(" "),
+    }
+}
+
+/// Tool call response items as a tool part.
+fn call_part(item: &Value) -> Option<PartData> {
+    let name = match item.get("type")?.as_str()? {
+        "local_shell_call" => "local_shell",
+        _ => item.get("name")?.as_str()?,
+    };
+    let mut input = match item.get("arguments").or_else(|| item.get("input")) {
+        // Function arguments arrive as a JSON string
+        Some(Value::String(s)) => serde_json::from_str(s).unwrap_or_else(|_| {
+            let key = if name == "apply_patch" {
+                "patchText"
+            } else {
+                "content"
+            };
+            serde_json::json!({ key: s })
+        }),
+        Some(v) => v.clone(),
+        None => item.get("action").cloned().unwrap_or(Value::Null),
+    };
+    if let Some(Value::Array(argv)) = input.get("command") {
+        let command = command_text(argv);
+        input["command"] = Value::String(command);
+    }
+    if let Some(dir) = input.get("workdir").cloned() {
+        input["path"] = dir;
+    }
+    Some(tool_part(tool_name(name), Some(&input)))
+}
+
+fn item_parts(item: &Value) -> Vec<PartData> {
+    let texts = |key: &str, kind: &str| -> Vec<String> {
+        item.get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|b| b.get("type").and_then(Value::as_str) == Some(kind))
+            .filter_map(|b| b.get("text").and_then(Value::as_str))
+            .filter(|t| !t.trim().is_empty())
+            .map(ToString::to_string)
+            .collect()
+    };
+    match item.get("type").and_then(Value::as_str) {
+        Some("message") if item.get("role").and_then(Value::as_str) == Some("assistant") => {
+            texts("content", "output_text")
+                .iter()
+                .map(|t| text_part(t))
+                .collect()
+        }
+        Some("reasoning") => {
+            let summary = texts("summary", "summary_text");
+            if summary.is_empty() {
+                Vec::new()
+            } else {
+                vec![thinking_part(summary.join("\n\n"))]
+            }
+        }
+        Some("function_call" | "custom_tool_call" | "local_shell_call") => {
+            call_part(item).into_iter().collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Session id from `rollout-<date>T<time>-<uuid>.jsonl`.
+fn id_from_file_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let uuid = stem.get(stem.len().checked_sub(36)?..)?;
+    Some(uuid.to_string())
+}
+
+struct Rollout {
+    session: String,
+    cwd: Option<String>,
+    model: Option<String>,
+    messages: Vec<ImportedMessage>,
+    pending: Option<ImportedMessage>,
+}
+
+impl Rollout {
+    fn next_id(&self) -> String {
+        let n = self.messages.len() + usize::from(self.pending.is_some());
+        format!("{}:{:05}", self.session, n)
+    }
+
+    fn path(&self) -> Option<PathData> {
+        self.cwd.as_ref().map(|cwd| PathData {
+            cwd: Some(cwd.clone()),
+            root: Some(cwd.clone()),
+        })
+    }
+
+    fn flush(&mut self) {
+        if let Some(m) = self.pending.take() {
+            self.messages.push(m);
+        }
+    }
+
+    /// Assistant message collecting the current model call.
+    fn pending(&mut self, created: Option<i64>) -> &mut ImportedMessage {
+        if self.pending.is_none() {
+            let msg = Message {
+                id: lenient(&self.next_id()),
+                session_id: lenient(&self.session),
+                role: lenient("assistant"),
+                agent: lenient(AGENT),
+                provider_id: lenient(PROVIDER),
+                model_id: self.model.as_deref().and_then(lenient),
+                time: Some(TimeData {
+                    created: created.map(LenientI64),
+                    completed: None,
+                }),
+                path: self.path(),
+                ..Message::default()
+            };
+            self.pending = Some(ImportedMessage {
+                msg,
+                parts: Vec::new(),
+            });
+        }
+        self.pending.as_mut().expect("pending message was just set")
+    }
+
+    fn prompt(&mut self, text: &str, created: Option<i64>) {
+        self.flush();
+        let msg = Message {
+            id: lenient(&self.next_id()),
+            session_id: lenient(&self.session),
+            role: lenient("user"),
+            agent: lenient(AGENT),
+            time: Some(TimeData {
+                created: created.map(LenientI64),
+                completed: None,
+            }),
+            path: self.path(),
+            ..Message::default()
+        };
+        self.messages.push(ImportedMessage {
+            msg,
+            parts: vec![text_part(text)],
+        });
+    }
+}
+
+/// Parse one rollout; unreadable lines are skipped.
+pub(super) fn parse_file(path: &Path) -> ParsedFile {
+    let Ok(data) = fs::read_to_string(path) else {
+        return ParsedFile::default();
+    };
+    let mut rollout = Rollout {
+        session: id_from_file_name(path).unwrap_or_default(),
+        cwd: None,
+        model: None,
+        messages: Vec::new(),
+        pending: None,
+    };
+    let mut first_prompt: Option<String> = None;
+    let mut last_total: Option<u64> = None;
+
+    for raw in data.lines() {
+        let Ok(line) = serde_json::from_str::<Line>(raw) else {
+            continue;
+        };
+        let Some(payload) = line.payload else {
+            continue;
+        };
+        let ts = parse_timestamp(line.timestamp.as_ref());
+        match line.kind.as_ref().map(|k| k.as_str()).unwrap_or("") {
+            "session_meta" | "turn_context" => {
+                let Ok(ctx) = serde_json::from_value::<Context>(payload) else {
+                    continue;
+                };
+                if let Some(id) = ctx.id.filter(|id| !id.is_empty()) {
+                    rollout.session = id.0;
+                }
+                rollout.cwd = ctx.cwd.filter(|c| !c.is_empty()).or(rollout.cwd.take());
+                if let Some(model) = ctx.model.filter(|m| !m.is_empty()) {
+                    rollout.model = Some(model.0);
+                }
+            }
+            "response_item" => {
+                let parts = item_parts(&payload);
+                if !parts.is_empty() && !rollout.session.is_empty() {
+                    rollout.pending(ts).parts.extend(parts);
+                }
+            }
+            "event_msg" => match payload.get("type").and_then(Value::as_str) {
+                Some("user_message") => {
+                    let Some(text) = payload
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .filter(|t| !t.trim().is_empty())
+                    else {
+                        continue;
+                    };
+                    if rollout.session.is_empty() {
+                        continue;
+                    }
+                    rollout.prompt(text, ts);
+                    if first_prompt.is_none() {
+                        first_prompt = first_line_title(&[text_part(text)]);
+                    }
+                }
+                Some("token_count") => {
+                    let Some(info) = serde_json::from_value::<TokenCount>(payload)
+                        .ok()
+                        .and_then(|c| c.info)
+                    else {
+                        continue;
+                    };
+                    let (Some(last), total) = (info.last_token_usage, info.total_token_usage)
+                    else {
+                        continue;
+                    };
+                    // The same count is sometimes reported twice in a row
+                    let total = total.map(|t| t.sum());
+                    if total.is_some() && total == last_total {
+                        continue;
+                    }
+                    last_total = total;
+                    if rollout.session.is_empty() {
+                        continue;
+                    }
+                    let msg = &mut rollout.pending(ts).msg;
+                    msg.tokens = Some(last.tokens());
+                    if let Some(time) = msg.time.as_mut() {
+                        time.completed = ts.map(LenientI64);
+                    }
+                    rollout.flush();
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    rollout.flush();
+
+    ParsedFile {
+        title: first_prompt
+            .filter(|_| !rollout.session.is_empty())
+            .map(|title| (rollout.session.clone().into_boxed_str(), title)),
+        messages: rollout.messages,
+    }
+}
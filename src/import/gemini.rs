@@ -0,0 +1,221 @@
+//! Gemini CLI chats (`~/.gemini/tmp/<project hash>/chats/session-*.json`).
+//!
+//! Each file is one JSON document rewritten as the chat grows. `user` and
+//! `gemini` entries map to prompts and replies; replies carry their own token
+//! counts, thoughts and tool calls. Chats only record a hash of the project
+//! directory, so imported sessions have no project path.
+
+use super::{
+    first_line_title, lenient, parse_timestamp, text_part, thinking_part, tool_part,
+    ImportedMessage, ParsedFile,
+};
+use crate::stats::{
+    CacheData, LenientI64, LenientString, LenientU64, Message, PartData, TimeData, TokensData,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PROVIDER: &str = "google";
+const AGENT: &str = "gemini-cli";
+
+/// `~/.gemini/tmp`, where chats are kept per project.
+pub(super) fn chat_dir() -> PathBuf {
+    super::home().join(".gemini").join("tmp")
+}
+
+#[derive(Deserialize)]
+struct Chat {
+    #[serde(rename = "sessionId")]
+    session_id: Option<LenientString>,
+    messages: Option<Vec<Value>>,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    id: Option<LenientString>,
+    timestamp: Option<LenientString>,
+    #[serde(rename = "type")]
+    kind: Option<LenientString>,
+    content: Option<Value>,
+    model: Option<LenientString>,
+    tokens: Option<Tokens>,
+    thoughts: Option<Vec<Thought>>,
+    #[serde(rename = "toolCalls")]
+    tool_calls: Option<Vec<ToolCall>>,
+}
+
+#[derive(Deserialize)]
+struct Tokens {
+    input: Option<LenientU64>,
+    output: Option<LenientU64>,
+    cached: Option<LenientU64>,
+    thoughts: Option<LenientU64>,
+    tool: Option<LenientU64>,
+}
+
+impl Tokens {
+    /// Gemini counts cached input inside input; tool-use prompts are input too.
+    fn to_data(&self) -> TokensData {
+        let get = |v: Option<LenientU64>| v.map(|v| v.0).unwrap_or(0);
+        let cached = get(self.cached);
+        TokensData {
+            input: Some(LenientU64(
+                (get(self.input) + get(self.tool)).saturating_sub(cached),
+            )),
+            output: self.output,
+            reasoning: self.thoughts,
+            cache: Some(CacheData {
+                read: Some(LenientU64(cached)),
+                write: None,
+                write_1h: None,
+            }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Thought {
+    subject: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ToolCall {
+    name: Option<LenientString>,
+    args: Option<Value>,
+}
+
+/// Map Gemini CLI tool names onto opencode's where they do the same job.
+fn tool_name(name: &str) -> &str {
+    match name {
+        "read_file" | "read_many_files" => "read",
+        "write_file" => "write",
+        "replace" => "edit",
+        "run_shell_command" => "bash",
+        "search_file_content" => "grep",
+        "list_directory" => "list",
+        "web_fetch" => "webfetch",
+        "google_web_search" => "websearch",
+        "write_todos" => "todowrite",
+        other => other,
+    }
+}
+
+/// Message content is either a string or a list of `{text}` parts.
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn reply_parts(m: &ChatMessage, text: &str) -> Vec<PartData> {
+    let mut parts = Vec::new();
+    let thoughts: Vec<String> = m
+        .thoughts
+        .iter()
+        .flatten()
+        .map(|t| match (&t.subject, &t.description) {
+            (Some(s), Some(d)) => format!("{}\n{}", s, d),
+            (s, d) => s.clone().or(d.clone()).unwrap_or_default(),
+        })
+        .filter(|t| !t.trim().is_empty())
+        .collect();
+    if !thoughts.is_empty() {
+        parts.push(thinking_part(thoughts.join("\n\n")));
+    }
+    if !text.trim().is_empty() {
+        parts.push(text_part(text));
+    }
+    parts.extend(m.tool_calls.iter().flatten().filter_map(|call| {
+        let name = call.name.as_ref().filter(|n| !n.is_empty())?;
+        Some(tool_part(tool_name(name.as_str()), call.args.as_ref()))
+    }));
+    parts
+}
+
+/// Parse one chat file; unreadable entries are skipped.
+pub(super) fn parse_file(path: &Path) -> ParsedFile {
+    let Some(chat) = fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<Chat>(&data).ok())
+    else {
+        return ParsedFile::default();
+    };
+    let Some(session) = chat.session_id.filter(|s| !s.is_empty()) else {
+        return ParsedFile::default();
+    };
+
+    let mut messages = Vec::new();
+    let mut first_prompt: Option<String> = None;
+    for (idx, raw) in chat.messages.into_iter().flatten().enumerate() {
+        let Ok(entry) = serde_json::from_value::<ChatMessage>(raw) else {
+            continue;
+        };
+        let role = match entry.kind.as_ref().map(|k| k.as_str()) {
+            Some("user") => "user",
+            Some("gemini") => "assistant",
+            _ => continue,
+        };
+        let id = entry
+            .id
+            .clone()
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| LenientString(format!("{}:{:05}", session.as_str(), idx)));
+        let created = parse_timestamp(entry.timestamp.as_ref()).map(LenientI64);
+        let text = entry.content.as_ref().map(content_text).unwrap_or_default();
+
+        let (msg, parts) = if role == "user" {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let parts = vec![text_part(&text)];
+            if first_prompt.is_none() {
+                first_prompt = first_line_title(&parts);
+            }
+            let msg = Message {
+                id: Some(id),
+                session_id: Some(session.clone()),
+                role: lenient(role),
+                agent: lenient(AGENT),
+                time: Some(TimeData {
+                    created,
+                    completed: None,
+                }),
+                ..Message::default()
+            };
+            (msg, parts)
+        } else {
+            let parts = reply_parts(&entry, &text);
+            let msg = Message {
+                id: Some(id),
+                session_id: Some(session.clone()),
+                role: lenient(role),
+                agent: lenient(AGENT),
+                provider_id: lenient(PROVIDER),
+                model_id: entry.model.clone().filter(|m| !m.is_empty()),
+                // Replies are written once they finish
+                time: Some(TimeData {
+                    created,
+                    completed: created,
+                }),
+                tokens: entry.tokens.as_ref().map(Tokens::to_data),
+                ..Message::default()
+            };
+            (msg, parts)
+        };
+        messages.push(ImportedMessage { msg, parts });
+    }
+
+    ParsedFile {
+        title: first_prompt.map(|title| (session.0.into_boxed_str(), title)),
+        messages,
+    }
+}
@@ -5,6 +5,7 @@ use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use parking_lot::Mutex;
 use rustc_hash::FxHashSet;
 use std::{
+    ops::ControlFlow,
     path::PathBuf,
     sync::{mpsc, Arc},
     time::{Duration, Instant},
@@ -47,7 +48,9 @@ impl LiveWatcher {
                                     continue;
                                 }
 
-                                let is_json = path.extension().is_some_and(|e| e == "json");
+                                let is_json = path
+                                    .extension()
+                                    .is_some_and(|e| e == "json" || e == "jsonl");
                                 let is_sqlite =
                                     path.file_name().and_then(|n| n.to_str()).is_some_and(|n| {
                                         n == "opencode.db"
@@ -139,3 +142,32 @@ impl LiveWatcher {
         !self.changed_files.lock().is_empty()
     }
 }
+
+/// Watch the storage paths and call `on_tick` every `poll` with the files
+/// changed since the previous tick (possibly none). Blocks until `on_tick`
+/// breaks, or fails if the watcher cannot be started.
+pub fn watch_storage_blocking(
+    poll: Duration,
+    mut on_tick: impl FnMut(Vec<PathBuf>) -> ControlFlow<()>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pending = Arc::new(Mutex::new(Vec::new()));
+    let pending_clone = pending.clone();
+    let (wake_tx, wake_rx) = mpsc::channel();
+    let mut watcher = LiveWatcher::new(
+        crate::storage::watch_paths(),
+        Arc::new(move |files| {
+            pending_clone.lock().extend(files);
+        }),
+        wake_tx,
+    )?;
+    watcher.start()?;
+
+    loop {
+        let _ = wake_rx.recv_timeout(poll);
+        watcher.process_changes();
+        let changed = std::mem::take(&mut *pending.lock());
+        if on_tick(changed).is_break() {
+            return Ok(());
+        }
+    }
+}
@@ -2,14 +2,28 @@
 
 use std::io;
 
+mod budget;
+mod cli;
+mod config;
 mod cost;
+mod date_range;
 mod device;
+mod export;
+mod import;
 mod live_watcher;
+mod markdown;
+mod metrics;
+mod pricing;
+mod report;
+mod serve;
 mod session;
 mod stats;
 mod stats_cache;
+mod status;
+mod storage;
 mod theme;
 mod ui;
+mod watch;
 mod overview_stats;
 
 /// Restore terminal to normal mode.
@@ -118,6 +132,40 @@ fn flush_stdin_buffer() {
 fn flush_stdin_buffer() {}
 
 fn main() -> io::Result<()> {
+    // Headless subcommands never touch the terminal
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let parsed = date_range::DateRange::take_from_args(&mut args).and_then(|range| {
+        config::RootSpec::take_from_args(&mut args).map(|roots| (range, roots))
+    });
+    let (range, roots) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    for missing in stats::set_extra_roots(config::extra_roots(roots)) {
+        eprintln!("warning: storage root not found: {}", missing.display());
+    }
+    cost::init_overrides();
+    let headless = match args.first().map(String::as_str) {
+        Some("report") => Some(report::run(&args[1..], &range)),
+        Some("export") => Some(export::run(&args[1..], &range)),
+        Some("metrics") => Some(metrics::run(&args[1..], &range)),
+        Some("pricing") => Some(pricing::run(&args[1..], &range)),
+        Some("serve") => Some(serve::run(&args[1..], &range)),
+        Some("status") => Some(status::run(&args[1..], &range)),
+        Some("watch") => Some(watch::run(&args[1..], &range)),
+        _ => None,
+    };
+    if let Some(result) = headless {
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     setup_panic_hook();
 
     // Background initialization
@@ -141,7 +189,7 @@ fn main() -> io::Result<()> {
     let mut terminal = ratatui::Terminal::new(backend)?;
 
     // Run application
-    let result = ui::App::new().run(&mut terminal);
+    let result = ui::App::new(range).run(&mut terminal);
 
     // Terminal cleanup sequence
     disable_all_modes();
@@ -0,0 +1,319 @@
+//! Markdown usage report for a period.
+
+use crate::date_range::{DateRange, RangePreset};
+use crate::stats::{format_active_duration, format_number_full, Stats};
+use chrono::{Datelike, Timelike};
+use std::io::{self, Write};
+
+const TOP_SESSIONS: usize = 10;
+const HEATMAP_SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Map a `--period` value to a preset.
+pub fn parse_period(s: &str) -> Option<RangePreset> {
+    match s {
+        "week" => Some(RangePreset::Last7Days),
+        "month" => Some(RangePreset::Last30Days),
+        "last-month" => Some(RangePreset::LastMonth),
+        "all" => Some(RangePreset::AllTime),
+        _ => None,
+    }
+}
+
+/// Escape a value for use inside a Markdown table cell.
+fn cell(s: &str) -> String {
+    s.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+}
+
+#[inline]
+fn percent(part: f64, total: f64) -> String {
+    if total > 0.0 {
+        format!("{:.1}%", part / total * 100.0)
+    } else {
+        "0.0%".to_string()
+    }
+}
+
+/// Render the report for stats already restricted to `range`.
+pub fn write_markdown(out: &mut impl Write, stats: &Stats, range: &DateRange) -> io::Result<()> {
+    let totals = &stats.totals;
+    writeln!(out, "# OpenCode Usage: {}", range.label())?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "_Generated {}_",
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    )?;
+    writeln!(out)?;
+
+    // Headline
+    let active_ms: i64 = stats
+        .per_day
+        .values()
+        .flat_map(|d| d.sessions.values())
+        .map(|s| s.active_duration_ms)
+        .sum();
+    writeln!(out, "## Headline")?;
+    writeln!(out)?;
+    writeln!(out, "| Metric | Value |")?;
+    writeln!(out, "|--------|------:|")?;
+    let rows = [
+        (
+            "Active days",
+            format_number_full(stats.per_day.len() as u64),
+        ),
+        ("Sessions", format_number_full(totals.sessions.len() as u64)),
+        ("Messages", format_number_full(totals.messages)),
+        ("Prompts", format_number_full(totals.prompts)),
+        ("Input tokens", format_number_full(totals.tokens.input)),
+        ("Output tokens", format_number_full(totals.tokens.output)),
+        (
+            "Thinking tokens",
+            format_number_full(totals.tokens.reasoning),
+        ),
+        ("Cache read", format_number_full(totals.tokens.cache_read)),
+        ("Cache write", format_number_full(totals.tokens.cache_write)),
+        ("Active time", format_active_duration(active_ms)),
+        (
+            "Lines changed",
+            format!(
+                "+{} / -{}",
+                format_number_full(totals.diffs.additions),
+                format_number_full(totals.diffs.deletions)
+            ),
+        ),
+        ("Cost", format!("${:.2}", totals.display_cost())),
+    ];
+    for (label, value) in rows {
+        writeln!(out, "| {} | {} |", label, value)?;
+    }
+    writeln!(out)?;
+
+    write_models(out, stats)?;
+    write_projects(out, stats)?;
+    write_sessions(out, stats)?;
+    write_tools(out, stats)?;
+    write_languages(out, stats)?;
+    write_heatmap(out, stats)
+}
+
+fn write_models(out: &mut impl Write, stats: &Stats) -> io::Result<()> {
+    writeln!(out, "## Cost by Model")?;
+    writeln!(out)?;
+    if stats.model_usage.is_empty() {
+        writeln!(out, "No model usage.")?;
+        return writeln!(out);
+    }
+    let mut models: Vec<_> = stats.model_usage.iter().collect();
+    models.sort_unstable_by(|a, b| b.cost.total_cmp(&a.cost).then_with(|| a.name.cmp(&b.name)));
+    let total_cost: f64 = models.iter().map(|m| m.cost).sum();
+    writeln!(
+        out,
+        "| Model | Provider | Sessions | Messages | Tokens | Cost | Share |"
+    )?;
+    writeln!(
+        out,
+        "|-------|----------|---------:|---------:|-------:|-----:|------:|"
+    )?;
+    for m in models {
+        writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | ${:.2} | {} |",
+            cell(&m.short_name),
+            cell(&m.provider),
+            format_number_full(m.sessions.len() as u64),
+            format_number_full(m.messages),
+            format_number_full(m.tokens.total()),
+            m.cost,
+            percent(m.cost, total_cost)
+        )?;
+    }
+    writeln!(out)
+}
+
+fn write_projects(out: &mut impl Write, stats: &Stats) -> io::Result<()> {
+    writeln!(out, "## Top Projects")?;
+    writeln!(out)?;
+    let projects = crate::overview_stats::project_counts(&stats.per_day);
+    if projects.is_empty() {
+        writeln!(out, "No projects.")?;
+        return writeln!(out);
+    }
+    let total: usize = projects.iter().map(|(_, c)| c).sum();
+    writeln!(out, "| Project | Session-days | Share |")?;
+    writeln!(out, "|---------|-------------:|------:|")?;
+    for (name, count) in &projects {
+        writeln!(
+            out,
+            "| {} | {} | {} |",
+            cell(name),
+            count,
+            percent(*count as f64, total as f64)
+        )?;
+    }
+    writeln!(out)
+}
+
+fn write_sessions(out: &mut impl Write, stats: &Stats) -> io::Result<()> {
+    writeln!(out, "## Most Expensive Sessions")?;
+    writeln!(out)?;
+    let mut sessions = crate::export::merge_sessions(&stats.per_day);
+    if sessions.is_empty() {
+        writeln!(out, "No sessions.")?;
+        return writeln!(out);
+    }
+    sessions.sort_unstable_by(|a, b| {
+        b.stat
+            .cost
+            .total_cmp(&a.stat.cost)
+            .then_with(|| a.stat.id.cmp(&b.stat.id))
+    });
+    writeln!(
+        out,
+        "| Session | Title | Project | Days | Duration | Tokens | Cost |"
+    )?;
+    writeln!(
+        out,
+        "|---------|-------|---------|------|---------:|-------:|-----:|"
+    )?;
+    for m in sessions.iter().take(TOP_SESSIONS) {
+        let s = &m.stat;
+        let title = stats
+            .session_titles
+            .get(s.id.as_ref())
+            .map(|t| t.strip_prefix("New session - ").unwrap_or(t))
+            .unwrap_or("Untitled");
+        let project = if s.path_root.is_empty() {
+            &s.path_cwd
+        } else {
+            &s.path_root
+        };
+        let days = match (m.days.first(), m.days.last()) {
+            (Some(first), Some(last)) if first != last => format!("{} → {}", first, last),
+            (Some(first), _) => first.clone(),
+            _ => String::new(),
+        };
+        writeln!(
+            out,
+            "| `{}` | {} | {} | {} | {} | {} | ${:.2} |",
+            s.id,
+            cell(title),
+            cell(project),
+            days,
+            format_active_duration(s.active_duration_ms),
+            format_number_full(s.tokens.total()),
+            s.display_cost()
+        )?;
+    }
+    writeln!(out)
+}
+
+fn write_tools(out: &mut impl Write, stats: &Stats) -> io::Result<()> {
+    writeln!(out, "## Tool Usage")?;
+    writeln!(out)?;
+    let mut tools: Vec<(&str, u64)> = stats
+        .totals
+        .tools
+        .iter()
+        .map(|(name, count)| (name.as_ref(), *count))
+        .collect();
+    if tools.is_empty() {
+        writeln!(out, "No tool calls.")?;
+        return writeln!(out);
+    }
+    tools.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    let total: u64 = tools.iter().map(|(_, c)| c).sum();
+    writeln!(out, "| Tool | Calls | Share |")?;
+    writeln!(out, "|------|------:|------:|")?;
+    for (name, count) in tools {
+        writeln!(
+            out,
+            "| {} | {} | {} |",
+            cell(name),
+            format_number_full(count),
+            percent(count as f64, total as f64)
+        )?;
+    }
+    writeln!(out)
+}
+
+fn write_languages(out: &mut impl Write, stats: &Stats) -> io::Result<()> {
+    writeln!(out, "## Languages")?;
+    writeln!(out)?;
+    let langs = crate::overview_stats::language_counts(&stats.per_day);
+    if langs.is_empty() {
+        writeln!(out, "No file changes recorded.")?;
+        return writeln!(out);
+    }
+    let total: u64 = langs.iter().map(|(_, c)| c).sum();
+    writeln!(out, "| Language | Lines changed | Share |")?;
+    writeln!(out, "|----------|--------------:|------:|")?;
+    for (lang, count) in langs {
+        writeln!(
+            out,
+            "| {} | {} | {} |",
+            lang,
+            format_number_full(count),
+            percent(count as f64, total as f64)
+        )?;
+    }
+    writeln!(out)
+}
+
+/// Weekday x hour grid of tokens, bucketed by session start time.
+fn write_heatmap(out: &mut impl Write, stats: &Stats) -> io::Result<()> {
+    let mut grid = [[0u64; 24]; 7];
+    for session in stats.per_day.values().flat_map(|d| d.sessions.values()) {
+        let ts = session.first_activity;
+        if ts <= 0 || ts == i64::MAX {
+            continue;
+        }
+        let Some(dt) = chrono::DateTime::from_timestamp(ts / 1000, 0) else {
+            continue;
+        };
+        let local = dt.with_timezone(&chrono::Local);
+        grid[local.weekday().num_days_from_monday() as usize][local.hour() as usize] +=
+            session.tokens.total();
+    }
+    let max = grid.iter().flatten().copied().max().unwrap_or(0);
+
+    writeln!(out, "## Activity Heatmap")?;
+    writeln!(out)?;
+    writeln!(out, "```text")?;
+    let header: String = (0..24)
+        .step_by(3)
+        .map(|h| format!("{:<6}", format!("{:02}", h)))
+        .collect();
+    writeln!(out, "     {}", header.trim_end())?;
+    for (day, row) in WEEKDAYS.iter().zip(grid.iter()) {
+        let cells: String = row
+            .iter()
+            .map(|&v| {
+                let level = if v == 0 || max == 0 {
+                    0
+                } else {
+                    ((v as f64 / max as f64 * 4.0).ceil() as usize).clamp(1, 4)
+                };
+                let c = HEATMAP_SHADES[level];
+                format!("{}{}", c, c)
+            })
+            .collect();
+        writeln!(out, "{}  {}", day, cells)?;
+    }
+    writeln!(out, "```")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "Tokens by session start (local time). {} none, {} up to 25%, {} 50%, {} 75%, {} 100% of the busiest hour ({}).",
+        HEATMAP_SHADES[0],
+        HEATMAP_SHADES[1],
+        HEATMAP_SHADES[2],
+        HEATMAP_SHADES[3],
+        HEATMAP_SHADES[4],
+        format_number_full(max)
+    )
+}
@@ -0,0 +1,241 @@
+//! Prometheus textfile exporter for node_exporter's textfile collector.
+//!
+//! The file is written once from the cache, then kept current from the live
+//! watcher through `StatsCache::update_files`, so only changed storage files
+//! are reprocessed. Writes go through a temp file and a rename so the collector
+//! never reads a partial file.
+
+use crate::cli::invalid_arg;
+use crate::date_range::DateRange;
+use crate::stats::{ModelUsage, Tokens, Totals};
+use crate::stats_cache::StatsCache;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const DEFAULT_INTERVAL_SECS: u64 = 10;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Run the `metrics` subcommand.
+pub fn run(args: &[String], range: &DateRange) -> io::Result<()> {
+    let mut output: Option<PathBuf> = None;
+    let mut interval = Duration::from_secs(DEFAULT_INTERVAL_SECS);
+    let mut once = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                output = Some(
+                    iter.next()
+                        .map(PathBuf::from)
+                        .ok_or_else(|| invalid_arg("--output expects a path"))?,
+                );
+            }
+            "--interval" => {
+                interval = iter
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .map(Duration::from_secs)
+                    .ok_or_else(|| invalid_arg("--interval expects seconds"))?;
+            }
+            "--once" => once = true,
+            "-h" | "--help" => {
+                println!(
+                    "Usage: opencode-stats-tui metrics --output FILE.prom [--interval SECS] [--once]"
+                );
+                println!(
+                    "Writes counters for node_exporter's textfile collector and rewrites them as storage changes (at most every {}s by default).",
+                    DEFAULT_INTERVAL_SECS
+                );
+                return Ok(());
+            }
+            other => return Err(invalid_arg(&format!("unknown argument: {}", other))),
+        }
+    }
+    let output = output.ok_or_else(|| invalid_arg("--output is required"))?;
+    if !range.is_all() {
+        return Err(invalid_arg(
+            "metrics are cumulative counters; --since/--until are not supported",
+        ));
+    }
+
+    let cache = StatsCache::new(crate::stats::default_storage_dir())
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let stats = cache.load_or_compute();
+    write_metrics_file(&output, &stats.totals, &stats.model_usage)?;
+    if once {
+        return Ok(());
+    }
+
+    let mut changed: Vec<PathBuf> = Vec::new();
+    let mut last_write = Instant::now();
+    crate::live_watcher::watch_storage_blocking(POLL_INTERVAL, |mut files| {
+        changed.append(&mut files);
+        if changed.is_empty() || last_write.elapsed() < interval {
+            return ControlFlow::Continue(());
+        }
+
+        let files: Vec<String> = changed
+            .drain(..)
+            .filter_map(|p| p.to_str().map(ToString::to_string))
+            .collect();
+        let update = cache.update_files(files);
+        if let Err(e) = write_metrics_file(&output, &update.totals, &update.model_usage) {
+            eprintln!("warning: failed to write {}: {}", output.display(), e);
+        }
+        last_write = Instant::now();
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| io::Error::other(e.to_string()))
+}
+
+/// Write to `<path>.tmp` and rename over `path`.
+fn write_metrics_file(path: &Path, totals: &Totals, models: &[ModelUsage]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    {
+        let mut out = io::BufWriter::new(fs::File::create(&tmp)?);
+        write_metrics(&mut out, totals, models)?;
+        out.flush()?;
+    }
+    fs::rename(&tmp, path)
+}
+
+// ============================================================================
+// Exposition
+// ============================================================================
+
+#[inline]
+fn token_types(t: &Tokens) -> [(&'static str, u64); 5] {
+    [
+        ("input", t.input),
+        ("output", t.output),
+        ("reasoning", t.reasoning),
+        ("cache_read", t.cache_read),
+        ("cache_write", t.cache_write),
+    ]
+}
+
+/// Escape a label value per the exposition format.
+fn label(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn header(out: &mut impl Write, name: &str, help: &str) -> io::Result<()> {
+    writeln!(out, "# HELP {} {}", name, help)?;
+    writeln!(out, "# TYPE {} counter", name)
+}
+
+/// Render all metric families in the Prometheus text format.
+fn write_metrics(out: &mut impl Write, totals: &Totals, models: &[ModelUsage]) -> io::Result<()> {
+    let mut models: Vec<&ModelUsage> = models.iter().collect();
+    models.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+    header(out, "opencode_sessions_total", "Sessions recorded.")?;
+    writeln!(out, "opencode_sessions_total {}", totals.sessions.len())?;
+
+    header(out, "opencode_messages_total", "Messages recorded.")?;
+    writeln!(out, "opencode_messages_total {}", totals.messages)?;
+
+    header(out, "opencode_prompts_total", "User prompts recorded.")?;
+    writeln!(out, "opencode_prompts_total {}", totals.prompts)?;
+
+    header(out, "opencode_tokens_total", "Tokens by type.")?;
+    for (kind, value) in token_types(&totals.tokens) {
+        writeln!(out, "opencode_tokens_total{{type=\"{}\"}} {}", kind, value)?;
+    }
+
+    header(out, "opencode_cost_usd_total", "Cost in USD.")?;
+    writeln!(out, "opencode_cost_usd_total {}", totals.display_cost())?;
+
+    header(
+        out,
+        "opencode_lines_added_total",
+        "Lines added by session diffs.",
+    )?;
+    writeln!(out, "opencode_lines_added_total {}", totals.diffs.additions)?;
+
+    header(
+        out,
+        "opencode_lines_removed_total",
+        "Lines removed by session diffs.",
+    )?;
+    writeln!(
+        out,
+        "opencode_lines_removed_total {}",
+        totals.diffs.deletions
+    )?;
+
+    header(out, "opencode_tool_calls_total", "Tool calls by tool name.")?;
+    let mut tools: Vec<(&str, u64)> = totals
+        .tools
+        .iter()
+        .map(|(name, count)| (name.as_ref(), *count))
+        .collect();
+    tools.sort_unstable();
+    for (tool, count) in tools {
+        writeln!(
+            out,
+            "opencode_tool_calls_total{{tool=\"{}\"}} {}",
+            label(tool),
+            count
+        )?;
+    }
+
+    header(
+        out,
+        "opencode_model_tokens_total",
+        "Tokens by model and type.",
+    )?;
+    for m in &models {
+        for (kind, value) in token_types(&m.tokens) {
+            writeln!(
+                out,
+                "opencode_model_tokens_total{{provider=\"{}\",model=\"{}\",type=\"{}\"}} {}",
+                label(&m.provider),
+                label(&m.short_name),
+                kind,
+                value
+            )?;
+        }
+    }
+
+    header(out, "opencode_model_messages_total", "Messages by model.")?;
+    for m in &models {
+        writeln!(
+            out,
+            "opencode_model_messages_total{{provider=\"{}\",model=\"{}\"}} {}",
+            label(&m.provider),
+            label(&m.short_name),
+            m.messages
+        )?;
+    }
+
+    header(
+        out,
+        "opencode_model_cost_usd_total",
+        "Cost in USD by model.",
+    )?;
+    for m in &models {
+        writeln!(
+            out,
+            "opencode_model_cost_usd_total{{provider=\"{}\",model=\"{}\"}} {}",
+            label(&m.provider),
+            label(&m.short_name),
+            m.cost
+        )?;
+    }
+    Ok(())
+}
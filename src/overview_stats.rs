@@ -1,4 +1,4 @@
-use crate::cost::estimate_cost;
+use crate::cost::estimate_model_cost;
 use crate::stats::{DayStat, ModelUsage};
 use chrono::{Datelike, NaiveDate};
 use rustc_hash::FxHashMap;
@@ -109,7 +109,6 @@ pub fn calculate(
     let mut tokens: u64 = 0;
     let mut period_buckets = [0u64; 4];
     let mut day_buckets = [0u64; 7];
-    let mut lang_counts: FxHashMap<&'static str, u64> = FxHashMap::default();
 
     let mut peak_day: Option<&str> = None;
     let mut start_day: Option<&str> = None;
@@ -144,14 +143,6 @@ pub fn calculate(
                 18..=23 => 3,
                 _ => 0,
             }] += 1;
-
-            for d in &session.file_diffs {
-                if let Some((_, ext)) = d.path.rsplit_once('.') {
-                    if let Some(l) = lang(ext) {
-                        *lang_counts.entry(l).or_insert(0) += (d.additions + d.deletions).max(1);
-                    }
-                }
-            }
         }
 
         if let Ok(d) = NaiveDate::parse_from_str(day_key, "%Y-%m-%d") {
@@ -160,35 +151,16 @@ pub fn calculate(
         }
     }
 
-    let est: f64 = models
-        .iter()
-        .filter_map(|m| {
-            m.name
-                .split('/')
-                .next_back()
-                .and_then(|n| estimate_cost(n, &m.tokens))
-        })
-        .sum();
+    let est: f64 = models.iter().filter_map(estimate_model_cost).sum();
 
-    let (top_langs, has_more_langs) = if lang_counts.is_empty() {
-        (Vec::new(), false)
-    } else {
-        let total: u64 = lang_counts.values().sum();
-        let mut v: Vec<_> = lang_counts.into_iter().collect();
-        let has_more = v.len() > 5;
-        if has_more {
-            v.select_nth_unstable_by(4, |a, b| b.1.cmp(&a.1));
-            v[..5].sort_unstable_by(|a, b| b.1.cmp(&a.1));
-        } else {
-            v.sort_unstable_by(|a, b| b.1.cmp(&a.1));
-        }
-        let top: Vec<_> = v
-            .iter()
-            .take(if has_more { 4 } else { 5 })
-            .map(|(l, c)| (l.to_string(), (*c as f64 / total as f64) * 100.0))
-            .collect();
-        (top, has_more)
-    };
+    let langs = language_counts(per_day);
+    let lang_total: u64 = langs.iter().map(|(_, c)| c).sum();
+    let has_more_langs = langs.len() > 5;
+    let top_langs: Vec<(String, f64)> = langs
+        .iter()
+        .take(if has_more_langs { 4 } else { 5 })
+        .map(|(l, c)| (l.to_string(), (*c as f64 / lang_total as f64) * 100.0))
+        .collect();
 
     OverviewStats {
         peak_day: peak_day.map(fmt_date).unwrap_or_else(|| "—".into()),
@@ -235,6 +207,43 @@ pub fn calculate(
     }
 }
 
+/// Lines changed per language from session file diffs, largest first.
+pub fn language_counts(per_day: &FxHashMap<String, DayStat>) -> Vec<(&'static str, u64)> {
+    let mut counts: FxHashMap<&'static str, u64> = FxHashMap::default();
+    for session in per_day.values().flat_map(|d| d.sessions.values()) {
+        for d in &session.file_diffs {
+            if let Some((_, ext)) = d.path.rsplit_once('.') {
+                if let Some(l) = lang(ext) {
+                    *counts.entry(l).or_insert(0) += (d.additions + d.deletions).max(1);
+                }
+            }
+        }
+    }
+    let mut v: Vec<(&'static str, u64)> = counts.into_iter().collect();
+    v.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    v
+}
+
+/// Session-days per project directory name, as listed in TOP PROJECTS.
+pub fn project_counts(per_day: &FxHashMap<String, DayStat>) -> Vec<(String, usize)> {
+    let mut counts: FxHashMap<String, usize> = FxHashMap::default();
+    for session in per_day.values().flat_map(|d| d.sessions.values()) {
+        let path = session.path_root.as_ref();
+        let name = if path.is_empty() {
+            "home".to_string()
+        } else {
+            path.rsplit('/')
+                .find(|s| !s.is_empty())
+                .unwrap_or("home")
+                .to_string()
+        };
+        *counts.entry(name).or_insert(0) += 1;
+    }
+    let mut projects: Vec<(String, usize)> = counts.into_iter().collect();
+    projects.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    projects
+}
+
 fn fmt_duration(ms: i64) -> String {
     if ms <= 0 {
         return "0h 0m".into();
@@ -0,0 +1,160 @@
+//! Pricing diagnostics: which catalog entry prices each model, and how it
+//! was matched.
+
+use crate::cli::invalid_arg;
+use crate::cost::{explain_pricing, MatchStrategy, ModelPricing, PriceMatch};
+use crate::date_range::DateRange;
+use crate::stats::{format_number_full, ModelUsage};
+use crate::ui::helpers::truncate_with_ellipsis;
+use std::io::{self, Write};
+
+const MODEL_COL_WIDTH: usize = 32;
+const KEY_COL_WIDTH: usize = 36;
+
+/// How one used model is priced.
+#[derive(Clone)]
+pub struct PricingRow {
+    pub model: Box<str>,
+    pub tokens: u64,
+    pub found: PriceMatch,
+}
+
+/// Every model in `models`, unmatched and fuzzy matches first, then by tokens.
+pub fn diagnose(models: &[ModelUsage]) -> Vec<PricingRow> {
+    let mut rows: Vec<PricingRow> = models
+        .iter()
+        .map(|m| PricingRow {
+            model: m.name.clone(),
+            tokens: m.tokens.total(),
+            found: explain_pricing(&m.name),
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.found
+            .strategy
+            .cmp(&a.found.strategy)
+            .then_with(|| b.tokens.cmp(&a.tokens))
+    });
+    rows
+}
+
+/// USD per million tokens.
+pub fn per_million(rate: f64) -> String {
+    format!("{:.2}", rate * 1_000_000.0)
+}
+
+/// Run the `pricing` subcommand.
+pub fn run(args: &[String], range: &DateRange) -> io::Result<()> {
+    let mut unmatched_only = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--unmatched" => unmatched_only = true,
+            "--pin" => {
+                let (Some(model), Some(key)) = (iter.next(), iter.next()) else {
+                    return Err(invalid_arg("--pin expects a model and a catalog key"));
+                };
+                let path = crate::cost::pin_alias(model, key)?;
+                println!("Pinned {} to {} in {}", model, key, path.display());
+                return Ok(());
+            }
+            "-h" | "--help" => {
+                println!(
+                    "Usage: opencode-stats-tui pricing [--unmatched] [--since DATE] [--until DATE]"
+                );
+                println!("       opencode-stats-tui pricing --pin MODEL KEY");
+                println!("Lists how each used model was matched to a pricing catalog entry.");
+                println!("--pin prices MODEL as catalog entry KEY via the override file.");
+                return Ok(());
+            }
+            other => return Err(invalid_arg(&format!("unknown argument: {}", other))),
+        }
+    }
+
+    crate::cost::init_pricing();
+    let mut stats = crate::stats_cache::load_stats();
+    crate::date_range::apply_to_stats(&mut stats, range);
+    let rows = diagnose(&stats.model_usage);
+    let mut out = io::stdout().lock();
+    write_rows(&mut out, &rows, unmatched_only)
+}
+
+fn write_rows(out: &mut impl Write, rows: &[PricingRow], unmatched_only: bool) -> io::Result<()> {
+    if let Some(source) = crate::cost::pricing_source() {
+        writeln!(
+            out,
+            "PRICING ({}, {})",
+            source.catalog_name(),
+            source.label()
+        )?;
+    }
+    writeln!(
+        out,
+        "  {:<mw$} {:<14} {:>5}  {:<kw$} {:>8} {:>8} {:>8} {:>8}",
+        "MODEL",
+        "MATCH",
+        "SCORE",
+        "KEY",
+        "IN $/M",
+        "OUT $/M",
+        "CACHE R",
+        "CACHE W",
+        mw = MODEL_COL_WIDTH,
+        kw = KEY_COL_WIDTH
+    )?;
+    let mut shown = 0;
+    for row in rows {
+        let unmatched = row.found.strategy == MatchStrategy::Unmatched;
+        if unmatched_only && !unmatched {
+            continue;
+        }
+        shown += 1;
+        // An unmatched model shows its closest key as a suggestion
+        let key = match (&row.found.key, unmatched) {
+            (Some(key), true) => format!("({})", key),
+            (Some(key), false) => key.clone(),
+            (None, _) => "-".to_string(),
+        };
+        let rates = |f: fn(&ModelPricing) -> f64| match (&row.found.pricing, unmatched) {
+            (Some(p), false) => per_million(f(p)),
+            _ => "-".to_string(),
+        };
+        writeln!(
+            out,
+            "  {:<mw$} {:<14} {:>4}%  {:<kw$} {:>8} {:>8} {:>8} {:>8}",
+            truncate_with_ellipsis(&row.model, MODEL_COL_WIDTH),
+            row.found.strategy.label(),
+            row.found.score,
+            truncate_with_ellipsis(&key, KEY_COL_WIDTH),
+            rates(|p| p.prompt),
+            rates(|p| p.completion),
+            rates(|p| p.input_cache_read),
+            rates(|p| p.input_cache_write),
+            mw = MODEL_COL_WIDTH,
+            kw = KEY_COL_WIDTH
+        )?;
+    }
+    if shown == 0 {
+        writeln!(out, "  No models")?;
+    }
+    let unmatched = rows
+        .iter()
+        .filter(|r| r.found.strategy == MatchStrategy::Unmatched)
+        .count();
+    if unmatched > 0 {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{} of {} models unmatched ({} tokens). Pin one with: pricing --pin MODEL KEY",
+            unmatched,
+            rows.len(),
+            format_number_full(
+                rows.iter()
+                    .filter(|r| r.found.strategy == MatchStrategy::Unmatched)
+                    .map(|r| r.tokens)
+                    .sum()
+            )
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,198 @@
+//! Headless text report printed to stdout.
+
+use crate::cli::invalid_arg;
+use crate::date_range::{DateRange, RangePreset};
+use crate::stats::{
+    format_active_duration, format_number_full, DayStat, ModelUsage, Stats, Totals,
+};
+use crate::ui::helpers::truncate_with_ellipsis;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+const DEFAULT_TOP_MODELS: usize = 10;
+const MODEL_COL_WIDTH: usize = 32;
+
+/// Run the `report` subcommand.
+pub fn run(args: &[String], range: &DateRange) -> io::Result<()> {
+    let mut top = DEFAULT_TOP_MODELS;
+    let mut markdown = false;
+    let mut period: Option<RangePreset> = None;
+    let mut output: Option<PathBuf> = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--top" | "-n" => {
+                top = iter
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| invalid_arg("--top expects a number"))?;
+            }
+            "--markdown" | "--md" => markdown = true,
+            "--period" => {
+                period = Some(
+                    iter.next()
+                        .and_then(|v| crate::markdown::parse_period(v))
+                        .ok_or_else(|| {
+                            invalid_arg("--period expects week, month, last-month or all")
+                        })?,
+                );
+            }
+            "-o" | "--output" => {
+                output = Some(
+                    iter.next()
+                        .map(PathBuf::from)
+                        .ok_or_else(|| invalid_arg("--output expects a path"))?,
+                );
+            }
+            "-h" | "--help" => {
+                println!(
+                    "Usage: opencode-stats-tui report [--top N] [--since DATE] [--until DATE]"
+                );
+                println!(
+                    "       opencode-stats-tui report --markdown [--period week|month|last-month|all] [--output FILE]"
+                );
+                return Ok(());
+            }
+            other => return Err(invalid_arg(&format!("unknown argument: {}", other))),
+        }
+    }
+
+    // Explicit --since/--until win over --period; Markdown defaults to a week
+    let today = chrono::Local::now().date_naive();
+    let range = match period {
+        _ if !range.is_all() => *range,
+        Some(preset) => preset.range(today),
+        None if markdown => RangePreset::Last7Days.range(today),
+        None => *range,
+    };
+
+    let mut stats = crate::stats_cache::load_stats();
+    crate::date_range::apply_to_stats(&mut stats, &range);
+    let mut out: Box<dyn Write> = match &output {
+        Some(path) => Box::new(io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    if markdown {
+        crate::markdown::write_markdown(&mut out, &stats, &range)?;
+    } else {
+        write_report(&mut out, &stats, &range, top)?;
+    }
+    out.flush()
+}
+
+fn write_report(
+    out: &mut impl Write,
+    stats: &Stats,
+    range: &DateRange,
+    top: usize,
+) -> io::Result<()> {
+    write_totals(out, &stats.totals, range)?;
+    writeln!(out)?;
+
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    write_today(out, &today, stats.per_day.get(&today))?;
+    writeln!(out)?;
+
+    write_models(out, &stats.model_usage, top)
+}
+
+fn write_row(out: &mut impl Write, label: &str, value: &str) -> io::Result<()> {
+    writeln!(out, "  {:<12}{:>18}", label, value)
+}
+
+fn write_totals(out: &mut impl Write, totals: &Totals, range: &DateRange) -> io::Result<()> {
+    if range.is_all() {
+        writeln!(out, "TOTALS")?;
+    } else {
+        writeln!(out, "TOTALS ({})", range.label())?;
+    }
+    write_row(
+        out,
+        "Sessions",
+        &format_number_full(totals.sessions.len() as u64),
+    )?;
+    write_row(out, "Messages", &format_number_full(totals.messages))?;
+    write_row(out, "Prompts", &format_number_full(totals.prompts))?;
+    write_row(out, "Input", &format_number_full(totals.tokens.input))?;
+    write_row(out, "Output", &format_number_full(totals.tokens.output))?;
+    write_row(
+        out,
+        "Thinking",
+        &format_number_full(totals.tokens.reasoning),
+    )?;
+    write_row(
+        out,
+        "Cache Read",
+        &format_number_full(totals.tokens.cache_read),
+    )?;
+    write_row(
+        out,
+        "Cache Write",
+        &format_number_full(totals.tokens.cache_write),
+    )?;
+    write_row(out, "Lines", &format_diffs(totals.diffs))?;
+    write_row(out, "Cost", &format!("${:.2}", totals.display_cost()))
+}
+
+fn write_today(out: &mut impl Write, today: &str, day: Option<&DayStat>) -> io::Result<()> {
+    writeln!(out, "TODAY ({})", today)?;
+    let Some(day) = day else {
+        writeln!(out, "  No activity")?;
+        return Ok(());
+    };
+    let active_ms: i64 = day.sessions.values().map(|s| s.active_duration_ms).sum();
+    write_row(
+        out,
+        "Sessions",
+        &format_number_full(day.sessions.len() as u64),
+    )?;
+    write_row(out, "Messages", &format_number_full(day.messages))?;
+    write_row(out, "Prompts", &format_number_full(day.prompts))?;
+    write_row(out, "Tokens", &format_number_full(day.tokens.total()))?;
+    write_row(out, "Lines", &format_diffs(day.diffs))?;
+    write_row(out, "Active", &format_active_duration(active_ms))?;
+    write_row(out, "Cost", &format!("${:.2}", day.display_cost()))
+}
+
+fn write_models(out: &mut impl Write, models: &[ModelUsage], top: usize) -> io::Result<()> {
+    writeln!(out, "TOP MODELS")?;
+    if models.is_empty() || top == 0 {
+        writeln!(out, "  No models")?;
+        return Ok(());
+    }
+
+    let mut ranked: Vec<&ModelUsage> = models.iter().collect();
+    ranked.sort_unstable_by_key(|m| std::cmp::Reverse(m.tokens.total()));
+
+    writeln!(
+        out,
+        "  {:<w$} {:>9} {:>9} {:>16} {:>10}",
+        "MODEL",
+        "SESSIONS",
+        "MESSAGES",
+        "TOKENS",
+        "COST",
+        w = MODEL_COL_WIDTH
+    )?;
+    for m in ranked.into_iter().take(top) {
+        writeln!(
+            out,
+            "  {:<w$} {:>9} {:>9} {:>16} {:>10}",
+            truncate_with_ellipsis(&m.name, MODEL_COL_WIDTH),
+            format_number_full(m.sessions.len() as u64),
+            format_number_full(m.messages),
+            format_number_full(m.tokens.total()),
+            format!("${:.2}", m.cost),
+            w = MODEL_COL_WIDTH
+        )?;
+    }
+    Ok(())
+}
+
+fn format_diffs(diffs: crate::stats::Diffs) -> String {
+    format!(
+        "+{} -{}",
+        format_number_full(diffs.additions),
+        format_number_full(diffs.deletions)
+    )
+}
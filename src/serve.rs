@@ -0,0 +1,510 @@
+//! Read-only local HTTP JSON API.
+//!
+//! Responses reuse the export schema. The document is rebuilt lazily after the
+//! live watcher feeds changes through `StatsCache::update_files`, and chat or
+//! session detail requests load messages on demand like the session modal.
+
+use crate::cli::invalid_arg;
+use crate::date_range::DateRange;
+use crate::export::{self, ExportDocument, ExportTokens, StatsView};
+use crate::stats::{ChatMessage, MessageContent, Stats};
+use crate::stats_cache::StatsCache;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_PORT: u16 = 4097;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_REQUEST_BYTES: u64 = 16 * 1024;
+
+const ENDPOINTS: [&str; 9] = [
+    "/totals",
+    "/days",
+    "/days/{date}",
+    "/sessions",
+    "/sessions/{id}",
+    "/sessions/{id}/chat",
+    "/messages/{id}/parts/{index}",
+    "/models",
+    "/agents",
+];
+
+/// Stats shared between the watcher thread and request handlers.
+struct ServeState {
+    range: DateRange,
+    stats: RwLock<Stats>,
+    /// Built on first request after each update.
+    doc: Mutex<Option<Arc<ExportDocument>>>,
+}
+
+impl ServeState {
+    fn set_stats(&self, mut stats: Stats) {
+        crate::date_range::apply_to_stats(&mut stats, &self.range);
+        *self.stats.write() = stats;
+        *self.doc.lock() = None;
+    }
+
+    fn document(&self) -> Arc<ExportDocument> {
+        let mut doc = self.doc.lock();
+        if let Some(doc) = doc.as_ref() {
+            return doc.clone();
+        }
+        let built = Arc::new(export::build_document(&StatsView::from_stats(
+            &self.stats.read(),
+        )));
+        *doc = Some(built.clone());
+        built
+    }
+}
+
+/// Run the `serve` subcommand.
+pub fn run(args: &[String], range: &DateRange) -> io::Result<()> {
+    let mut port = DEFAULT_PORT;
+    let mut cors: Option<String> = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-p" | "--port" => {
+                port = iter
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| invalid_arg("--port expects a number"))?;
+            }
+            "--cors" => {
+                cors = Some(
+                    iter.next()
+                        .cloned()
+                        .ok_or_else(|| invalid_arg("--cors expects an origin"))?,
+                );
+            }
+            "-h" | "--help" => {
+                println!(
+                    "Usage: opencode-stats-tui serve [--port N] [--cors ORIGIN] [--since DATE] [--until DATE]"
+                );
+                println!(
+                    "Serves read-only JSON on 127.0.0.1 (default port {}): {}",
+                    DEFAULT_PORT,
+                    ENDPOINTS.join(", ")
+                );
+                return Ok(());
+            }
+            other => return Err(invalid_arg(&format!("unknown argument: {}", other))),
+        }
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    let cache = StatsCache::new(crate::stats::default_storage_dir())
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let state = Arc::new(ServeState {
+        range: *range,
+        stats: RwLock::new(Stats::default()),
+        doc: Mutex::new(None),
+    });
+    state.set_stats(cache.load_or_compute());
+
+    let watcher_state = state.clone();
+    std::thread::spawn(move || {
+        let result = crate::live_watcher::watch_storage_blocking(POLL_INTERVAL, |files| {
+            if files.is_empty() {
+                return ControlFlow::Continue(());
+            }
+            let files: Vec<String> = files
+                .iter()
+                .filter_map(|p| p.to_str().map(ToString::to_string))
+                .collect();
+            let u = cache.update_files(files);
+            watcher_state.set_stats(Stats {
+                totals: u.totals,
+                per_day: u.per_day,
+                session_titles: u.session_titles,
+                model_usage: u.model_usage,
+                session_message_files: u.session_message_files,
+                parent_map: u.parent_map,
+                children_map: u.children_map,
+                ..Stats::default()
+            });
+            ControlFlow::Continue(())
+        });
+        if let Err(e) = result {
+            eprintln!("warning: live updates disabled: {}", e);
+        }
+    });
+
+    println!("Serving on http://{}", listener.local_addr()?);
+    let cors: Option<Arc<str>> = cors.map(Into::into);
+    for stream in listener.incoming().flatten() {
+        let state = state.clone();
+        let cors = cors.clone();
+        std::thread::spawn(move || handle_connection(stream, &state, cors.as_deref()));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// HTTP
+// ============================================================================
+
+struct Response {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(value: &impl Serialize) -> Self {
+        match serde_json::to_vec_pretty(value) {
+            Ok(body) => Self { status: 200, body },
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        let body = serde_json::to_vec(&serde_json::json!({ "error": message })).unwrap_or_default();
+        Self { status, body }
+    }
+}
+
+#[inline]
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: &ServeState, cors: Option<&str>) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let mut reader = BufReader::new((&stream).take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+        return;
+    }
+    // Only the Host header matters; bodies are never read
+    let mut host_ok = true;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if line.trim_end().is_empty() => break,
+            Ok(_) => {
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("host") {
+                        host_ok = is_local_host(value.trim());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("/");
+    let response = match method {
+        // Rejecting foreign Host headers blocks DNS rebinding from web pages
+        _ if !host_ok => Response::error(400, "host must be localhost"),
+        "GET" | "HEAD" => route(target, state),
+        _ => Response::error(405, "only GET is supported"),
+    };
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n",
+        response.status,
+        status_text(response.status),
+        response.body.len()
+    );
+    if let Some(origin) = cors {
+        head.push_str(&format!("Access-Control-Allow-Origin: {}\r\n", origin));
+    }
+    if response.status == 405 {
+        head.push_str("Allow: GET, HEAD\r\n");
+    }
+    head.push_str("\r\n");
+
+    let mut out = &stream;
+    let _ = out.write_all(head.as_bytes());
+    if method != "HEAD" {
+        let _ = out.write_all(&response.body);
+    }
+    let _ = out.flush();
+}
+
+#[inline]
+fn is_local_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or(""),
+        None => host.split(':').next().unwrap_or(""),
+    };
+    matches!(name, "localhost" | "127.0.0.1" | "::1")
+}
+
+/// Decode `%XX` escapes in a path segment or query value.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn query_param(query: &str, key: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| percent_decode(v))
+}
+
+// ============================================================================
+// Routes
+// ============================================================================
+
+fn route(target: &str, state: &ServeState) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(percent_decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let day = query_param(query, "day");
+    if day
+        .as_deref()
+        .is_some_and(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").is_err())
+    {
+        return Response::error(400, "day must be YYYY-MM-DD");
+    }
+
+    match segments.as_slice() {
+        [] => Response::json(&serde_json::json!({
+            "schema_version": export::SCHEMA_VERSION,
+            "range": state.range.label(),
+            "endpoints": ENDPOINTS,
+        })),
+        ["totals"] => Response::json(&state.document().totals),
+        ["days"] => Response::json(&state.document().days),
+        ["days", date] => {
+            let doc = state.document();
+            match doc.days.iter().find(|d| d.date == *date) {
+                Some(d) => Response::json(d),
+                None => Response::error(404, "no activity on this day"),
+            }
+        }
+        ["sessions"] => Response::json(&state.document().sessions),
+        ["sessions", id] => session_response(state, id, day.as_deref()),
+        ["sessions", id, "chat"] => chat_response(state, id, day.as_deref()),
+        ["messages", id, "parts", index] => part_text_response(id, index),
+        ["models"] => Response::json(&state.document().models),
+        ["agents"] => Response::json(&state.document().agents),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+#[derive(Serialize)]
+struct ModelStatsJson {
+    model: String,
+    messages: u64,
+    prompts: u64,
+    tokens: ExportTokens,
+    cost: f64,
+}
+
+#[derive(Serialize)]
+struct SessionResponse<'a> {
+    #[serde(flatten)]
+    session: &'a export::ExportSession,
+    /// Per-model breakdown including subagent sessions.
+    model_stats: Vec<ModelStatsJson>,
+}
+
+/// Message files of a session and its subagent children.
+fn session_files(stats: &Stats, id: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = stats
+        .session_message_files
+        .get(id)
+        .map(|f| f.iter().cloned().collect())
+        .unwrap_or_default();
+    for child in stats.children_map.get(id).into_iter().flatten() {
+        if let Some(child_files) = stats.session_message_files.get(child.as_ref()) {
+            files.extend(child_files.iter().cloned());
+        }
+    }
+    files
+}
+
+fn session_response(state: &ServeState, id: &str, day: Option<&str>) -> Response {
+    let doc = state.document();
+    let Some(session) = doc.sessions.iter().find(|s| s.id == id) else {
+        return Response::error(404, "unknown session");
+    };
+    let (files, parent_map) = {
+        let stats = state.stats.read();
+        (session_files(&stats, id), stats.parent_map.clone())
+    };
+    let details = crate::stats::load_session_details(id, Some(&files), day, &parent_map);
+    Response::json(&SessionResponse {
+        session,
+        model_stats: details
+            .model_stats
+            .iter()
+            .map(|m| ModelStatsJson {
+                model: m.name.to_string(),
+                messages: m.messages,
+                prompts: m.prompts,
+                tokens: ExportTokens::from(&m.tokens),
+                cost: m.cost,
+            })
+            .collect(),
+    })
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ChatPartJson<'a> {
+    Text {
+        text: &'a str,
+        /// Set when `text` was cut short.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_id: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        index: Option<usize>,
+    },
+    Tool {
+        name: &'a str,
+        file_path: Option<&'a str>,
+        input: Option<&'a str>,
+        additions: Option<u64>,
+        deletions: Option<u64>,
+        status: Option<&'a str>,
+        output: Option<&'a str>,
+        error: Option<&'a str>,
+        duration_ms: Option<i64>,
+    },
+    Thinking {
+        message_id: &'a str,
+        index: usize,
+        tokens: u64,
+        tokens_estimated: bool,
+    },
+}
+
+#[derive(Serialize)]
+struct ChatMessageJson<'a> {
+    role: &'a str,
+    model: Option<&'a str>,
+    agent: Option<&'a str>,
+    is_subagent: bool,
+    parts: Vec<ChatPartJson<'a>>,
+}
+
+impl<'a> From<&'a ChatMessage> for ChatMessageJson<'a> {
+    fn from(m: &'a ChatMessage) -> Self {
+        Self {
+            role: &m.role,
+            model: m.model.as_deref(),
+            agent: m.agent_label.as_deref(),
+            is_subagent: m.is_subagent,
+            parts: m
+                .parts
+                .iter()
+                .map(|p| match p {
+                    MessageContent::Text(t) => ChatPartJson::Text {
+                        text: &t.text,
+                        message_id: t.full.as_ref().map(|(id, _)| &**id),
+                        index: t.full.as_ref().map(|(_, i)| *i),
+                    },
+                    MessageContent::ToolCall(t) => ChatPartJson::Tool {
+                        name: &t.name,
+                        file_path: t.file_path.as_deref(),
+                        input: t.input.as_deref(),
+                        additions: t.additions,
+                        deletions: t.deletions,
+                        status: t.status.as_deref(),
+                        output: t.output.as_deref(),
+                        error: t.error.as_deref(),
+                        duration_ms: t.duration_ms,
+                    },
+                    MessageContent::Thinking(t) => ChatPartJson::Thinking {
+                        message_id: &t.message_id,
+                        index: t.index,
+                        tokens: t.tokens,
+                        tokens_estimated: t.estimated,
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+fn chat_response(state: &ServeState, id: &str, day: Option<&str>) -> Response {
+    let messages = {
+        let stats = state.stats.read();
+        if !stats.session_message_files.contains_key(id) {
+            return Response::error(404, "unknown session");
+        }
+        match stats.children_map.get(id) {
+            Some(child_ids) => {
+                let children: Vec<(Box<str>, Box<str>)> = child_ids
+                    .iter()
+                    .map(|cid| {
+                        let agent = stats
+                            .session_titles
+                            .get(cid)
+                            .map(|t| crate::stats::extract_agent_name(t))
+                            .unwrap_or_else(|| "subagent".into());
+                        (cid.clone(), agent)
+                    })
+                    .collect();
+                crate::stats::load_combined_session_chat(
+                    id,
+                    &children,
+                    &stats.session_message_files,
+                    day,
+                )
+                .0
+            }
+            None => {
+                let files = session_files(&stats, id);
+                drop(stats);
+                crate::stats::load_session_chat_with_max_ts(id, Some(&files), day).0
+            }
+        }
+    };
+    let json: Vec<ChatMessageJson> = messages.iter().map(ChatMessageJson::from).collect();
+    Response::json(&json)
+}
+
+/// Reasoning and the rest of long texts are left out of chat responses and
+/// fetched per part.
+fn part_text_response(id: &str, index: &str) -> Response {
+    let Ok(index) = index.parse::<usize>() else {
+        return Response::error(400, "index must be a number");
+    };
+    match crate::stats::load_part_text(id, index) {
+        Some(text) => Response::json(&serde_json::json!({ "text": text })),
+        None => Response::error(404, "no such part"),
+    }
+}
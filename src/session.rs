@@ -1,9 +1,10 @@
 //! Session modal for detailed session view
 
-use crate::cost::estimate_cost;
+use crate::cost::estimate_cost_on;
 use crate::stats::{
-    format_active_duration, format_number, load_session_details, ChatMessage, MessageContent,
-    SessionDetails, SessionStat,
+    clamp_ms, format_active_duration, format_latency, format_number, load_part_text,
+    load_session_details, ChatMessage, Latency, MessageContent, SessionDetails, SessionStat,
+    TextPart, ThinkingInfo,
 };
 use crate::theme::ThemeColors;
 use crossterm::event::{KeyCode, MouseEvent, MouseEventKind};
@@ -43,12 +44,17 @@ pub struct SessionModal {
     pub expanded_agents: FxHashSet<Box<str>>,
     pub expanded_messages: FxHashSet<usize>,
     pub expanded_tools: FxHashSet<Box<str>>,
+    /// Texts loaded on demand by (message id, part position): expanded
+    /// thinking blocks and cut-off parts of expanded messages. Entries are
+    /// dropped on collapse so memory stays bounded.
+    part_texts: FxHashMap<(Box<str>, usize), Box<str>>,
     chat_click_targets: Vec<(u16, ChatClickTarget)>,
     expanded_info_agents: FxHashSet<Box<str>>,
     expanded_info_models: FxHashSet<Box<str>>,
     info_click_targets: Vec<(u16, InfoClickTarget)>,
     cached_chat_blocks: Vec<ChatBlock>,
     cached_msg_count: usize,
+    source_label: Option<&'static str>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -56,6 +62,7 @@ pub enum ChatClickTarget {
     Agent(Box<str>),
     Message(usize),
     ToolBox(Box<str>),
+    Thinking(Box<str>, usize),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -86,6 +93,7 @@ impl SessionModal {
         self.expanded_agents.clear();
         self.expanded_messages.clear();
         self.expanded_tools.clear();
+        self.part_texts.clear();
         self.chat_click_targets.clear();
         self.expanded_info_agents.clear();
         self.expanded_info_models.clear();
@@ -94,6 +102,55 @@ impl SessionModal {
         self.cached_msg_count = 0;
     }
 
+    /// Indices of subagent messages shown in the card for `name`.
+    fn subagent_message_indices(&self, name: &str) -> Vec<usize> {
+        self.chat_messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| {
+                m.is_subagent && m.agent_label.as_deref().unwrap_or("subagent") == name
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Load the untruncated text of cut-off parts in the given messages.
+    fn load_full_texts(&mut self, indices: &[usize]) {
+        for &idx in indices {
+            let Some(msg) = self.chat_messages.get(idx) else {
+                continue;
+            };
+            for part in &msg.parts {
+                if let MessageContent::Text(TextPart {
+                    full: Some(key), ..
+                }) = part
+                {
+                    if !self.part_texts.contains_key(key) {
+                        if let Some(text) = load_part_text(&key.0, key.1) {
+                            self.part_texts.insert(key.clone(), text.into());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn drop_full_texts(&mut self, indices: &[usize]) {
+        for &idx in indices {
+            let Some(msg) = self.chat_messages.get(idx) else {
+                continue;
+            };
+            for part in &msg.parts {
+                if let MessageContent::Text(TextPart {
+                    full: Some(key), ..
+                }) = part
+                {
+                    self.part_texts.remove(key);
+                }
+            }
+        }
+    }
+
     #[inline]
     pub fn new() -> Self {
         Self {
@@ -109,12 +166,14 @@ impl SessionModal {
             expanded_agents: FxHashSet::default(),
             expanded_messages: FxHashSet::default(),
             expanded_tools: FxHashSet::default(),
+            part_texts: FxHashMap::default(),
             chat_click_targets: Vec::new(),
             expanded_info_agents: FxHashSet::default(),
             expanded_info_models: FxHashSet::default(),
             info_click_targets: Vec::new(),
             cached_chat_blocks: Vec::new(),
             cached_msg_count: 0,
+            source_label: None,
         }
     }
 
@@ -128,6 +187,16 @@ impl SessionModal {
         parent_map: &FxHashMap<Box<str>, Box<str>>,
     ) {
         let details = load_session_details(session_id, files, day_filter, parent_map);
+        // Importing tool, else the storage root when several are configured
+        self.source_label = match files
+            .and_then(|f| f.first())
+            .map(|p| crate::import::Source::of_path(p))
+        {
+            Some(tool) if tool != crate::import::Source::OpenCode => Some(tool.label()),
+            _ => files
+                .filter(|_| crate::stats::get_labeled_roots().len() > 1)
+                .and_then(|f| f.iter().find_map(|p| crate::stats::root_label_for_path(p))),
+        };
         self.session_details = Some(details);
         self.current_session = Some(session_stat.clone());
         self.chat_messages = chat_messages;
@@ -301,14 +370,21 @@ impl SessionModal {
                             match target {
                                 ChatClickTarget::Agent(name) => {
                                     let name = name.clone();
-                                    if !self.expanded_agents.remove(&name) {
+                                    let indices = self.subagent_message_indices(&name);
+                                    if self.expanded_agents.remove(&name) {
+                                        self.drop_full_texts(&indices);
+                                    } else {
                                         self.expanded_agents.insert(name);
+                                        self.load_full_texts(&indices);
                                     }
                                 }
                                 ChatClickTarget::Message(idx) => {
                                     let idx = *idx;
-                                    if !self.expanded_messages.remove(&idx) {
+                                    if self.expanded_messages.remove(&idx) {
+                                        self.drop_full_texts(&[idx]);
+                                    } else {
                                         self.expanded_messages.insert(idx);
+                                        self.load_full_texts(&[idx]);
                                     }
                                 }
                                 ChatClickTarget::ToolBox(id) => {
@@ -317,6 +393,14 @@ impl SessionModal {
                                         self.expanded_tools.insert(id);
                                     }
                                 }
+                                ChatClickTarget::Thinking(msg_id, index) => {
+                                    let key = (msg_id.clone(), *index);
+                                    if self.part_texts.remove(&key).is_none() {
+                                        let text =
+                                            load_part_text(msg_id, *index).unwrap_or_default();
+                                        self.part_texts.insert(key, text.into());
+                                    }
+                                }
                             }
                             return true;
                         }
@@ -410,6 +494,7 @@ impl SessionModal {
         let mut lines = Vec::with_capacity(50);
         let device = crate::device::get_device_info();
         let device_display = device.display_name();
+        let session_day = crate::stats::get_day(Some(session.first_activity));
         let title = session_titles
             .get(&session.id)
             .map(|t| t.strip_prefix("New session - ").unwrap_or(t))
@@ -466,6 +551,15 @@ impl SessionModal {
                     ),
                 ]));
             }
+            if let Some(source) = self.source_label {
+                lines.push(Line::from(vec![
+                    Span::styled("    Source:   ", Style::default().fg(colors.text_secondary)),
+                    Span::styled(
+                        safe_truncate_plain(source, value_width),
+                        Style::default().fg(colors.host),
+                    ),
+                ]));
+            }
             let active_dur = format_active_duration(session.active_duration_ms);
             lines.push(Line::from(vec![
                 Span::styled("    Duration: ", Style::default().fg(colors.text_secondary)),
@@ -688,8 +782,13 @@ impl SessionModal {
                         ];
                         let responses = model.messages.saturating_sub(model.prompts);
                         let model_cost = model.cost;
-                        let model_est =
-                            estimate_cost(&model.name, &model.tokens).unwrap_or(model_cost);
+                        let model_est = estimate_cost_on(
+                            &model.name,
+                            &model.tokens,
+                            &model.priced_messages,
+                            &session_day,
+                        )
+                        .unwrap_or(model_cost);
                         let model_savings = model_est - model_cost;
                         let right_labels = [
                             ("Prompts", model.prompts.to_string(), colors.user),
@@ -790,13 +889,24 @@ impl SessionModal {
             ]));
         }
         let total_cost = session.cost;
-        // Use OpenRouter pricing for estimated cost
-        let est_cost = session
-            .models
-            .iter()
-            .next()
-            .and_then(|m| estimate_cost(m, &session.tokens))
-            .unwrap_or(total_cost);
+        // Use catalog pricing from the session's day for estimated cost,
+        // per model once details are loaded so long prompts get their tier
+        let est_cost = match details.filter(|d| !d.model_stats.is_empty()) {
+            Some(d) => d
+                .model_stats
+                .iter()
+                .map(|m| {
+                    estimate_cost_on(&m.name, &m.tokens, &m.priced_messages, &session_day)
+                        .unwrap_or(m.cost)
+                })
+                .sum(),
+            None => session
+                .models
+                .iter()
+                .next()
+                .and_then(|m| estimate_cost_on(m, &session.tokens, &[], &session_day))
+                .unwrap_or(total_cost),
+        };
         let savings = est_cost - total_cost;
         let (savings_text, savings_color) = if savings < 0.0 {
             (format!("-${:.2}", savings.abs()), colors.neg_savings)
@@ -1054,7 +1164,15 @@ impl SessionModal {
                         .push((lines.len() as u16, ChatClickTarget::Message(*idx)));
                     if &*msg.role == "user" {
                         user_count += 1;
-                        render_user_box(&mut lines, msg, box_w, is_expanded, user_count, colors);
+                        render_user_box(
+                            &mut lines,
+                            msg,
+                            box_w,
+                            is_expanded,
+                            user_count,
+                            &self.part_texts,
+                            colors,
+                        );
                     } else {
                         agent_count += 1;
                         render_agent_box(
@@ -1065,7 +1183,10 @@ impl SessionModal {
                             agent_count,
                             *idx,
                             &mut self.chat_click_targets,
-                            &self.expanded_tools,
+                            ChatExpansions {
+                                tools: &self.expanded_tools,
+                                part_texts: &self.part_texts,
+                            },
                             colors,
                         );
                     }
@@ -1134,6 +1255,7 @@ impl SessionModal {
                                 let is_user = &*m.role == "user";
                                 for part in &m.parts {
                                     if let MessageContent::Text(t) = part {
+                                        let t = part_text(t, &self.part_texts);
                                         let text = if is_user {
                                             filter_user_text(t)
                                         } else {
@@ -1221,7 +1343,7 @@ impl SessionModal {
                             'collect: for &mi in msg_indices {
                                 for part in &msgs[mi].parts {
                                     if let MessageContent::Text(t) = part {
-                                        let filtered = filter_user_text(t);
+                                        let filtered = filter_user_text(&t.text);
                                         if !filtered.trim().is_empty() {
                                             if !preview.is_empty() {
                                                 preview.push(' ');
@@ -1361,10 +1483,16 @@ impl SessionModal {
 struct ToolInvocation {
     file_path: Option<String>,
     input: Option<String>,
+    failed: bool,
+    /// Error text, else output, of a failed call.
+    failure: Option<String>,
 }
 
 struct ToolStatsEntry {
     count: usize,
+    errors: usize,
+    /// Durations in ms of the timed calls.
+    durations: Vec<u32>,
     invocations: Vec<ToolInvocation>,
 }
 
@@ -1382,12 +1510,24 @@ fn aggregate_tools_in_group(
                     .entry(normalize_tool_name(&tc.name))
                     .or_insert_with(|| ToolStatsEntry {
                         count: 0,
+                        errors: 0,
+                        durations: Vec::new(),
                         invocations: Vec::new(),
                     });
+                let failed = tc.failed();
                 entry.count += 1;
+                entry.errors += usize::from(failed);
+                if let Some(ms) = tc.duration_ms {
+                    entry.durations.push(clamp_ms(ms));
+                }
                 entry.invocations.push(ToolInvocation {
                     file_path: tc.file_path.as_deref().map(|s| s.to_string()),
                     input: tc.input.as_deref().map(|s| s.to_string()),
+                    failed,
+                    failure: failed
+                        .then(|| tc.error.as_deref().or(tc.output.as_deref()))
+                        .flatten()
+                        .map(|s| s.to_string()),
                 });
             }
         }
@@ -1411,6 +1551,7 @@ fn render_tool_stats_box<'a>(
     let frame_color = colors.border_default;
     let tool_header_color = colors.text_secondary;
     let tool_text_color = colors.text_secondary;
+    let fail_color = colors.remove_line;
     let toggle_label = if is_expanded {
         "▾ collapse"
     } else {
@@ -1427,7 +1568,20 @@ fn render_tool_stats_box<'a>(
 
     click_targets.push((lines.len() as u16, ChatClickTarget::ToolBox(target_id)));
 
-    let header = format!("⚙︎ tools used ({})", total_tools);
+    let total_errors: usize = tool_stats.values().map(|e| e.errors).sum();
+    let total_ms: u64 = tool_stats
+        .values()
+        .flat_map(|e| &e.durations)
+        .map(|&ms| ms as u64)
+        .sum();
+    let mut header = if total_errors > 0 {
+        format!("⚙︎ tools used ({}, {} failed)", total_tools, total_errors)
+    } else {
+        format!("⚙︎ tools used ({})", total_tools)
+    };
+    if total_ms > 0 {
+        header.push_str(&format!(" · Σ {}", format_latency(total_ms)));
+    }
     let dash_len = inner_w.saturating_sub(header.chars().count() + toggle_label.len() + 3);
     lines.push(Line::from(vec![
         Span::styled(prefix, Style::default().fg(dim_color)),
@@ -1458,7 +1612,22 @@ fn render_tool_stats_box<'a>(
 
     let tools_len = tools.len();
     for (idx, (name, entry)) in tools.iter().enumerate() {
-        let tool_line = format!("  {} (x{})", name, entry.count);
+        let (mut tool_line, line_color) = if entry.errors > 0 {
+            (
+                format!("  {} (x{}, {} failed)", name, entry.count, entry.errors),
+                fail_color,
+            )
+        } else {
+            (format!("  {} (x{})", name, entry.count), tool_text_color)
+        };
+        if let Some(latency) = Latency::from_samples(&entry.durations) {
+            tool_line.push_str(&format!(
+                " · Σ {} · p50 {} · p95 {}",
+                format_latency(latency.total_ms),
+                format_latency(latency.p50_ms),
+                format_latency(latency.p95_ms)
+            ));
+        }
         push_tool_line(
             lines,
             prefix,
@@ -1466,7 +1635,7 @@ fn render_tool_stats_box<'a>(
             frame_color,
             inner_w,
             &tool_line,
-            tool_text_color,
+            line_color,
         );
 
         if is_expanded {
@@ -1494,12 +1663,23 @@ fn render_tool_stats_box<'a>(
                 for (g_idx, (fp, invs)) in file_groups.iter().enumerate() {
                     let is_last_group = g_idx == group_count - 1;
                     let tree_char = if is_last_group { "└" } else { "├" };
-                    let file_line = format!(
-                        "    {} {} (x{})",
-                        tree_char,
-                        short_file_path(Some(fp)),
-                        invs.len()
-                    );
+                    let group_errors = invs.iter().filter(|inv| inv.failed).count();
+                    let file_line = if group_errors > 0 {
+                        format!(
+                            "    {} {} (x{}, {} failed)",
+                            tree_char,
+                            short_file_path(Some(fp)),
+                            invs.len(),
+                            group_errors
+                        )
+                    } else {
+                        format!(
+                            "    {} {} (x{})",
+                            tree_char,
+                            short_file_path(Some(fp)),
+                            invs.len()
+                        )
+                    };
                     push_tool_line(
                         lines,
                         prefix,
@@ -1507,8 +1687,19 @@ fn render_tool_stats_box<'a>(
                         frame_color,
                         inner_w,
                         &file_line,
-                        tool_text_color,
+                        if group_errors > 0 {
+                            fail_color
+                        } else {
+                            tool_text_color
+                        },
                     );
+                    if !is_read {
+                        for inv in invs.iter().filter(|inv| inv.failed) {
+                            push_tool_failure(
+                                lines, prefix, dim_color, inner_w, "      ", inv, colors,
+                            );
+                        }
+                    }
                     if is_read {
                         // For read, show each invocation as a subtree (dedup'd)
                         let mut seen_details: FxHashSet<String> = FxHashSet::default();
@@ -1557,8 +1748,17 @@ fn render_tool_stats_box<'a>(
                                     frame_color,
                                     inner_w,
                                     &bullet_line,
-                                    tool_text_color,
+                                    if inv.failed {
+                                        fail_color
+                                    } else {
+                                        tool_text_color
+                                    },
                                 );
+                                if inv.failed {
+                                    push_tool_failure(
+                                        lines, prefix, dim_color, inner_w, "        ", inv, colors,
+                                    );
+                                }
                             }
                         }
                     }
@@ -1580,8 +1780,15 @@ fn render_tool_stats_box<'a>(
                         frame_color,
                         inner_w,
                         &line_text,
-                        tool_text_color,
+                        if inv.failed {
+                            fail_color
+                        } else {
+                            tool_text_color
+                        },
                     );
+                    if inv.failed {
+                        push_tool_failure(lines, prefix, dim_color, inner_w, "      ", inv, colors);
+                    }
                 }
             }
         }
@@ -1746,12 +1953,32 @@ fn filter_user_text(text: &str) -> String {
     result.trim().to_string()
 }
 
+/// Text of a part, untruncated when its full text has been loaded.
+#[inline]
+fn part_text<'a>(
+    t: &'a TextPart,
+    part_texts: &'a FxHashMap<(Box<str>, usize), Box<str>>,
+) -> &'a str {
+    t.full
+        .as_ref()
+        .and_then(|key| part_texts.get(key))
+        .unwrap_or(&t.text)
+}
+
+/// Expanded tool boxes and on-demand part texts of the chat view.
+#[derive(Clone, Copy)]
+struct ChatExpansions<'a> {
+    tools: &'a FxHashSet<Box<str>>,
+    part_texts: &'a FxHashMap<(Box<str>, usize), Box<str>>,
+}
+
 fn render_user_box<'a>(
     lines: &mut Vec<Line<'a>>,
     msg: &ChatMessage,
     box_w: usize,
     is_expanded: bool,
     user_num: usize,
+    part_texts: &FxHashMap<(Box<str>, usize), Box<str>>,
     colors: ThemeColors,
 ) {
     let border_color = colors.user;
@@ -1783,7 +2010,11 @@ fn render_user_box<'a>(
         .iter()
         .filter_map(|p| {
             if let MessageContent::Text(t) = p {
-                Some(filter_user_text(t).trim().to_string())
+                Some(
+                    filter_user_text(part_text(t, part_texts))
+                        .trim()
+                        .to_string(),
+                )
             } else {
                 None
             }
@@ -1832,7 +2063,7 @@ fn render_agent_box<'a>(
     agent_num: usize,
     msg_idx: usize,
     click_targets: &mut Vec<(u16, ChatClickTarget)>,
-    expanded_tools: &FxHashSet<Box<str>>,
+    expanded: ChatExpansions,
     colors: ThemeColors,
 ) {
     let border_color = colors.main_agent;
@@ -1864,12 +2095,23 @@ fn render_agent_box<'a>(
         ),
     ]));
     let content_w = box_w.saturating_sub(4);
+    let mut has_thinking = false;
+    for part in &msg.parts {
+        if let MessageContent::Thinking(info) = part {
+            has_thinking = true;
+            let text = expanded
+                .part_texts
+                .get(&(info.message_id.clone(), info.index))
+                .map(|t| &**t);
+            render_thinking_block(lines, info, text, content_w, click_targets, colors);
+        }
+    }
     let all_text: String = msg
         .parts
         .iter()
         .filter_map(|p| {
             if let MessageContent::Text(t) = p {
-                Some(t.trim())
+                Some(part_text(t, expanded.part_texts).trim())
             } else {
                 None
             }
@@ -1902,7 +2144,7 @@ fn render_agent_box<'a>(
     let (total_tools, tool_stats) = aggregate_tools_in_group(std::slice::from_ref(msg), &[0]);
     if total_tools > 0 {
         let target_id = format!("tools:msg:{}", msg_idx).into_boxed_str();
-        let tools_expanded = expanded_tools.contains(&target_id);
+        let tools_expanded = expanded.tools.contains(&target_id);
         render_tool_stats_box(
             lines,
             " ║  ",
@@ -1916,7 +2158,7 @@ fn render_agent_box<'a>(
             colors,
         );
     }
-    if !has_text && total_tools == 0 {
+    if !has_text && total_tools == 0 && !has_thinking {
         lines.push(Line::from(vec![
             Span::styled(" ║", Style::default().fg(border_color)),
             Span::styled("  (empty)", Style::default().fg(colors.text_secondary)),
@@ -1931,6 +2173,75 @@ fn render_agent_box<'a>(
     ]));
 }
 
+/// Collapsible reasoning block inside an agent box; `text` is set while expanded.
+fn render_thinking_block<'a>(
+    lines: &mut Vec<Line<'a>>,
+    info: &ThinkingInfo,
+    text: Option<&str>,
+    content_w: usize,
+    click_targets: &mut Vec<(u16, ChatClickTarget)>,
+    colors: ThemeColors,
+) {
+    let border_color = colors.main_agent;
+    let toggle_label = if text.is_some() {
+        "▾ collapse"
+    } else {
+        "▸ expand"
+    };
+    click_targets.push((
+        lines.len() as u16,
+        ChatClickTarget::Thinking(info.message_id.clone(), info.index),
+    ));
+    let approx = if info.estimated { "~" } else { "" };
+    let header = format!(
+        "✻ thinking · {}{} tokens ",
+        approx,
+        format_number(info.tokens)
+    );
+    let dash_len = content_w.saturating_sub(header.chars().count() + toggle_label.len() + 1);
+    lines.push(Line::from(vec![
+        Span::styled(" ║  ", Style::default().fg(border_color)),
+        Span::styled(
+            header,
+            Style::default()
+                .fg(colors.thinking)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            "╌".repeat(dash_len),
+            Style::default().fg(colors.border_default),
+        ),
+        Span::styled(
+            format!(" {}", toggle_label),
+            Style::default().fg(colors.text_secondary),
+        ),
+    ]));
+    let Some(text) = text else {
+        return;
+    };
+    let cleaned = clean_text_with_breaks(text);
+    if cleaned.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled(" ║  ", Style::default().fg(border_color)),
+            Span::styled("┊ ", Style::default().fg(colors.thinking)),
+            Span::styled("(no text)", Style::default().fg(colors.text_secondary)),
+        ]));
+        return;
+    }
+    for line in wrap_text_plain(&cleaned, content_w.saturating_sub(2)) {
+        lines.push(Line::from(vec![
+            Span::styled(" ║  ", Style::default().fg(border_color)),
+            Span::styled("┊ ", Style::default().fg(colors.thinking)),
+            Span::styled(
+                line,
+                Style::default()
+                    .fg(colors.text_secondary)
+                    .add_modifier(Modifier::ITALIC),
+            ),
+        ]));
+    }
+}
+
 fn wrap_text_plain(s: &str, max_w: usize) -> Vec<String> {
     if max_w == 0 {
         return vec![s.to_string()];
@@ -2040,6 +2351,40 @@ fn push_tool_line<'a>(
     ]));
 }
 
+/// First lines of a failed call's error or output, under its invocation line.
+fn push_tool_failure<'a>(
+    lines: &mut Vec<Line<'a>>,
+    prefix: &'a str,
+    dim_color: Color,
+    inner_w: usize,
+    indent: &str,
+    inv: &ToolInvocation,
+    colors: ThemeColors,
+) {
+    let (frame_color, color) = (colors.border_default, colors.remove_line);
+    const MAX_FAILURE_LINES: usize = 4;
+    let Some(text) = inv.failure.as_deref() else {
+        return;
+    };
+    let body: Vec<&str> = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.trim().is_empty())
+        .collect();
+    for line in body.iter().take(MAX_FAILURE_LINES) {
+        let text = format!("{}✗ {}", indent, line);
+        push_tool_line(lines, prefix, dim_color, frame_color, inner_w, &text, color);
+    }
+    if body.len() > MAX_FAILURE_LINES {
+        let more = format!(
+            "{}  … {} more lines",
+            indent,
+            body.len() - MAX_FAILURE_LINES
+        );
+        push_tool_line(lines, prefix, dim_color, frame_color, inner_w, &more, color);
+    }
+}
+
 fn push_tool_padding<'a>(
     lines: &mut Vec<Line<'a>>,
     prefix: &'a str,
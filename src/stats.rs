@@ -1,35 +1,30 @@
 //! Statistics collection from opencode/kilo storage.
 
+use crate::storage::PartDetail;
 use chrono::Timelike;
 use rayon::prelude::*;
-use rusqlite::{params, Connection, OpenFlags};
 use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
-use std::cell::RefCell;
+use std::borrow::Cow;
 use std::env;
-use std::fs;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, OnceLock};
 
 const MAX_CHARS_PER_TEXT_PART: usize = 2000;
-const DB_MESSAGE_PREFIX: &str = "db://message/";
+/// Prompts at least this large are kept per message for tiered pricing.
+const LONG_PROMPT_TOKENS: u64 = 32_000;
 
 static HOME_DIR: OnceLock<String> = OnceLock::new();
-static STORAGE_ROOTS: OnceLock<Vec<PathBuf>> = OnceLock::new();
-static STORAGE_DB_PATHS: OnceLock<Vec<PathBuf>> = OnceLock::new();
-static DB_MODE: OnceLock<bool> = OnceLock::new();
-
-thread_local! {
-    static DB_CONNS: RefCell<Vec<Option<Connection>>> = const { RefCell::new(Vec::new()) };
-}
+static EXTRA_ROOTS: OnceLock<Vec<crate::config::RootSpec>> = OnceLock::new();
+static STORAGE_ROOTS: OnceLock<Vec<StorageRoot>> = OnceLock::new();
 
 pub type SessionTitlesMap = FxHashMap<Box<str>, String>;
 pub type SessionParentsMap = FxHashMap<Box<str>, Box<str>>;
 pub type MessageBatch = Vec<(Message, Vec<MessageContent>, bool, Option<Box<str>>)>;
 
 // ============================================================================
-// Path & Database Helpers
+// Path Helpers
 // ============================================================================
 
 #[inline]
@@ -59,272 +54,202 @@ fn candidate_roots() -> Vec<PathBuf> {
     roots
 }
 
-#[inline]
-pub(crate) fn get_storage_roots() -> Vec<PathBuf> {
-    STORAGE_ROOTS
-        .get_or_init(|| {
-            let mut roots = Vec::with_capacity(4);
-            for root in candidate_roots() {
-                if root.exists() {
-                    roots.push(root);
-                }
-            }
-            if roots.is_empty() {
-                let fallback = candidate_roots();
-                roots.push(fallback.into_iter().next().unwrap_or_else(|| {
-                    PathBuf::from(format!("{}/.local/share/opencode", get_home()))
-                }));
-            }
-            roots
-        })
-        .clone()
+/// A storage root and the label shown for sessions loaded from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageRoot {
+    pub path: PathBuf,
+    pub label: Box<str>,
 }
 
-#[inline]
-pub(crate) fn get_storage_paths(subdir: &str) -> Vec<PathBuf> {
-    get_storage_dirs()
-        .into_iter()
-        .map(|storage| storage.join(subdir))
-        .collect()
+/// Register extra roots before any storage access. Returns roots that do not exist.
+pub(crate) fn set_extra_roots(roots: Vec<crate::config::RootSpec>) -> Vec<PathBuf> {
+    let missing = roots
+        .iter()
+        .filter(|r| !r.path.exists())
+        .map(|r| r.path.clone())
+        .collect();
+    let _ = EXTRA_ROOTS.set(roots);
+    missing
 }
 
+/// Accept either a root or its `storage/` subdirectory.
 #[inline]
-pub(crate) fn get_storage_dirs() -> Vec<PathBuf> {
-    get_storage_roots()
-        .into_iter()
-        .map(|root| root.join("storage"))
-        .collect()
+fn normalize_root(path: &Path) -> PathBuf {
+    let is_storage_dir = path.file_name().is_some_and(|n| n == "storage")
+        && !path.join("opencode.db").exists()
+        && !path.join("kilo.db").exists();
+    match path.parent() {
+        Some(parent) if is_storage_dir => parent.to_path_buf(),
+        _ => path.to_path_buf(),
+    }
 }
 
 #[inline]
-pub(crate) fn get_watch_paths() -> Vec<PathBuf> {
-    if is_db_mode() {
-        let mut paths: Vec<PathBuf> = Vec::new();
-        for db in get_storage_db_paths() {
-            if let Some(parent) = db.parent() {
-                let parent = parent.to_path_buf();
-                if !paths.contains(&parent) {
-                    paths.push(parent);
-                }
+fn default_root_label(path: &Path) -> Box<str> {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into())
+        .unwrap_or_else(|| path.to_string_lossy().into())
+}
+
+/// Existing roots: XDG/opencode/kilo candidates first, then configured extras.
+pub(crate) fn get_labeled_roots() -> &'static [StorageRoot] {
+    STORAGE_ROOTS.get_or_init(|| {
+        let mut roots: Vec<StorageRoot> = Vec::with_capacity(4);
+        for path in candidate_roots() {
+            if path.exists() {
+                let label = default_root_label(&path);
+                roots.push(StorageRoot { path, label });
             }
         }
-        if paths.is_empty() {
-            get_storage_roots()
-        } else {
-            paths
+        for spec in EXTRA_ROOTS.get().into_iter().flatten() {
+            let path = normalize_root(&spec.path);
+            if !path.exists() || roots.iter().any(|r| r.path == path) {
+                continue;
+            }
+            let label = match &spec.label {
+                Some(label) => label.as_str().into(),
+                None => {
+                    let label = default_root_label(&path);
+                    if roots.iter().any(|r| r.label == label) {
+                        path.to_string_lossy().into()
+                    } else {
+                        label
+                    }
+                }
+            };
+            roots.push(StorageRoot { path, label });
         }
-    } else {
-        get_storage_dirs()
-    }
+        if roots.is_empty() {
+            let path = candidate_roots()
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| PathBuf::from(format!("{}/.local/share/opencode", get_home())));
+            let label = default_root_label(&path);
+            roots.push(StorageRoot { path, label });
+        }
+        roots
+    })
 }
 
 #[inline]
-pub fn get_storage_path(subdir: &str) -> String {
-    get_storage_paths(subdir)
-        .into_iter()
-        .next()
-        .unwrap_or_else(|| {
-            PathBuf::from(format!("{}/.local/share/opencode/storage", get_home())).join(subdir)
-        })
-        .to_string_lossy()
-        .to_string()
+pub(crate) fn get_storage_roots() -> Vec<PathBuf> {
+    get_labeled_roots().iter().map(|r| r.path.clone()).collect()
 }
 
+/// True when the storage set differs from the built-in candidates.
 #[inline]
-pub(crate) fn get_storage_db_paths() -> Vec<PathBuf> {
-    STORAGE_DB_PATHS
-        .get_or_init(|| {
-            let mut dbs = Vec::with_capacity(2);
-            for root in get_storage_roots() {
-                let opencode_db = root.join("opencode.db");
-                if opencode_db.exists() {
-                    dbs.push(opencode_db);
-                }
-                let kilo_db = root.join("kilo.db");
-                if kilo_db.exists() {
-                    dbs.push(kilo_db);
-                }
-            }
-            dbs
-        })
-        .clone()
+pub(crate) fn has_extra_roots() -> bool {
+    let candidates = candidate_roots();
+    get_labeled_roots()
+        .iter()
+        .any(|r| !candidates.contains(&r.path))
 }
 
-#[inline]
-pub(crate) fn is_db_mode() -> bool {
-    *DB_MODE.get_or_init(|| !get_storage_db_paths().is_empty())
+/// Label of the root a message path (JSON file or DB key) belongs to.
+pub(crate) fn root_label_for_path(path: &Path) -> Option<&'static str> {
+    let root_path = crate::storage::message_root(path)?;
+    get_labeled_roots()
+        .iter()
+        .filter(|r| root_path.starts_with(&r.path))
+        .max_by_key(|r| r.path.as_os_str().len())
+        .map(|r| r.label.as_ref())
 }
 
-fn open_db(db_path: &Path) -> Option<Connection> {
-    if !db_path.exists() {
-        return None;
-    }
-    let conn = Connection::open_with_flags(
-        db_path,
-        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI,
-    )
-    .ok()?;
-    let _ = conn.busy_timeout(std::time::Duration::from_millis(300));
-    Some(conn)
+/// Root label for a session, taken from any of its message files.
+pub(crate) fn session_root_label(
+    session_message_files: &FxHashMap<String, FxHashSet<PathBuf>>,
+    session_id: &str,
+) -> Option<&'static str> {
+    session_message_files
+        .get(session_id)?
+        .iter()
+        .find_map(|p| root_label_for_path(p))
 }
 
-/// Thread-local cached DB connection by index.
 #[inline]
-fn with_cached_db<T>(db_idx: usize, f: impl FnOnce(&Connection) -> Option<T>) -> Option<T> {
-    let db_paths = get_storage_db_paths();
-    if db_idx >= db_paths.len() {
-        return None;
-    }
-    DB_CONNS.with(|slot| {
-        let mut conns = slot.borrow_mut();
-        if conns.len() < db_paths.len() {
-            conns.resize_with(db_paths.len(), || None);
-        }
-        if conns[db_idx].is_none() {
-            conns[db_idx] = open_db(&db_paths[db_idx]);
-        }
-        conns[db_idx].as_ref().and_then(f)
-    })
+fn get_storage_dirs() -> Vec<PathBuf> {
+    get_storage_roots()
+        .into_iter()
+        .map(|root| root.join("storage"))
+        .collect()
 }
 
+/// Primary storage directory used as the cache anchor.
 #[inline]
-fn db_message_key_from_path(path: &Path) -> Option<(usize, String)> {
-    let p = path.to_str()?;
-    let raw = p.strip_prefix(DB_MESSAGE_PREFIX)?;
-    let mut parts = raw.splitn(3, '/');
-    let db_idx = parts.next()?.parse::<usize>().ok()?;
-    let kind = parts.next()?;
-    if kind != "message" {
-        return None;
-    }
-    let msg_id = parts.next()?.to_string();
-    Some((db_idx, msg_id))
-}
-
-pub(crate) fn load_message_from_path(path: &Path) -> Option<Message> {
-    if let Some((db_idx, message_id)) = db_message_key_from_path(path) {
-        let (row_id, row_session_id, row_time_created, data): (String, String, i64, String) =
-            with_cached_db(db_idx, |conn| {
-                let Ok(mut stmt) = conn.prepare_cached(
-                    "SELECT id, session_id, time_created, data FROM message WHERE id = ?1",
-                ) else {
-                    return None;
-                };
-                stmt.query_row(params![message_id], |r| {
-                    Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?))
-                })
-                .ok()
-            })?;
-
-        let mut msg: Message = serde_json::from_str(&data).ok()?;
-
-        // Populate missing fields from DB row
-        if msg.id.is_none() || msg.id.as_ref().is_some_and(|id| id.0.is_empty()) {
-            msg.id = Some(LenientString(row_id));
-        }
-        if msg.session_id.is_none() || msg.session_id.as_ref().is_some_and(|s| s.0.is_empty()) {
-            msg.session_id = Some(LenientString(row_session_id));
-        }
-        if msg.time.is_none() {
-            msg.time = Some(TimeData {
-                created: Some(LenientI64(row_time_created)),
-                completed: None,
-            });
-        } else if msg.time.as_ref().is_some_and(|t| t.created.is_none()) {
-            if let Some(ref mut time) = msg.time {
-                time.created = Some(LenientI64(row_time_created));
-            }
-        }
-
-        return Some(msg);
-    }
-
-    let bytes = fs::read(path).ok()?;
-    serde_json::from_slice(&bytes).ok()
+pub(crate) fn default_storage_dir() -> PathBuf {
+    get_storage_dirs().into_iter().next().unwrap_or_else(|| {
+        PathBuf::from(
+            env::var("XDG_DATA_HOME").unwrap_or_else(|_| format!("{}/.local/share", get_home())),
+        )
+        .join("opencode")
+        .join("storage")
+    })
 }
 
 // ============================================================================
-// Batch Part Loading
+// Part Conversion
 // ============================================================================
 
-/// Batch-load parts for multiple messages
-fn batch_load_parts_db(message_ids: &[&str]) -> FxHashMap<Box<str>, Vec<PartData>> {
-    if message_ids.is_empty() {
-        return FxHashMap::default();
-    }
-    let mut result: FxHashMap<Box<str>, Vec<PartData>> =
-        FxHashMap::with_capacity_and_hasher(message_ids.len(), Default::default());
-
-    for db_path in get_storage_db_paths() {
-        let Some(conn) = open_db(&db_path) else {
-            continue;
-        };
-        for chunk in message_ids.chunks(500) {
-            let placeholders: String = (0..chunk.len())
-                .map(|i| format!("?{}", i + 1))
-                .collect::<Vec<_>>()
-                .join(",");
-            let sql = format!(
-                "SELECT message_id, data FROM part WHERE message_id IN ({}) \
-                 ORDER BY message_id, time_created ASC, id ASC",
-                placeholders
-            );
-            let Ok(mut stmt) = conn.prepare(&sql) else {
-                continue;
+/// Reasoning text of a part: opencode `reasoning` parts keep it in `text`,
+/// imported `thinking` parts in `thought`.
+#[inline]
+fn reasoning_text(part: &PartData) -> Option<&str> {
+    if part.part_type.as_deref() == Some("reasoning") {
+        Some(part.text.as_deref().unwrap_or(""))
+    } else {
+        part.thought.as_deref()
+    }
+}
+
+/// Convert raw parts to MessageContent. Text longer than
+/// `MAX_CHARS_PER_TEXT_PART` is cut and keeps its part position so the rest
+/// can be loaded with `load_part_text`. Reasoning text is not kept; each block
+/// records where to load it from and its share of the message's
+/// `reasoning_tokens` by text length, an even share when the text is empty,
+/// or an estimate from its length when none were reported.
+fn parts_to_content(
+    parts: Vec<PartData>,
+    message_id: &str,
+    reasoning_tokens: u64,
+) -> Vec<MessageContent> {
+    let reasoning_total: usize = parts.iter().filter_map(reasoning_text).map(str::len).sum();
+    let reasoning_blocks = parts.iter().filter_map(reasoning_text).count() as u64;
+    let mut block = 0;
+    let mut result = Vec::with_capacity(parts.len());
+    for (part_idx, part) in parts.into_iter().enumerate() {
+        if let Some(text) = reasoning_text(&part) {
+            let len = text.len();
+            let tokens = if reasoning_tokens == 0 {
+                (len / 4) as u64
+            } else if reasoning_total == 0 {
+                // No text to weight by (e.g. encrypted reasoning): split evenly,
+                // earlier blocks taking the remainder
+                let share = reasoning_tokens / reasoning_blocks
+                    + u64::from(block < reasoning_tokens % reasoning_blocks);
+                block += 1;
+                share
+            } else {
+                (reasoning_tokens as f64 * len as f64 / reasoning_total as f64).round() as u64
             };
-            let params: Vec<&dyn rusqlite::types::ToSql> = chunk
-                .iter()
-                .map(|s| s as &dyn rusqlite::types::ToSql)
-                .collect();
-            let Ok(rows) = stmt.query_map(params.as_slice(), |r| {
-                Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))
-            }) else {
+            result.push(MessageContent::Thinking(ThinkingInfo {
+                message_id: message_id.into(),
+                index: part_idx,
+                tokens,
+                estimated: reasoning_tokens == 0,
+            }));
+            if part.part_type.as_deref() == Some("reasoning") {
                 continue;
-            };
-
-            let mut cur_id: Box<str> = "".into();
-            let mut should_insert_for_cur = false;
-            for row in rows.flatten() {
-                if *cur_id != row.0 {
-                    cur_id = row.0.into_boxed_str();
-                    should_insert_for_cur = !result.contains_key(cur_id.as_ref());
-                    if should_insert_for_cur {
-                        result.entry(cur_id.clone()).or_default();
-                    }
-                }
-                if !should_insert_for_cur {
-                    continue;
-                }
-
-                let part = match serde_json::from_str::<PartData>(&row.1) {
-                    Ok(p) => p,
-                    Err(_) => continue,
-                };
-                if let Some(vec) = result.get_mut(&cur_id) {
-                    vec.push(part);
-                }
             }
         }
-    }
-
-    result
-}
-
-/// Convert raw parts to MessageContent, skipping reasoning parts
-fn parts_to_content(parts: Vec<PartData>) -> Vec<MessageContent> {
-    let mut result = Vec::with_capacity(parts.len());
-    for part in parts {
-        if part.part_type.as_deref() == Some("reasoning") {
-            continue;
-        }
-        if part.thought.is_some() {
-            result.push(MessageContent::Thinking(()));
-        }
         let mut current_text: Option<Box<str>> = None;
         if let Some(t) = part.text {
             let truncated = truncate_string(&t, MAX_CHARS_PER_TEXT_PART);
             current_text = Some(truncated.clone());
-            result.push(MessageContent::Text(truncated));
+            let full = (truncated.len() != t.len()).then(|| (message_id.into(), part_idx));
+            result.push(MessageContent::Text(TextPart {
+                text: truncated,
+                full,
+            }));
         }
         if let Some(tool) = part.tool {
             let state_input = part.state.as_ref().and_then(|s| s.input.as_ref());
@@ -333,73 +258,38 @@ fn parts_to_content(parts: Vec<PartData>) -> Vec<MessageContent> {
             let tool_detail = state_input
                 .map(|i| build_tool_detail(&tool, i).into_boxed_str())
                 .or(current_text);
+            let state = part.state.as_ref();
             result.push(MessageContent::ToolCall(ToolCallInfo {
                 name: tool.into(),
                 file_path: fp,
                 input: tool_detail,
                 additions: None,
                 deletions: None,
+                status: state
+                    .and_then(|s| s.status.as_ref())
+                    .map(|s| s.0.as_str().into()),
+                output: state.and_then(|s| s.output.as_ref()).and_then(tool_text),
+                error: state.and_then(|s| s.error.as_ref()).and_then(tool_text),
+                duration_ms: state.and_then(ToolState::duration_ms),
             }));
         }
     }
     result
 }
 
-/// Batch-load parts from multiple filesystem roots
-fn batch_load_parts_fs_multi(
-    message_ids: &[&str],
-    part_roots: &[PathBuf],
-) -> FxHashMap<Box<str>, Vec<PartData>> {
-    if message_ids.is_empty() {
-        return FxHashMap::default();
-    }
-
-    message_ids
-        .par_iter()
-        .filter_map(|msg_id| {
-            for root in part_roots {
-                let p = root.join(msg_id);
-                if !p.exists() {
-                    continue;
-                }
-                let mut parts = Vec::with_capacity(8);
-                if let Ok(entries) = fs::read_dir(&p) {
-                    let mut p_files: Vec<_> = entries.flatten().collect();
-                    p_files.sort_by_key(|e| e.path());
-                    for e in p_files {
-                        if let Ok(bytes) = fs::read(e.path()) {
-                            if let Ok(part) = serde_json::from_slice::<PartData>(&bytes) {
-                                parts.push(part);
-                            }
-                        }
-                    }
-                }
-                if !parts.is_empty() {
-                    return Some((msg_id.to_string().into_boxed_str(), parts));
-                }
-            }
-            None
-        })
-        .collect()
-}
-
-/// Load parts from all available sources.
-fn batch_load_parts_all(
-    message_ids: &[&str],
-    part_roots: &[PathBuf],
-) -> FxHashMap<Box<str>, Vec<PartData>> {
-    if message_ids.is_empty() {
-        return FxHashMap::default();
+/// Untruncated text of the part at `index` in a message: the reasoning of a
+/// thinking block, otherwise its text.
+pub fn load_part_text(message_id: &str, index: usize) -> Option<String> {
+    let mut parts =
+        crate::storage::load_parts(&[message_id], PartDetail::Summary).remove(message_id)?;
+    if index >= parts.len() {
+        return None;
     }
-
-    let (mut db_map, fs_map) = rayon::join(
-        || batch_load_parts_db(message_ids),
-        || batch_load_parts_fs_multi(message_ids, part_roots),
-    );
-    for (k, v) in fs_map {
-        db_map.entry(k).or_insert(v);
+    let part = parts.swap_remove(index);
+    match part.part_type.as_deref() {
+        Some("reasoning") => part.text,
+        _ => part.thought.or(part.text),
     }
-    db_map
 }
 
 // ============================================================================
@@ -420,7 +310,7 @@ impl DayStat {
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Tokens {
     pub input: u64,
     pub output: u64,
@@ -436,6 +326,17 @@ impl Tokens {
     }
 }
 
+impl std::ops::AddAssign for Tokens {
+    #[inline]
+    fn add_assign(&mut self, other: Self) {
+        self.input += other.input;
+        self.output += other.output;
+        self.reasoning += other.reasoning;
+        self.cache_read += other.cache_read;
+        self.cache_write += other.cache_write;
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
 pub struct Diffs {
     pub additions: u64,
@@ -452,6 +353,12 @@ pub struct SessionStat {
     pub diffs: Diffs,
     pub models: FxHashSet<Box<str>>,
     pub tools: FxHashMap<Box<str>, u64>,
+    /// Calls per tool that ended with an error.
+    #[serde(default)]
+    pub tool_errors: FxHashMap<Box<str>, u64>,
+    /// Durations in ms of the timed calls per tool.
+    #[serde(default)]
+    pub tool_durations: FxHashMap<Box<str>, Vec<u32>>,
     pub first_activity: i64,
     pub last_activity: i64,
     pub path_cwd: Box<str>,
@@ -487,6 +394,8 @@ impl SessionStat {
             diffs: Diffs::default(),
             models: FxHashSet::default(),
             tools: FxHashMap::default(),
+            tool_errors: FxHashMap::default(),
+            tool_durations: FxHashMap::default(),
             first_activity: i64::MAX,
             last_activity: 0,
             path_cwd: String::new().into_boxed_str(),
@@ -537,6 +446,10 @@ pub struct Totals {
     pub tokens: Tokens,
     pub diffs: Diffs,
     pub tools: FxHashMap<Box<str>, u64>,
+    #[serde(default)]
+    pub tool_errors: FxHashMap<Box<str>, u64>,
+    #[serde(default)]
+    pub tool_durations: FxHashMap<Box<str>, Vec<u32>>,
     pub cost: f64,
 }
 
@@ -549,6 +462,8 @@ impl Default for Totals {
             tokens: Tokens::default(),
             diffs: Diffs::default(),
             tools: FxHashMap::default(),
+            tool_errors: FxHashMap::default(),
+            tool_durations: FxHashMap::default(),
             cost: 0.0,
         }
     }
@@ -583,18 +498,287 @@ pub struct ModelUsage {
     pub sessions: FxHashSet<Box<str>>,
     pub tokens: Tokens,
     pub tools: FxHashMap<Box<str>, u64>,
+    #[serde(default)]
+    pub tool_errors: FxHashMap<Box<str>, u64>,
     pub agents: FxHashMap<Box<str>, u64>,
     #[serde(default)]
     pub daily_tokens: FxHashMap<String, u64>,
+    /// Token split by local date, so each day can be priced at its own rates.
+    #[serde(default)]
+    pub daily_split: FxHashMap<String, Tokens>,
     #[serde(default)]
     pub daily_last_hour: FxHashMap<String, u8>,
+    /// Timing of completed responses by local date.
+    #[serde(default)]
+    pub daily_speed: FxHashMap<String, Vec<SpeedSample>>,
+    /// Messages priced individually by local date: long prompts and one-hour
+    /// cache writes. The rest of `daily_split` is priced at base rates.
+    #[serde(default)]
+    pub daily_messages: FxHashMap<String, Vec<MessageUsage>>,
+    /// Message, cost, tool and agent counts by local date.
+    #[serde(default)]
+    pub daily: FxHashMap<String, ModelDay>,
+    /// The same usage split by the tool that recorded it.
+    #[serde(default)]
+    pub by_source: FxHashMap<crate::import::Source, ModelUsage>,
     pub cost: f64,
 }
 
+/// What one model recorded on one local date.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelDay {
+    pub messages: u64,
+    pub cost: f64,
+    pub tools: FxHashMap<Box<str>, u64>,
+    pub tool_errors: FxHashMap<Box<str>, u64>,
+    pub agents: FxHashMap<Box<str>, u64>,
+}
+
+impl ModelUsage {
+    /// Empty usage for a `provider/model` id.
+    pub fn new(name: &str) -> Self {
+        let short = name.rsplit('/').next().unwrap_or(name);
+        let provider = name.split('/').next().unwrap_or(name);
+        Self {
+            name: name.into(),
+            short_name: short.into(),
+            provider: provider.into(),
+            display_name: format!("{}/{}", provider, short).into_boxed_str(),
+            messages: 0,
+            sessions: FxHashSet::default(),
+            tokens: Tokens::default(),
+            tools: FxHashMap::default(),
+            tool_errors: FxHashMap::default(),
+            agents: FxHashMap::default(),
+            daily_tokens: FxHashMap::default(),
+            daily_split: FxHashMap::default(),
+            daily_last_hour: FxHashMap::default(),
+            daily_speed: FxHashMap::default(),
+            daily_messages: FxHashMap::default(),
+            daily: FxHashMap::default(),
+            by_source: FxHashMap::default(),
+            cost: 0.0,
+        }
+    }
+
+    /// Add another record of the same model, e.g. from a different source.
+    pub fn merge(&mut self, other: &ModelUsage) {
+        self.messages += other.messages;
+        self.sessions.extend(other.sessions.iter().cloned());
+        self.tokens += other.tokens;
+        for (dst, src) in [
+            (&mut self.tools, &other.tools),
+            (&mut self.tool_errors, &other.tool_errors),
+            (&mut self.agents, &other.agents),
+        ] {
+            for (k, v) in src {
+                *dst.entry(k.clone()).or_insert(0) += v;
+            }
+        }
+        for (day, v) in &other.daily_tokens {
+            *self.daily_tokens.entry(day.clone()).or_insert(0) += v;
+        }
+        for (day, t) in &other.daily_split {
+            *self.daily_split.entry(day.clone()).or_default() += *t;
+        }
+        for (day, &h) in &other.daily_last_hour {
+            let hour = self.daily_last_hour.entry(day.clone()).or_insert(h);
+            *hour = (*hour).max(h);
+        }
+        for (day, samples) in &other.daily_speed {
+            self.daily_speed
+                .entry(day.clone())
+                .or_default()
+                .extend_from_slice(samples);
+        }
+        for (day, usage) in &other.daily_messages {
+            self.daily_messages
+                .entry(day.clone())
+                .or_default()
+                .extend_from_slice(usage);
+        }
+        for (day, d) in &other.daily {
+            crate::date_range::add_model_day(self.daily.entry(day.clone()).or_default(), d);
+        }
+        self.cost += other.cost;
+    }
+
+    /// Latency and throughput over every recorded response.
+    pub fn speed(&self) -> Option<ModelSpeed> {
+        ModelSpeed::from_samples(self.daily_speed.values().flatten())
+    }
+}
+
+/// Total and percentile durations of a set of timed calls.
+#[derive(Clone, Copy, Default)]
+pub struct Latency {
+    pub total_ms: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+}
+
+impl Latency {
+    pub fn from_samples(samples: &[u32]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        Some(Self {
+            total_ms: sorted.iter().map(|&ms| ms as u64).sum(),
+            p50_ms: percentile(&sorted, 50.0).map_or(0, u64::from),
+            p95_ms: percentile(&sorted, 95.0).map_or(0, u64::from),
+        })
+    }
+}
+
+/// Nearest-rank percentile of ascending `sorted` samples.
+#[inline]
+pub(crate) fn percentile<T: Copy>(sorted: &[T], p: f64) -> Option<T> {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.clamp(1, sorted.len().max(1)) - 1).copied()
+}
+
+/// Token usage of one assistant message whose price depends on more than
+/// the day's totals.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct MessageUsage {
+    pub tokens: Tokens,
+    /// Part of `tokens.cache_write` cached for an hour instead of five minutes.
+    pub cache_write_1h: u64,
+}
+
+impl MessageUsage {
+    /// Prompt size that selects a pricing tier: input plus cached tokens.
+    #[inline]
+    pub fn prompt_tokens(&self) -> u64 {
+        self.tokens.input + self.tokens.cache_read + self.tokens.cache_write
+    }
+
+    /// Usage of `msg`, if it needs per-message pricing.
+    pub(crate) fn from_message(msg: &Message, tokens: Tokens) -> Option<Self> {
+        let cache_write_1h = msg
+            .tokens
+            .as_ref()
+            .and_then(|t| t.cache.as_ref()?.write_1h)
+            .map_or(0, |v| (*v).min(tokens.cache_write));
+        let usage = Self {
+            tokens,
+            cache_write_1h,
+        };
+        (usage.prompt_tokens() >= LONG_PROMPT_TOKENS || cache_write_1h > 0).then_some(usage)
+    }
+}
+
+/// Timing of one completed assistant response.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SpeedSample {
+    /// Request to completion.
+    pub latency_ms: u32,
+    /// Request to the first streamed part, when parts recorded start times.
+    pub ttft_ms: Option<u32>,
+    /// Output plus reasoning tokens.
+    pub output_tokens: u32,
+}
+
+impl SpeedSample {
+    /// Sample for a completed message; the first part start gives time to first token.
+    pub(crate) fn from_message(msg: &Message, parts: &[PartData]) -> Option<Self> {
+        let time = msg.time.as_ref()?;
+        let (created, completed) = (*time.created?, *time.completed?);
+        if completed <= created {
+            return None;
+        }
+        let first = parts
+            .iter()
+            .filter_map(PartData::start_ms)
+            .filter(|&start| start >= created && start <= completed)
+            .min();
+        let tokens = msg.tokens.as_ref().map(Tokens::from).unwrap_or_default();
+        Some(Self {
+            latency_ms: clamp_ms(completed - created),
+            ttft_ms: first.map(|start| clamp_ms(start - created)),
+            output_tokens: (tokens.output + tokens.reasoning).min(u32::MAX as u64) as u32,
+        })
+    }
+
+    /// Output tokens per second, counted from the first token when known.
+    #[inline]
+    pub fn tokens_per_sec(&self) -> Option<f64> {
+        let generation_ms = self.latency_ms.saturating_sub(self.ttft_ms.unwrap_or(0));
+        (self.output_tokens > 0 && generation_ms > 0)
+            .then(|| self.output_tokens as f64 * 1000.0 / generation_ms as f64)
+    }
+}
+
+/// Latency, time to first token and throughput over a set of responses.
+#[derive(Clone, Copy, Default)]
+pub struct ModelSpeed {
+    pub latency: Latency,
+    /// Only responses whose parts recorded start times.
+    pub ttft: Option<Latency>,
+    /// Median output tokens per second.
+    pub tps_p50: f64,
+    /// Slowest 5% of responses, in output tokens per second.
+    pub tps_p5: f64,
+}
+
+impl ModelSpeed {
+    pub fn from_samples<'a>(samples: impl IntoIterator<Item = &'a SpeedSample>) -> Option<Self> {
+        let samples: Vec<&SpeedSample> = samples.into_iter().collect();
+        let latencies: Vec<u32> = samples.iter().map(|s| s.latency_ms).collect();
+        let ttfts: Vec<u32> = samples.iter().filter_map(|s| s.ttft_ms).collect();
+        let mut tps: Vec<f64> = samples.iter().filter_map(|s| s.tokens_per_sec()).collect();
+        tps.sort_unstable_by(f64::total_cmp);
+        Some(Self {
+            latency: Latency::from_samples(&latencies)?,
+            ttft: Latency::from_samples(&ttfts),
+            tps_p50: percentile(&tps, 50.0).unwrap_or(0.0),
+            tps_p5: percentile(&tps, 5.0).unwrap_or(0.0),
+        })
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct ToolUsage {
     pub name: Box<str>,
     pub count: u64,
+    pub errors: u64,
+    /// Only calls that recorded start and end times.
+    pub latency: Option<Latency>,
+}
+
+impl ToolUsage {
+    /// Usage per tool from call and error counts and call durations, most
+    /// used first.
+    pub fn from_counts(
+        tools: &FxHashMap<Box<str>, u64>,
+        errors: &FxHashMap<Box<str>, u64>,
+        durations: &FxHashMap<Box<str>, Vec<u32>>,
+    ) -> Vec<Self> {
+        let mut usage: Vec<Self> = tools
+            .iter()
+            .map(|(name, count)| Self {
+                name: name.clone(),
+                count: *count,
+                errors: errors.get(name).copied().unwrap_or(0),
+                latency: durations
+                    .get(name)
+                    .and_then(|samples| Latency::from_samples(samples)),
+            })
+            .collect();
+        usage.sort_unstable_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        usage
+    }
+
+    /// Share of calls that did not fail, in percent.
+    #[inline]
+    pub fn success_rate(&self) -> f64 {
+        if self.count == 0 {
+            return 100.0;
+        }
+        100.0 * self.count.saturating_sub(self.errors) as f64 / self.count as f64
+    }
 }
 
 #[derive(Clone)]
@@ -604,13 +788,44 @@ pub struct ToolCallInfo {
     pub input: Option<Box<str>>,
     pub additions: Option<u64>,
     pub deletions: Option<u64>,
+    /// `pending`, `running`, `completed` or `error` as recorded by the tool part.
+    pub status: Option<Box<str>>,
+    pub output: Option<Box<str>>,
+    pub error: Option<Box<str>>,
+    pub duration_ms: Option<i64>,
+}
+
+impl ToolCallInfo {
+    #[inline]
+    pub fn failed(&self) -> bool {
+        self.status.as_deref() == Some("error")
+    }
 }
 
 #[derive(Clone)]
 pub enum MessageContent {
-    Text(Box<str>),
+    Text(TextPart),
     ToolCall(ToolCallInfo),
-    Thinking(()),
+    Thinking(ThinkingInfo),
+}
+
+#[derive(Clone)]
+pub struct TextPart {
+    pub text: Box<str>,
+    /// Message id and part position to load the full text from, set when
+    /// `text` was cut short.
+    pub full: Option<(Box<str>, usize)>,
+}
+
+/// A reasoning block; its text is loaded on demand with `load_part_text`.
+#[derive(Clone)]
+pub struct ThinkingInfo {
+    pub message_id: Box<str>,
+    /// Position of the part in its message.
+    pub index: usize,
+    pub tokens: u64,
+    /// `tokens` was estimated from the text length; none were reported.
+    pub estimated: bool,
 }
 
 #[derive(Clone)]
@@ -698,13 +913,16 @@ impl Deref for LenientI64 {
     }
 }
 
-#[derive(Deserialize, Default)]
+#[derive(Deserialize, Default, Clone)]
 pub(crate) struct CacheData {
     pub(crate) read: Option<LenientU64>,
     pub(crate) write: Option<LenientU64>,
+    /// Part of `write` cached for an hour; only some importers know it.
+    #[serde(default)]
+    pub(crate) write_1h: Option<LenientU64>,
 }
 
-#[derive(Deserialize, Default)]
+#[derive(Deserialize, Default, Clone)]
 pub(crate) struct TokensData {
     pub(crate) input: Option<LenientU64>,
     pub(crate) output: Option<LenientU64>,
@@ -712,6 +930,19 @@ pub(crate) struct TokensData {
     pub(crate) cache: Option<CacheData>,
 }
 
+impl From<&TokensData> for Tokens {
+    fn from(t: &TokensData) -> Self {
+        let cache = t.cache.as_ref();
+        Self {
+            input: t.input.map(|v| *v).unwrap_or(0),
+            output: t.output.map(|v| *v).unwrap_or(0),
+            reasoning: t.reasoning.map(|v| *v).unwrap_or(0),
+            cache_read: cache.and_then(|c| c.read.map(|v| *v)).unwrap_or(0),
+            cache_write: cache.and_then(|c| c.write.map(|v| *v)).unwrap_or(0),
+        }
+    }
+}
+
 // DiffItem and Summary are used to extract cumulative diff state from messages
 #[derive(Deserialize, Default, Clone)]
 pub(crate) struct DiffItem {
@@ -721,24 +952,24 @@ pub(crate) struct DiffItem {
     pub(crate) status: Option<LenientString>,
 }
 
-#[derive(Deserialize, Default)]
+#[derive(Deserialize, Default, Clone)]
 pub(crate) struct Summary {
     pub(crate) diffs: Option<Vec<DiffItem>>,
 }
 
-#[derive(Deserialize, Default)]
+#[derive(Deserialize, Default, Clone)]
 pub(crate) struct TimeData {
     pub(crate) created: Option<LenientI64>,
     pub(crate) completed: Option<LenientI64>,
 }
 
-#[derive(Deserialize, Default)]
+#[derive(Deserialize, Default, Clone)]
 pub(crate) struct PathData {
     pub(crate) cwd: Option<String>,
     pub(crate) root: Option<String>,
 }
 
-#[derive(Deserialize, Default)]
+#[derive(Deserialize, Default, Clone)]
 pub(crate) struct ModelData {
     #[serde(rename = "providerID")]
     pub(crate) provider_id: Option<LenientString>,
@@ -838,7 +1069,7 @@ impl Deref for LenientF64 {
     }
 }
 
-#[derive(Deserialize, Default)]
+#[derive(Deserialize, Default, Clone)]
 pub(crate) struct Message {
     pub(crate) id: Option<LenientString>,
     #[serde(rename = "sessionID")]
@@ -867,22 +1098,6 @@ pub struct FileDiff {
     pub status: Box<str>,
 }
 
-#[derive(Deserialize, Default, Clone)]
-struct SessionDiffEntry {
-    file: Option<LenientString>,
-    additions: Option<LenientU64>,
-    deletions: Option<LenientU64>,
-    status: Option<LenientString>,
-}
-
-#[derive(Deserialize)]
-struct SessionData {
-    id: Option<LenientString>,
-    title: Option<LenientString>,
-    #[serde(rename = "parentID")]
-    parent_id: Option<LenientString>,
-}
-
 #[derive(Deserialize, Default, Clone)]
 pub(crate) struct ToolStateInput {
     #[serde(rename = "filePath")]
@@ -907,9 +1122,50 @@ pub(crate) struct ToolStateInput {
     pub(crate) ids: Option<Vec<String>>,
 }
 
+/// Start and end of a part or tool call.
+#[derive(Deserialize, Default, Clone)]
+pub(crate) struct PartTime {
+    pub(crate) start: Option<LenientI64>,
+    pub(crate) end: Option<LenientI64>,
+}
+
 #[derive(Deserialize, Default, Clone)]
 pub(crate) struct ToolState {
     pub(crate) input: Option<ToolStateInput>,
+    pub(crate) status: Option<LenientString>,
+    /// Usually a string; kept as a value so odd shapes do not drop the part.
+    pub(crate) output: Option<serde_json::Value>,
+    pub(crate) error: Option<serde_json::Value>,
+    pub(crate) time: Option<PartTime>,
+}
+
+impl ToolState {
+    #[inline]
+    pub(crate) fn failed(&self) -> bool {
+        self.status.as_ref().is_some_and(|s| s.0 == "error")
+    }
+
+    pub(crate) fn duration_ms(&self) -> Option<i64> {
+        let time = self.time.as_ref()?;
+        let (start, end) = (*time.start?, *time.end?);
+        (end >= start).then_some(end - start)
+    }
+}
+
+/// Duration sample in ms, saturating at `u32::MAX` (about 49 days).
+#[inline]
+pub(crate) fn clamp_ms(ms: i64) -> u32 {
+    ms.clamp(0, u32::MAX as i64) as u32
+}
+
+/// Text of a tool output or error value, cut to `MAX_CHARS_PER_TEXT_PART`.
+fn tool_text(value: &serde_json::Value) -> Option<Box<str>> {
+    let text = match value {
+        serde_json::Value::Null => return None,
+        serde_json::Value::String(s) => Cow::Borrowed(s.as_str()),
+        other => Cow::Owned(other.to_string()),
+    };
+    (!text.trim().is_empty()).then(|| truncate_string(&text, MAX_CHARS_PER_TEXT_PART))
 }
 
 #[derive(Deserialize, Default, Clone)]
@@ -920,6 +1176,85 @@ pub(crate) struct PartData {
     pub(crate) tool: Option<String>,
     pub(crate) thought: Option<String>,
     pub(crate) state: Option<ToolState>,
+    pub(crate) time: Option<PartTime>,
+}
+
+/// `ToolState` without output and error, which serde then skips unparsed.
+#[derive(Deserialize)]
+struct ToolStateSummary {
+    input: Option<ToolStateInput>,
+    status: Option<LenientString>,
+    time: Option<PartTime>,
+}
+
+/// `PartData` as aggregation reads it.
+#[derive(Deserialize)]
+struct PartSummary {
+    #[serde(rename = "type")]
+    part_type: Option<String>,
+    text: Option<String>,
+    tool: Option<String>,
+    thought: Option<String>,
+    state: Option<ToolStateSummary>,
+    time: Option<PartTime>,
+}
+
+impl From<PartSummary> for PartData {
+    fn from(p: PartSummary) -> Self {
+        Self {
+            part_type: p.part_type,
+            text: p.text,
+            tool: p.tool,
+            thought: p.thought,
+            state: p.state.map(|s| ToolState {
+                input: s.input,
+                status: s.status,
+                output: None,
+                error: None,
+                time: s.time,
+            }),
+            time: p.time,
+        }
+    }
+}
+
+impl PartData {
+    /// Parse a stored part; `PartDetail::Summary` leaves out tool output and errors.
+    pub(crate) fn parse(bytes: &[u8], detail: PartDetail) -> Option<Self> {
+        match detail {
+            PartDetail::Full => serde_json::from_slice(bytes).ok(),
+            PartDetail::Summary => serde_json::from_slice::<PartSummary>(bytes)
+                .ok()
+                .map(Self::from),
+        }
+    }
+
+    /// Copy without tool output and errors.
+    pub(crate) fn summary(&self) -> Self {
+        Self {
+            part_type: self.part_type.clone(),
+            text: self.text.clone(),
+            tool: self.tool.clone(),
+            thought: self.thought.clone(),
+            state: self.state.as_ref().map(|s| ToolState {
+                input: s.input.clone(),
+                status: s.status.clone(),
+                output: None,
+                error: None,
+                time: s.time.clone(),
+            }),
+            time: self.time.clone(),
+        }
+    }
+
+    /// When the part started streaming, from its own time or its tool state.
+    fn start_ms(&self) -> Option<i64> {
+        self.time
+            .as_ref()
+            .and_then(|t| t.start)
+            .or_else(|| self.state.as_ref()?.time.as_ref()?.start)
+            .map(|v| *v)
+    }
 }
 
 // ============================================================================
@@ -947,6 +1282,17 @@ pub fn format_active_duration(ms: i64) -> String {
     }
 }
 
+/// Short duration for latencies: `850ms`, `12.3s`, else as `format_active_duration`.
+pub fn format_latency(ms: u64) -> String {
+    if ms < 1_000 {
+        format!("{}ms", ms)
+    } else if ms < 60_000 {
+        format!("{:.1}s", ms as f64 / 1_000.0)
+    } else {
+        format_active_duration(ms as i64)
+    }
+}
+
 #[inline]
 pub fn format_number(value: u64) -> String {
     if value >= 1_000_000_000 {
@@ -1043,164 +1389,17 @@ pub(crate) fn get_model_id(msg: &Message) -> Box<str> {
     }
 }
 
-pub(crate) fn list_message_files(root: &Path) -> Vec<std::path::PathBuf> {
-    let mut all = Vec::new();
-
-    // DB-backed messages
-    for (db_idx, db_path) in get_storage_db_paths().iter().enumerate() {
-        let Some(conn) = open_db(db_path) else {
-            continue;
-        };
-        let Ok(mut stmt) = conn.prepare("SELECT id FROM message") else {
-            continue;
-        };
-        let Ok(rows) = stmt.query_map([], |r| r.get::<_, String>(0)) else {
-            continue;
-        };
-        all.extend(
-            rows.filter_map(|row| row.ok())
-                .map(|id| PathBuf::from(format!("{}{}/message/{}", DB_MESSAGE_PREFIX, db_idx, id))),
-        );
-    }
-
-    // Filesystem-backed messages
-    let mut message_roots = get_storage_paths("message");
-    if message_roots.is_empty() {
-        message_roots.push(root.to_path_buf());
-    }
-
-    for root in message_roots {
-        let Ok(entries) = fs::read_dir(&root) else {
-            continue;
-        };
-        let top_entries: Vec<_> = entries.flatten().collect();
-        let mut files: Vec<PathBuf> = top_entries
-            .par_iter()
-            .flat_map(|entry| {
-                let path = entry.path();
-                if path.is_dir() {
-                    fs::read_dir(&path)
-                        .map(|sub_entries| {
-                            sub_entries
-                                .flatten()
-                                .filter_map(|sub_entry| {
-                                    let sub_path = sub_entry.path();
-                                    if sub_path.extension().is_some_and(|e| e == "json") {
-                                        Some(sub_path)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect::<Vec<_>>()
-                        })
-                        .unwrap_or_default()
-                } else if path.extension().is_some_and(|e| e == "json") {
-                    vec![path]
-                } else {
-                    Vec::new()
-                }
-            })
-            .collect();
-        all.append(&mut files);
-    }
-
-    all
-}
-
-fn load_session_titles() -> (SessionTitlesMap, SessionParentsMap) {
-    if is_db_mode() {
-        let mut titles = FxHashMap::default();
-        let mut parent_map = FxHashMap::default();
-        for db_path in get_storage_db_paths() {
-            let Some(conn) = open_db(&db_path) else {
-                continue;
-            };
-            let Ok(mut stmt) = conn.prepare("SELECT id, title, parent_id FROM session") else {
-                continue;
-            };
-            let Ok(rows) = stmt.query_map([], |r| {
-                Ok((
-                    r.get::<_, String>(0)?,
-                    r.get::<_, String>(1).unwrap_or_default(),
-                    r.get::<_, Option<String>>(2).unwrap_or(None),
-                ))
-            }) else {
-                continue;
-            };
-
-            for row in rows.flatten() {
-                let id: Box<str> = row.0.into_boxed_str();
-                if !titles.contains_key(id.as_ref()) {
-                    titles.insert(id.clone(), row.1);
-                }
-                if let Some(pid) = row.2 {
-                    if !pid.is_empty() {
-                        parent_map.entry(id).or_insert_with(|| pid.into_boxed_str());
-                    }
-                }
-            }
-        }
-        return (titles, parent_map);
-    }
-
-    let mut all_sessions: Vec<(Box<str>, String, Option<Box<str>>)> = Vec::new();
-    for root in get_storage_paths("session") {
-        let Ok(entries) = fs::read_dir(&root) else {
-            continue;
-        };
-        let top_entries: Vec<_> = entries.flatten().collect();
-        let mut root_sessions: Vec<(Box<str>, String, Option<Box<str>>)> = top_entries
-            .par_iter()
-            .flat_map(|entry| {
-                let path = entry.path();
-                if path.is_dir() {
-                    fs::read_dir(&path)
-                        .map(|sub| {
-                            sub.flatten()
-                                .filter_map(|se| {
-                                    let bytes = fs::read(se.path()).ok()?;
-                                    let session =
-                                        serde_json::from_slice::<SessionData>(&bytes).ok()?;
-                                    let id = session
-                                        .id
-                                        .map(|s| s.0)
-                                        .unwrap_or_default()
-                                        .into_boxed_str();
-                                    let title = session.title.map(|s| s.0).unwrap_or_default();
-                                    let parent = session.parent_id.map(|s| s.0.into_boxed_str());
-                                    Some((id, title, parent))
-                                })
-                                .collect::<Vec<_>>()
-                        })
-                        .unwrap_or_default()
-                } else if path.extension().is_some_and(|e| e == "json") {
-                    fs::read(&path)
-                        .ok()
-                        .and_then(|bytes| serde_json::from_slice::<SessionData>(&bytes).ok())
-                        .map(|session| {
-                            let id = session.id.map(|s| s.0).unwrap_or_default().into_boxed_str();
-                            let title = session.title.map(|s| s.0).unwrap_or_default();
-                            let parent = session.parent_id.map(|s| s.0.into_boxed_str());
-                            vec![(id, title, parent)]
-                        })
-                        .unwrap_or_default()
-                } else {
-                    Vec::new()
-                }
-            })
-            .collect();
-        all_sessions.append(&mut root_sessions);
-    }
-
-    let mut titles = FxHashMap::with_capacity_and_hasher(all_sessions.len(), Default::default());
+fn load_session_titles(
+    backends: &[Box<dyn crate::storage::StorageBackend>],
+) -> (SessionTitlesMap, SessionParentsMap) {
+    let sessions = crate::storage::list_sessions_in(backends);
+    let mut titles = FxHashMap::with_capacity_and_hasher(sessions.len(), Default::default());
     let mut parent_map = FxHashMap::default();
-    for (id, title, parent) in all_sessions {
-        if let Some(pid) = parent {
-            if !pid.is_empty() {
-                parent_map.insert(id.clone(), pid);
-            }
+    for session in sessions {
+        if let Some(pid) = session.parent_id {
+            parent_map.entry(session.id.clone()).or_insert(pid);
         }
-        titles.insert(id, title);
+        titles.entry(session.id).or_insert(session.title);
     }
     (titles, parent_map)
 }
@@ -1219,7 +1418,7 @@ pub fn extract_agent_name(title: &str) -> Box<str> {
 }
 
 #[inline]
-fn sort_file_diffs(file_diffs: &mut [FileDiff]) {
+pub(crate) fn sort_file_diffs(file_diffs: &mut [FileDiff]) {
     file_diffs.sort_by(|a, b| {
         let order = |s: &str| match s {
             "modified" => 0,
@@ -1233,149 +1432,6 @@ fn sort_file_diffs(file_diffs: &mut [FileDiff]) {
     });
 }
 
-pub(crate) fn load_session_diff_map() -> FxHashMap<String, Vec<FileDiff>> {
-    let mut out: FxHashMap<String, Vec<FileDiff>> = FxHashMap::default();
-    for root in get_storage_paths("session_diff") {
-        let Ok(entries) = fs::read_dir(&root) else {
-            continue;
-        };
-        let all_entries: Vec<_> = entries.flatten().collect();
-        let rows: Vec<(String, Vec<FileDiff>)> = all_entries
-            .par_iter()
-            .filter_map(|entry| {
-                let path = entry.path();
-                if path.extension().is_none_or(|e| e != "json") {
-                    return None;
-                }
-                let stem = path.file_stem()?.to_str()?;
-                let bytes = fs::read(&path).ok()?;
-                let entries = serde_json::from_slice::<Vec<SessionDiffEntry>>(&bytes).ok()?;
-
-                let mut diffs: Vec<FileDiff> = entries
-                    .into_iter()
-                    .map(|item| FileDiff {
-                        path: item
-                            .file
-                            .map(|s| s.0)
-                            .unwrap_or_else(|| "unknown".into())
-                            .into_boxed_str(),
-                        additions: item.additions.map(|v| *v).unwrap_or(0),
-                        deletions: item.deletions.map(|v| *v).unwrap_or(0),
-                        status: item
-                            .status
-                            .map(|s| s.0)
-                            .unwrap_or_else(|| "modified".into())
-                            .into_boxed_str(),
-                    })
-                    .collect();
-                sort_file_diffs(&mut diffs);
-                Some((stem.to_string(), diffs))
-            })
-            .collect();
-        for (session_id, diffs) in rows {
-            out.entry(session_id).or_insert(diffs);
-        }
-    }
-
-    // DB fallback: if session_diff files are unavailable for a session, read summary_diffs from SQLite.
-    if is_db_mode() {
-        for db_path in get_storage_db_paths() {
-            let Some(conn) = open_db(&db_path) else {
-                continue;
-            };
-            let Ok(mut stmt) = conn.prepare(
-                "SELECT id, summary_diffs FROM session WHERE summary_diffs IS NOT NULL AND summary_diffs <> ''",
-            ) else {
-                continue;
-            };
-            let Ok(rows) = stmt.query_map([], |r| {
-                Ok((
-                    r.get::<_, String>(0)?,
-                    r.get::<_, String>(1).unwrap_or_default(),
-                ))
-            }) else {
-                continue;
-            };
-
-            for (session_id, json) in rows.flatten() {
-                if out.contains_key(&session_id) {
-                    continue;
-                }
-                let Ok(entries) = serde_json::from_str::<Vec<SessionDiffEntry>>(&json) else {
-                    continue;
-                };
-                let mut diffs: Vec<FileDiff> = entries
-                    .into_iter()
-                    .map(|item| FileDiff {
-                        path: item
-                            .file
-                            .map(|s| s.0)
-                            .unwrap_or_else(|| "unknown".into())
-                            .into_boxed_str(),
-                        additions: item.additions.map(|v| *v).unwrap_or(0),
-                        deletions: item.deletions.map(|v| *v).unwrap_or(0),
-                        status: item
-                            .status
-                            .map(|s| s.0)
-                            .unwrap_or_else(|| "modified".into())
-                            .into_boxed_str(),
-                    })
-                    .collect();
-                sort_file_diffs(&mut diffs);
-                out.insert(session_id, diffs);
-            }
-        }
-    }
-
-    out
-}
-
-// ============================================================================
-// Session Diff Loading
-// ============================================================================
-
-pub(crate) fn load_session_diff_totals(
-    session_diff_map: &FxHashMap<String, Vec<FileDiff>>,
-) -> FxHashMap<String, (u64, u64)> {
-    let mut totals: FxHashMap<String, (u64, u64)> = session_diff_map
-        .iter()
-        .map(|(id, diffs)| {
-            let adds: u64 = diffs.iter().map(|d| d.additions).sum();
-            let dels: u64 = diffs.iter().map(|d| d.deletions).sum();
-            (id.clone(), (adds, dels))
-        })
-        .collect();
-
-    if is_db_mode() {
-        for db_path in get_storage_db_paths() {
-            let Some(conn) = open_db(&db_path) else {
-                continue;
-            };
-            let Ok(mut stmt) = conn.prepare(
-                "SELECT id, COALESCE(summary_additions, 0), COALESCE(summary_deletions, 0) FROM session",
-            ) else {
-                continue;
-            };
-            let Ok(rows) = stmt.query_map([], |r| {
-                Ok((
-                    r.get::<_, String>(0)?,
-                    r.get::<_, i64>(1).unwrap_or(0),
-                    r.get::<_, i64>(2).unwrap_or(0),
-                ))
-            }) else {
-                continue;
-            };
-            for (id, adds, dels) in rows.flatten() {
-                totals
-                    .entry(id)
-                    .or_insert((adds.max(0) as u64, dels.max(0) as u64));
-            }
-        }
-    }
-
-    totals
-}
-
 /// Compute incremental diffs: current minus previous cumulative state.
 #[inline]
 fn compute_incremental_diffs(current: &[FileDiff], previous: &[FileDiff]) -> Vec<FileDiff> {
@@ -1430,15 +1486,18 @@ fn compute_incremental_diffs(current: &[FileDiff], previous: &[FileDiff]) -> Vec
 // ============================================================================
 
 pub fn collect_stats() -> Stats {
+    collect_stats_from(crate::storage::backends())
+}
+
+/// Aggregate everything held by `backends`.
+pub(crate) fn collect_stats_from(backends: &[Box<dyn crate::storage::StorageBackend>]) -> Stats {
     let mut totals = Totals::default();
 
-    let message_path = get_storage_path("message");
-    let part_roots = get_storage_paths("part");
     let ((session_titles, raw_parent_map), (session_diff_map, msg_files)) = rayon::join(
-        || load_session_titles(),
+        || load_session_titles(backends),
         || {
-            let diff_map = load_session_diff_map();
-            let files = list_message_files(Path::new(&message_path));
+            let diff_map = crate::storage::load_diffs_in(backends);
+            let files = crate::storage::list_messages_in(backends);
             (diff_map, files)
         },
     );
@@ -1469,7 +1528,7 @@ pub fn collect_stats() -> Stats {
 
     let mut per_day: FxHashMap<String, DayStat> =
         FxHashMap::with_capacity_and_hasher(msg_files.len() / 20, Default::default());
-    let mut model_stats: FxHashMap<Box<str>, ModelUsage> =
+    let mut model_stats: FxHashMap<(Box<str>, crate::import::Source), ModelUsage> =
         FxHashMap::with_capacity_and_hasher(8, Default::default());
     let mut session_message_files: FxHashMap<String, FxHashSet<std::path::PathBuf>> =
         FxHashMap::with_capacity_and_hasher(128, Default::default());
@@ -1480,7 +1539,8 @@ pub fn collect_stats() -> Stats {
 
     struct FullMessageData {
         msg: Message,
-        tools: Vec<Box<str>>,
+        /// Tool name, whether the call failed and how long it took.
+        tools: Vec<(Box<str>, bool, Option<u32>)>,
         parts: Vec<PartData>,
         path: std::path::PathBuf,
         message_id: Box<str>,
@@ -1491,7 +1551,7 @@ pub fn collect_stats() -> Stats {
     let raw_messages: Vec<(Message, std::path::PathBuf, Box<str>)> = msg_files
         .par_iter()
         .filter_map(|p| {
-            let msg: Message = load_message_from_path(p)?;
+            let msg: Message = crate::storage::load_message_in(backends, p)?;
             let message_id = match &msg.id {
                 Some(id) if !id.0.is_empty() => id.0.clone().into_boxed_str(),
                 _ => p.to_string_lossy().to_string().into_boxed_str(),
@@ -1507,7 +1567,7 @@ pub fn collect_stats() -> Stats {
         .filter(|id| !id.is_empty())
         .collect();
     let mut all_parts_map: FxHashMap<Box<str>, Vec<PartData>> =
-        batch_load_parts_all(&all_msg_ids, &part_roots);
+        crate::storage::load_parts_in(backends, &all_msg_ids, PartDetail::Summary);
 
     // Step 3: Build FullMessageData with cached parts
     let mut processed_data: Vec<FullMessageData> = raw_messages
@@ -1519,10 +1579,17 @@ pub fn collect_stats() -> Stats {
                 .and_then(|id| all_parts_map.remove(id.0.as_str()))
                 .unwrap_or_default();
 
-            let tools: Vec<Box<str>> = parts
+            let tools: Vec<(Box<str>, bool, Option<u32>)> = parts
                 .iter()
                 .filter(|p| p.part_type.as_deref() == Some("tool"))
-                .filter_map(|p| p.tool.as_ref().map(|t| t.as_str().into()))
+                .filter_map(|p| {
+                    let state = p.state.as_ref();
+                    let failed = state.is_some_and(ToolState::failed);
+                    let duration = state.and_then(ToolState::duration_ms).map(clamp_ms);
+                    p.tool
+                        .as_ref()
+                        .map(|t| (t.as_str().into(), failed, duration))
+                })
                 .collect();
 
             let cumulative_diffs: Vec<FileDiff> = msg
@@ -1610,6 +1677,7 @@ pub fn collect_stats() -> Stats {
             .map(|a| a.0.clone().into_boxed_str())
             .unwrap_or_else(|| "unknown".into());
 
+        let source = crate::import::Source::of_path(&data.path);
         if !session_id_boxed.is_empty() {
             session_message_files
                 .entry(session_id_boxed.to_string())
@@ -1696,27 +1764,15 @@ pub fn collect_stats() -> Stats {
         totals.tokens.cache_read += tokens_from_msg.cache_read;
         totals.tokens.cache_write += tokens_from_msg.cache_write;
 
+        let model_key = (model_id.clone(), source);
         if is_assistant {
-            let model_entry = model_stats.entry(model_id.clone()).or_insert_with(|| {
-                let name_str: &str = &model_id;
-                let short: Box<str> = name_str.rsplit('/').next().unwrap_or(name_str).into();
-                let provider: Box<str> = name_str.split('/').next().unwrap_or(name_str).into();
-                ModelUsage {
-                    name: model_id.clone(),
-                    short_name: short.clone(),
-                    provider: provider.clone(),
-                    display_name: format!("{}/{}", provider, short).into_boxed_str(),
-                    messages: 0,
-                    sessions: FxHashSet::default(),
-                    tokens: Tokens::default(),
-                    tools: FxHashMap::default(),
-                    agents: FxHashMap::default(),
-                    daily_tokens: FxHashMap::default(),
-                    daily_last_hour: FxHashMap::default(),
-                    cost: 0.0,
-                }
-            });
+            let model_entry = model_stats
+                .entry(model_key.clone())
+                .or_insert_with(|| ModelUsage::new(&model_id));
             model_entry.messages += 1;
+            let model_day = model_entry.daily.entry(day.clone()).or_default();
+            model_day.messages += 1;
+            model_day.cost += cost;
             if !effective_session_id.is_empty()
                 && !model_entry.sessions.contains(effective_session_id.as_ref())
             {
@@ -1730,6 +1786,7 @@ pub fn collect_stats() -> Stats {
             model_entry.tokens.cache_read += tokens_from_msg.cache_read;
             model_entry.tokens.cache_write += tokens_from_msg.cache_write;
             *model_entry.daily_tokens.entry(day.clone()).or_insert(0) += tokens_from_msg.total();
+            *model_entry.daily_split.entry(day.clone()).or_default() += tokens_from_msg;
             if let Some(secs) = ts_val {
                 if let Some(dt) = chrono::DateTime::from_timestamp(secs, 0) {
                     model_entry
@@ -1737,6 +1794,26 @@ pub fn collect_stats() -> Stats {
                         .insert(day.clone(), dt.hour() as u8);
                 }
             }
+            if let Some(usage) = MessageUsage::from_message(msg, tokens_from_msg) {
+                model_entry
+                    .daily_messages
+                    .entry(day.clone())
+                    .or_default()
+                    .push(usage);
+            }
+            // Imported logs stamp messages when blocks were written, not when
+            // the request went out and the reply finished
+            let timed = source == crate::import::Source::OpenCode;
+            if let Some(sample) = timed
+                .then(|| SpeedSample::from_message(msg, &data.parts))
+                .flatten()
+            {
+                model_entry
+                    .daily_speed
+                    .entry(day.clone())
+                    .or_default()
+                    .push(sample);
+            }
             if let Some(agent) = msg
                 .agent
                 .as_ref()
@@ -1747,6 +1824,9 @@ pub fn collect_stats() -> Stats {
                     .agents
                     .entry(agent.to_string().into_boxed_str())
                     .or_insert(0) += 1;
+                if let Some(model_day) = model_entry.daily.get_mut(&day) {
+                    *model_day.agents.entry(agent.into()).or_insert(0) += 1;
+                }
             }
         }
 
@@ -1930,11 +2010,32 @@ pub fn collect_stats() -> Stats {
             }
         }
 
-        for t in data.tools {
+        for (t, failed, duration) in data.tools {
             *totals.tools.entry(t.clone()).or_insert(0) += 1;
             *session_stat.tools.entry(t.clone()).or_insert(0) += 1;
+            if let Some(ms) = duration {
+                totals.tool_durations.entry(t.clone()).or_default().push(ms);
+                session_stat
+                    .tool_durations
+                    .entry(t.clone())
+                    .or_default()
+                    .push(ms);
+            }
+            if failed {
+                *totals.tool_errors.entry(t.clone()).or_insert(0) += 1;
+                *session_stat.tool_errors.entry(t.clone()).or_insert(0) += 1;
+            }
             if is_assistant {
-                if let Some(model_entry) = model_stats.get_mut(&model_id) {
+                if let Some(model_entry) = model_stats.get_mut(&model_key) {
+                    if let Some(model_day) = model_entry.daily.get_mut(&day) {
+                        if failed {
+                            *model_day.tool_errors.entry(t.clone()).or_insert(0) += 1;
+                        }
+                        *model_day.tools.entry(t.clone()).or_insert(0) += 1;
+                    }
+                    if failed {
+                        *model_entry.tool_errors.entry(t.clone()).or_insert(0) += 1;
+                    }
                     *model_entry.tools.entry(t).or_insert(0) += 1;
                 }
             }
@@ -2034,7 +2135,7 @@ pub fn collect_stats() -> Stats {
 
     // Precompute diff totals from session_diff_map for global totals
     let precomputed_diff_totals: FxHashMap<String, (u64, u64)> =
-        load_session_diff_totals(&session_diff_map);
+        crate::storage::load_diff_totals_in(backends, &session_diff_map);
 
     // Build sorted list of days per session to compute previous day's cumulative state
     let mut session_sorted_days: FxHashMap<String, Vec<String>> =
@@ -2136,7 +2237,16 @@ pub fn collect_stats() -> Stats {
         }
     }
 
-    let mut model_usage: Vec<ModelUsage> = model_stats.into_values().collect();
+    // One entry per model, keeping each source's share
+    let mut merged: FxHashMap<Box<str>, ModelUsage> = FxHashMap::default();
+    for ((name, source), usage) in model_stats {
+        let entry = merged
+            .entry(name.clone())
+            .or_insert_with(|| ModelUsage::new(&name));
+        entry.merge(&usage);
+        entry.by_source.insert(source, usage);
+    }
+    let mut model_usage: Vec<ModelUsage> = merged.into_values().collect();
     model_usage.sort_unstable_by(|a, b| b.tokens.total().cmp(&a.tokens.total()));
 
     // Sort agents in each session: main agent first, then alphabetically
@@ -2173,12 +2283,10 @@ fn load_session_chat_internal(
     day_filter: Option<&str>,
     since_ts: Option<i64>,
 ) -> (Vec<ChatMessage>, i64) {
-    let part_roots = get_storage_paths("part");
-
     let mut session_msgs: Vec<Message> = if let Some(f) = files {
         f.par_iter()
             .filter_map(|p| {
-                let msg: Message = load_message_from_path(p)?;
+                let msg: Message = crate::storage::load_message(p)?;
                 if let Some(target_day) = day_filter {
                     let msg_day = get_day(msg.time.as_ref().and_then(|t| t.created.map(|v| *v)));
                     if msg_day != target_day {
@@ -2199,12 +2307,11 @@ fn load_session_chat_internal(
             })
             .collect()
     } else {
-        let message_path = get_storage_path("message");
-        let msg_files = list_message_files(Path::new(&message_path));
+        let msg_files = crate::storage::list_messages();
         msg_files
             .par_iter()
             .filter_map(|p| {
-                let msg: Message = load_message_from_path(p)?;
+                let msg: Message = crate::storage::load_message(p)?;
                 if let Some(session_id) = session_id {
                     if msg.session_id.as_ref().map(|s| s.as_ref()) != Some(session_id) {
                         return None;
@@ -2244,15 +2351,18 @@ fn load_session_chat_internal(
         .filter_map(|m| m.id.as_ref().map(|id| id.0.as_str()))
         .filter(|id| !id.is_empty())
         .collect();
-    let mut parts_map = batch_load_parts_all(&msg_ids, &part_roots);
+    let mut parts_map = crate::storage::load_parts(&msg_ids, PartDetail::Full);
     let session_msgs_with_parts: Vec<(Message, Vec<MessageContent>)> = session_msgs
         .into_iter()
         .map(|msg| {
             let parts_vec = msg
                 .id
                 .as_ref()
-                .and_then(|id| parts_map.remove(id.0.as_str()))
-                .map(parts_to_content)
+                .and_then(|id| {
+                    let parts = parts_map.remove(id.0.as_str())?;
+                    let reasoning = msg.tokens.as_ref().map_or(0, |t| Tokens::from(t).reasoning);
+                    Some(parts_to_content(parts, &id.0, reasoning))
+                })
                 .unwrap_or_default();
             (msg, parts_vec)
         })
@@ -2361,6 +2471,8 @@ pub struct ModelTokenStats {
     pub prompts: u64,
     pub tokens: Tokens,
     pub cost: f64,
+    /// Messages priced individually, as in [`ModelUsage::daily_messages`].
+    pub priced_messages: Vec<MessageUsage>,
 }
 
 #[derive(Clone, Default)]
@@ -2380,6 +2492,7 @@ pub fn load_session_details(
         is_subagent: bool,
         tokens: Tokens,
         cost: f64,
+        usage: Option<MessageUsage>,
     }
 
     #[inline]
@@ -2395,8 +2508,10 @@ pub fn load_session_details(
                 prompts: 0,
                 tokens: Tokens::default(),
                 cost: 0.0,
+                priced_messages: Vec::new(),
             });
         entry.messages += 1;
+        entry.priced_messages.extend(ms.usage);
         if ms.is_user && !ms.is_subagent {
             entry.prompts += 1;
         }
@@ -2420,8 +2535,10 @@ pub fn load_session_details(
                 prompts: 0,
                 tokens: Tokens::default(),
                 cost: 0.0,
+                priced_messages: Vec::new(),
             });
             entry.messages += v.messages;
+            entry.priced_messages.extend(v.priced_messages);
             entry.prompts += v.prompts;
             entry.cost += v.cost;
             entry.tokens.input += v.tokens.input;
@@ -2437,7 +2554,7 @@ pub fn load_session_details(
     let messages: Vec<Message> = if let Some(f) = files {
         f.par_iter()
             .filter_map(|p| {
-                let msg: Message = load_message_from_path(p)?;
+                let msg: Message = crate::storage::load_message(p)?;
                 if let Some(target_day) = day_filter {
                     let msg_day = get_day(msg.time.as_ref().and_then(|t| t.created.map(|v| *v)));
                     if msg_day != target_day {
@@ -2448,12 +2565,11 @@ pub fn load_session_details(
             })
             .collect()
     } else {
-        let message_path = get_storage_path("message");
-        let msg_files = list_message_files(Path::new(&message_path));
+        let msg_files = crate::storage::list_messages();
         msg_files
             .par_iter()
             .filter_map(|p| {
-                let msg: Message = load_message_from_path(p)?;
+                let msg: Message = crate::storage::load_message(p)?;
                 if msg.session_id.as_ref().map(|s| s.as_ref()) != Some(session_id) {
                     return None;
                 }
@@ -2474,8 +2590,8 @@ pub fn load_session_details(
         .filter_map(|m| m.id.as_ref().map(|id| id.0.as_str()))
         .filter(|id| !id.is_empty())
         .collect();
-    let part_roots = get_storage_paths("part");
-    let parts_map: FxHashMap<Box<str>, Vec<PartData>> = batch_load_parts_all(&msg_ids, &part_roots);
+    let parts_map: FxHashMap<Box<str>, Vec<PartData>> =
+        crate::storage::load_parts(&msg_ids, PartDetail::Summary);
 
     // Step 3: Process messages with cached parts
     let model_map: FxHashMap<Box<str>, ModelTokenStats> = messages
@@ -2512,6 +2628,7 @@ pub fn load_session_details(
                 model: model_id,
                 is_user,
                 is_subagent,
+                usage: MessageUsage::from_message(&msg, tokens),
                 tokens,
                 cost,
             }
@@ -2552,7 +2669,7 @@ pub fn load_combined_session_chat(
     let mut filtered_msgs: Vec<(Message, bool, Option<Box<str>>)> = all_files
         .par_iter()
         .filter_map(|p| {
-            let msg: Message = load_message_from_path(p)?;
+            let msg: Message = crate::storage::load_message(p)?;
             if let Some(target_day) = day_filter {
                 let msg_day = get_day(msg.time.as_ref().and_then(|t| t.created.map(|v| *v)));
                 if msg_day != target_day {
@@ -2577,21 +2694,23 @@ pub fn load_combined_session_chat(
     });
 
     // Batch-load parts from all sources
-    let part_roots = get_storage_paths("part");
     let msg_ids: Vec<&str> = filtered_msgs
         .iter()
         .filter_map(|(m, _, _)| m.id.as_ref().map(|id| id.0.as_str()))
         .filter(|id| !id.is_empty())
         .collect();
-    let mut parts_map = batch_load_parts_all(&msg_ids, &part_roots);
+    let mut parts_map = crate::storage::load_parts(&msg_ids, PartDetail::Full);
     let all_messages: MessageBatch = filtered_msgs
         .into_iter()
         .map(|(msg, is_sub, agent_lbl)| {
             let parts_vec = msg
                 .id
                 .as_ref()
-                .and_then(|id| parts_map.remove(id.0.as_str()))
-                .map(parts_to_content)
+                .and_then(|id| {
+                    let parts = parts_map.remove(id.0.as_str())?;
+                    let reasoning = msg.tokens.as_ref().map_or(0, |t| Tokens::from(t).reasoning);
+                    Some(parts_to_content(parts, &id.0, reasoning))
+                })
                 .unwrap_or_default();
             (msg, parts_vec, is_sub, agent_lbl)
         })
@@ -1,5 +1,6 @@
 //! Incremental statistics cache with file-based persistence.
 
+use crate::storage::PartDetail;
 use bincode::{deserialize, serialize};
 use chrono::Timelike;
 use parking_lot::RwLock;
@@ -16,7 +17,7 @@ use std::{
 type SessionDiffs = FxHashMap<String, FxHashMap<String, crate::stats::FileDiff>>;
 type SessionSortedDays = FxHashMap<String, Vec<String>>;
 
-const CACHE_FORMAT_VERSION: u64 = 12;
+const CACHE_FORMAT_VERSION: u64 = 19;
 
 /// Metadata for file validation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,15 +69,29 @@ pub struct StatsCache {
     stats: Arc<RwLock<CachedStats>>,
 }
 
+/// Location of the bincode cache for the configured storage roots.
+fn cache_file_path() -> PathBuf {
+    let cache_dir = std::env::var("XDG_CACHE_HOME").unwrap_or_else(|_| {
+        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        format!("{}/.cache", home)
+    });
+    // Extra roots change the dataset, so they get their own cache file
+    let cache_name = if crate::stats::has_extra_roots() {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = rustc_hash::FxHasher::default();
+        crate::stats::get_storage_roots().hash(&mut hasher);
+        format!("cache-{:016x}.bincode", hasher.finish())
+    } else {
+        "cache.bincode".to_string()
+    };
+    PathBuf::from(cache_dir)
+        .join("opencode-stats-tui")
+        .join(cache_name)
+}
+
 impl StatsCache {
     pub fn new(storage_path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
-        let cache_dir = std::env::var("XDG_CACHE_HOME").unwrap_or_else(|_| {
-            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-            format!("{}/.cache", home)
-        });
-        let cache_dir = PathBuf::from(cache_dir);
-        let cache_path = cache_dir.join("opencode-stats-tui").join("cache.bincode");
-
+        let cache_path = cache_file_path();
         if let Some(parent) = cache_path.parent() {
             fs::create_dir_all(parent)?;
         }
@@ -142,6 +157,60 @@ impl StatsCache {
         stats
     }
 
+    /// Bring the on-disk cache up to date by applying the tracked files that
+    /// changed since it was written, without a full scan unless a backend
+    /// needs one. `None` when there is no usable cache.
+    pub fn refresh(&self) -> Option<crate::stats::Stats> {
+        let cached = self.load_cache().ok()?;
+        if cached.format_version != CACHE_FORMAT_VERSION {
+            return None;
+        }
+        let mut stats_lock = self.stats.write();
+        *stats_lock = cached;
+        let changed = self.changed_files(&stats_lock);
+        if changed.is_empty() {
+            // Only directory mtimes moved; rewrite so the cache is newer again
+            let data = serialize(&*stats_lock).ok()?;
+            fs::write(&self.cache_path, data).ok()?;
+        } else {
+            self.update_files_internal(&mut stats_lock, changed);
+        }
+        Some(stats_lock.stats.clone())
+    }
+
+    /// Tracked files that are new, modified or gone since `cached` was written.
+    fn changed_files(&self, cached: &CachedStats) -> Vec<String> {
+        let files = self.list_all_files().unwrap_or_default();
+        let present: FxHashSet<&str> = files.iter().map(String::as_str).collect();
+        let mut changed: Vec<String> = files
+            .par_iter()
+            .filter(|f| {
+                let Some(meta) = cached.file_meta.get(*f) else {
+                    return true;
+                };
+                let Ok(current) = fs::metadata(f) else {
+                    return true;
+                };
+                let mtime = current
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                mtime != meta.mtime || current.len() != meta.size
+            })
+            .cloned()
+            .collect();
+        changed.extend(
+            cached
+                .file_meta
+                .keys()
+                .filter(|p| !present.contains(p.as_str()))
+                .cloned(),
+        );
+        changed
+    }
+
     fn load_cache(&self) -> Result<CachedStats, Box<dyn std::error::Error>> {
         let data = fs::read(&self.cache_path)?;
         Ok(deserialize(&data)?)
@@ -152,10 +221,6 @@ impl StatsCache {
             return false;
         }
 
-        if crate::stats::is_db_mode() && crate::stats::get_storage_db_paths().is_empty() {
-            return false;
-        }
-
         // Optimized: Check a subset of files for changes, but use mtime+size which is very fast
         // We still don't want to check thousands of files every time, so we sample
         // but the sample is now more robust.
@@ -208,57 +273,56 @@ impl StatsCache {
     ) -> FxHashSet<String> {
         let mut affected_sessions = FxHashSet::default();
 
-        // DB mode: sqlite writes update *.{db,-wal,-shm}, not storage/* files.
-        // Preserve existing logic by doing a full refresh when DB files change.
-        if crate::stats::is_db_mode() {
-            let db_paths = crate::stats::get_storage_db_paths();
-            let touched_db = paths.iter().any(|p| {
-                db_paths.iter().any(|db| p == db.to_string_lossy().as_ref())
-                    || p.ends_with("opencode.db")
-                    || p.ends_with("opencode.db-wal")
-                    || p.ends_with("opencode.db-shm")
-                    || p.ends_with("kilo.db")
-                    || p.ends_with("kilo.db-wal")
-                    || p.ends_with("kilo.db-shm")
-            });
-
-            if touched_db {
-                cached.stats = crate::stats::collect_stats();
-                cached.parent_map = cached.stats.parent_map.clone();
-                cached.children_map = cached.stats.children_map.clone();
-
-                for day_stat in cached.stats.per_day.values() {
-                    for id in day_stat.sessions.keys() {
-                        affected_sessions.insert(id.clone());
-                    }
+        // Changes outside every backend (e.g. files next to a database) are ignored
+        let paths: Vec<String> = paths
+            .into_iter()
+            .filter(|p| crate::storage::owns_change(Path::new(p)))
+            .collect();
+        if paths.is_empty() {
+            return affected_sessions;
+        }
+
+        // SQLite writes update *.{db,-wal,-shm} and transcripts are parsed whole,
+        // so a change to a backend that cannot apply single files means a full refresh.
+        if paths
+            .iter()
+            .any(|p| crate::storage::needs_rescan(Path::new(p)))
+        {
+            cached.stats = crate::stats::collect_stats();
+            cached.parent_map = cached.stats.parent_map.clone();
+            cached.children_map = cached.stats.children_map.clone();
+
+            for day_stat in cached.stats.per_day.values() {
+                for id in day_stat.sessions.keys() {
+                    affected_sessions.insert(id.clone());
                 }
+            }
 
-                cached.version += 1;
-                cached.format_version = CACHE_FORMAT_VERSION;
-
-                if let Ok(files) = self.list_all_files() {
-                    for p in files {
-                        if let Ok(m) = fs::metadata(&p) {
-                            let mtime = m
-                                .modified()
-                                .ok()
-                                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                                .map(|d| d.as_secs())
-                                .unwrap_or(0);
-                            cached.file_meta.insert(
-                                p,
-                                FileMeta {
-                                    mtime,
-                                    size: m.len(),
-                                },
-                            );
-                        }
+            cached.version += 1;
+            cached.format_version = CACHE_FORMAT_VERSION;
+
+            if let Ok(files) = self.list_all_files() {
+                for p in files {
+                    if let Ok(m) = fs::metadata(&p) {
+                        let mtime = m
+                            .modified()
+                            .ok()
+                            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                            .map(|d| d.as_secs())
+                            .unwrap_or(0);
+                        cached.file_meta.insert(
+                            p,
+                            FileMeta {
+                                mtime,
+                                size: m.len(),
+                            },
+                        );
                     }
                 }
+            }
 
-                if let Ok(data) = serialize(&*cached) {
-                    let _ = fs::write(&self.cache_path, data);
-                }
+            if let Ok(data) = serialize(&*cached) {
+                let _ = fs::write(&self.cache_path, data);
             }
 
             return affected_sessions;
@@ -348,10 +412,9 @@ impl StatsCache {
         cached.stats.clone_from(stats);
         cached.parent_map = stats.parent_map.clone();
         cached.children_map = stats.children_map.clone();
-        cached.session_diff_map = crate::stats::load_session_diff_map();
-        cached.session_diff_totals =
-            crate::stats::load_session_diff_totals(&cached.session_diff_map);
-        let message_files = self.list_message_files();
+        cached.session_diff_map = crate::storage::load_diffs();
+        cached.session_diff_totals = crate::storage::load_diff_totals(&cached.session_diff_map);
+        let message_files = crate::storage::list_messages();
         let (union_diffs, sorted_days, message_contributions) =
             self.build_session_day_union_diffs(&message_files);
         cached.session_day_union_diffs = union_diffs;
@@ -389,11 +452,6 @@ impl StatsCache {
         }
     }
 
-    fn list_message_files(&self) -> Vec<PathBuf> {
-        let message_path = PathBuf::from(crate::stats::get_storage_path("message"));
-        crate::stats::list_message_files(Path::new(&message_path))
-    }
-
     fn build_session_day_union_diffs(
         &self,
         files: &[PathBuf],
@@ -412,7 +470,7 @@ impl StatsCache {
         let mut messages: Vec<(crate::stats::Message, PathBuf)> = files
             .par_iter()
             .filter_map(|p| {
-                let msg: crate::stats::Message = crate::stats::load_message_from_path(p)?;
+                let msg: crate::stats::Message = crate::storage::load_message(p)?;
                 Some((msg, p.clone()))
             })
             .collect();
@@ -577,73 +635,10 @@ impl StatsCache {
     }
 
     fn list_all_files(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        if crate::stats::is_db_mode() {
-            let mut files = Vec::new();
-            for db in crate::stats::get_storage_db_paths() {
-                if let Some(s) = db.to_str() {
-                    files.push(s.to_string());
-                }
-                let wal = db.with_extension("db-wal");
-                if wal.exists() {
-                    if let Some(s) = wal.to_str() {
-                        files.push(s.to_string());
-                    }
-                }
-                let shm = db.with_extension("db-shm");
-                if shm.exists() {
-                    if let Some(s) = shm.to_str() {
-                        files.push(s.to_string());
-                    }
-                }
-            }
-            return Ok(files);
-        }
-
-        let dirs = ["message", "part", "session", "session_diff"];
-        let files: Vec<String> = dirs
-            .par_iter()
-            .flat_map(|dir| {
-                let mut out = Vec::new();
-                for dp in crate::stats::get_storage_paths(dir) {
-                    if !dp.exists() {
-                        continue;
-                    }
-
-                    if let Ok(entries) = fs::read_dir(&dp) {
-                        let mut local: Vec<String> = entries
-                            .flatten()
-                            .par_bridge()
-                            .flat_map(|entry| {
-                                let p = entry.path();
-                                if p.is_dir() {
-                                    if let Ok(sub_entries) = fs::read_dir(&p) {
-                                        let mut local_files = Vec::new();
-                                        for sub_entry in sub_entries.flatten() {
-                                            let sp = sub_entry.path();
-                                            if sp.extension().is_some_and(|e| e == "json") {
-                                                if let Ok(s) = sp.into_os_string().into_string() {
-                                                    local_files.push(s);
-                                                }
-                                            }
-                                        }
-                                        return local_files;
-                                    }
-                                } else if p.extension().is_some_and(|e| e == "json") {
-                                    if let Ok(s) = p.into_os_string().into_string() {
-                                        return vec![s];
-                                    }
-                                }
-                                Vec::new()
-                            })
-                            .collect();
-                        out.append(&mut local);
-                    }
-                }
-                out
-            })
-            .collect();
-
-        Ok(files)
+        Ok(crate::storage::tracked_files()
+            .into_iter()
+            .filter_map(|p| p.into_os_string().into_string().ok())
+            .collect())
     }
 
     fn incrementally_update_messages(
@@ -651,13 +646,6 @@ impl StatsCache {
         cached: &mut CachedStats,
         path: &str,
     ) -> Option<String> {
-        if crate::stats::is_db_mode() {
-            cached.stats = crate::stats::collect_stats();
-            cached.parent_map = cached.stats.parent_map.clone();
-            cached.children_map = cached.stats.children_map.clone();
-            return None;
-        }
-
         let stats = &mut cached.stats;
         let Ok(bytes) = fs::read(path) else {
             return None;
@@ -678,6 +666,7 @@ impl StatsCache {
         let is_user = role == "user";
         let is_assistant = role == "assistant";
         let model_id = crate::stats::get_model_id(&msg);
+        let source = crate::import::Source::of_path(std::path::Path::new(path));
         let cost = msg.cost.as_ref().map(|c| **c).unwrap_or(0.0);
 
         let agent_name: Box<str> = msg
@@ -718,6 +707,19 @@ impl StatsCache {
         };
 
         let is_new_message = !cached.message_contributions.contains_key(&message_id_str);
+        // A response is timed once, on the update that completes it
+        let was_completed = cached
+            .message_contributions
+            .get(&message_id_str)
+            .is_some_and(|c| c.2 > 0);
+        let speed_sample = if is_assistant && !was_completed {
+            let parts = crate::storage::load_parts(&[&message_id], PartDetail::Summary)
+                .remove(&message_id)
+                .unwrap_or_default();
+            crate::stats::SpeedSample::from_message(&msg, &parts)
+        } else {
+            None
+        };
 
         let mut duration_add = 0;
         if is_assistant {
@@ -729,6 +731,12 @@ impl StatsCache {
                 }
             }
         }
+        // Kept per message once, when its final token counts are known
+        let message_usage = if is_assistant && !was_completed && duration_add > 0 {
+            crate::stats::MessageUsage::from_message(&msg, tokens_add)
+        } else {
+            None
+        };
 
         // Handle updates: if we already processed this message, subtract its old contribution
         if !is_new_message {
@@ -759,15 +767,22 @@ impl StatsCache {
 
             if is_assistant {
                 if let Some(m) = stats.model_usage.iter_mut().find(|m| *m.name == *model_id) {
-                    m.cost -= old_cost;
-                    m.tokens.input = m.tokens.input.saturating_sub(old_tokens.input);
-                    m.tokens.output = m.tokens.output.saturating_sub(old_tokens.output);
-                    m.tokens.reasoning = m.tokens.reasoning.saturating_sub(old_tokens.reasoning);
-                    m.tokens.cache_read = m.tokens.cache_read.saturating_sub(old_tokens.cache_read);
-                    m.tokens.cache_write =
-                        m.tokens.cache_write.saturating_sub(old_tokens.cache_write);
-                    if let Some(dt) = m.daily_tokens.get_mut(&day) {
-                        *dt = dt.saturating_sub(old_tokens.total());
+                    let unrecord = |m: &mut crate::stats::ModelUsage| {
+                        m.cost -= old_cost;
+                        crate::date_range::sub_tokens(&mut m.tokens, &old_tokens);
+                        if let Some(dt) = m.daily_tokens.get_mut(&day) {
+                            *dt = dt.saturating_sub(old_tokens.total());
+                        }
+                        if let Some(split) = m.daily_split.get_mut(&day) {
+                            crate::date_range::sub_tokens(split, &old_tokens);
+                        }
+                        if let Some(model_day) = m.daily.get_mut(&day) {
+                            model_day.cost -= old_cost;
+                        }
+                    };
+                    unrecord(m);
+                    if let Some(part) = m.by_source.get_mut(&source) {
+                        unrecord(part);
                     }
                 }
             }
@@ -841,61 +856,50 @@ impl StatsCache {
             .insert(session_id.clone().into_boxed_str());
 
         if is_assistant {
-            if let Some(m) = stats.model_usage.iter_mut().find(|m| *m.name == *model_id) {
+            let record = |m: &mut crate::stats::ModelUsage| {
                 if is_new_message {
                     m.messages += 1;
                 }
                 m.cost += cost;
-                m.tokens.input += tokens_add.input;
-                m.tokens.output += tokens_add.output;
-                m.tokens.reasoning += tokens_add.reasoning;
-                m.tokens.cache_read += tokens_add.cache_read;
-                m.tokens.cache_write += tokens_add.cache_write;
+                m.tokens += tokens_add;
                 *m.daily_tokens.entry(day.clone()).or_insert(0) += tokens_add.total();
+                *m.daily_split.entry(day.clone()).or_default() += tokens_add;
                 if let Some(secs) = ts {
                     if let Some(dt) = chrono::DateTime::from_timestamp(secs, 0) {
                         m.daily_last_hour.insert(day.clone(), dt.hour() as u8);
                     }
                 }
                 m.sessions.insert(session_id.clone().into_boxed_str());
+                let model_day = m.daily.entry(day.clone()).or_default();
+                model_day.cost += cost;
                 if is_new_message {
+                    model_day.messages += 1;
+                    *model_day.agents.entry(agent_name.clone()).or_insert(0) += 1;
                     *m.agents.entry(agent_name.clone()).or_insert(0) += 1;
                 }
-            } else {
-                let name_str: &str = &model_id;
-                let parts: Vec<&str> = name_str.split('/').collect();
-                let (p, n) = if parts.len() >= 2 {
-                    (parts[0], parts[1])
-                } else {
-                    ("unknown", name_str)
-                };
-                let mut agents = FxHashMap::default();
-                agents.insert(agent_name.clone(), 1);
-                let mut sessions = FxHashSet::default();
-                sessions.insert(session_id.clone().into_boxed_str());
-                let mut daily_tokens = FxHashMap::default();
-                daily_tokens.insert(day.clone(), tokens_add.total());
-                let mut daily_last_hour = FxHashMap::default();
-                if let Some(secs) = ts {
-                    if let Some(dt) = chrono::DateTime::from_timestamp(secs, 0) {
-                        daily_last_hour.insert(day.clone(), dt.hour() as u8);
-                    }
+                if let Some(sample) = speed_sample {
+                    m.daily_speed.entry(day.clone()).or_default().push(sample);
                 }
-                stats.model_usage.push(crate::stats::ModelUsage {
-                    name: model_id.clone(),
-                    short_name: n.into(),
-                    provider: p.into(),
-                    display_name: format!("{}/{}", p, n).into_boxed_str(),
-                    messages: 1,
-                    sessions,
-                    tokens: tokens_add,
-                    tools: FxHashMap::default(),
-                    agents,
-                    daily_tokens,
-                    daily_last_hour,
-                    cost,
-                });
-            }
+                if let Some(usage) = message_usage {
+                    m.daily_messages.entry(day.clone()).or_default().push(usage);
+                }
+            };
+            let idx = match stats.model_usage.iter().position(|m| *m.name == *model_id) {
+                Some(idx) => idx,
+                None => {
+                    stats
+                        .model_usage
+                        .push(crate::stats::ModelUsage::new(&model_id));
+                    stats.model_usage.len() - 1
+                }
+            };
+            let m = &mut stats.model_usage[idx];
+            record(m);
+            record(
+                m.by_source
+                    .entry(source)
+                    .or_insert_with(|| crate::stats::ModelUsage::new(&model_id)),
+            );
         }
 
         {
@@ -1150,13 +1154,6 @@ impl StatsCache {
         cached: &mut CachedStats,
         path: &str,
     ) -> Option<String> {
-        if crate::stats::is_db_mode() {
-            cached.stats = crate::stats::collect_stats();
-            cached.parent_map = cached.stats.parent_map.clone();
-            cached.children_map = cached.stats.children_map.clone();
-            return None;
-        }
-
         let p = std::path::Path::new(path);
         let session_id = p.file_stem()?.to_str()?.to_string();
 
@@ -1239,13 +1236,6 @@ impl StatsCache {
         cached: &mut CachedStats,
         path: &str,
     ) -> Option<String> {
-        if crate::stats::is_db_mode() {
-            cached.stats = crate::stats::collect_stats();
-            cached.parent_map = cached.stats.parent_map.clone();
-            cached.children_map = cached.stats.children_map.clone();
-            return None;
-        }
-
         let bytes = match fs::read(path) {
             Ok(b) => b,
             Err(_) => return None,
@@ -1314,15 +1304,10 @@ impl StatsCache {
     }
 
     fn incrementally_update_parts(&self, stats: &mut crate::stats::Stats, path: &str) {
-        if crate::stats::is_db_mode() {
-            *stats = crate::stats::collect_stats();
-            return;
-        }
-
         let Ok(bytes) = fs::read(path) else {
             return;
         };
-        let Ok(part) = serde_json::from_slice::<crate::stats::PartData>(&bytes) else {
+        let Some(part) = crate::stats::PartData::parse(&bytes, PartDetail::Summary) else {
             return;
         };
         if let Some(text) = &part.text {
@@ -1336,7 +1321,55 @@ impl StatsCache {
         if part.part_type.as_deref() == Some("tool") {
             if let Some(tool) = &part.tool {
                 *stats.totals.tools.entry(tool.clone().into()).or_insert(0) += 1;
+                if let Some(ms) = part.state.as_ref().and_then(|s| s.duration_ms()) {
+                    stats
+                        .totals
+                        .tool_durations
+                        .entry(tool.clone().into())
+                        .or_default()
+                        .push(crate::stats::clamp_ms(ms));
+                }
+                if part.state.as_ref().is_some_and(|s| s.failed()) {
+                    *stats
+                        .totals
+                        .tool_errors
+                        .entry(tool.clone().into())
+                        .or_insert(0) += 1;
+                }
             }
         }
     }
 }
+
+/// Load stats through the on-disk cache, falling back to a full scan.
+pub fn load_stats() -> crate::stats::Stats {
+    match StatsCache::new(crate::stats::default_storage_dir()) {
+        Ok(cache) => cache.load_or_compute(),
+        Err(_) => crate::stats::collect_stats(),
+    }
+}
+
+/// Apply storage changes to the on-disk cache and return the result, or
+/// `None` when there is no usable cache to update.
+pub fn refresh_stats() -> Option<crate::stats::Stats> {
+    StatsCache::new(crate::stats::default_storage_dir())
+        .ok()?
+        .refresh()
+}
+
+/// Read the cached stats and whether storage changed after they were written.
+///
+/// Storage is only checked through mtimes of a few marker files, so this
+/// stays fast enough for shell prompts; callers decide what to do with a
+/// stale result.
+pub fn read_cached_stats() -> Option<(crate::stats::Stats, bool)> {
+    let path = cache_file_path();
+    let written = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    let data = fs::read(&path).ok()?;
+    let cached: CachedStats = deserialize(&data).ok()?;
+    if cached.format_version != CACHE_FORMAT_VERSION {
+        return None;
+    }
+    let stale = crate::storage::last_modified().is_some_and(|changed| changed > written);
+    Some((cached.stats, stale))
+}
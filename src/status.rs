@@ -0,0 +1,286 @@
+//! One-line status summary for shell prompts and status bars.
+//!
+//! The bincode cache written by `StatsCache` is read and checked against the
+//! mtimes of a few storage files, so the command returns in milliseconds while
+//! nothing changed. When storage is newer the changed files are applied to the
+//! cache first, or with `--no-refresh` it is printed as is with a suffix; a
+//! missing cache prints the fallback text.
+
+use crate::cli::invalid_arg;
+use crate::date_range::DateRange;
+use crate::stats::{format_active_duration, format_number, DayStat, Stats, Tokens};
+use std::io::{self, Write};
+
+const DEFAULT_FORMAT: &str = "today ${today.cost} · {today.tokens} tok · {today.sessions} sess";
+const DEFAULT_STALE_SUFFIX: &str = "*";
+
+/// Run the `status` subcommand.
+pub fn run(args: &[String], range: &DateRange) -> io::Result<()> {
+    let mut format = DEFAULT_FORMAT.to_string();
+    let mut refresh = true;
+    let mut stale_suffix = DEFAULT_STALE_SUFFIX.to_string();
+    let mut fallback = String::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-f" | "--format" => {
+                format = iter
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| invalid_arg("--format expects a format string"))?;
+            }
+            "--no-refresh" => refresh = false,
+            "--stale-suffix" => {
+                stale_suffix = iter
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| invalid_arg("--stale-suffix expects text"))?;
+            }
+            "--fallback" => {
+                fallback = iter
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| invalid_arg("--fallback expects text"))?;
+            }
+            "-h" | "--help" => {
+                print_help();
+                return Ok(());
+            }
+            other => return Err(invalid_arg(&format!("unknown argument: {}", other))),
+        }
+    }
+    // Reject bad placeholders even when there is nothing to print yet
+    let template = parse_format(&format).map_err(|e| invalid_arg(&e))?;
+
+    let mut out = io::stdout().lock();
+    let Some((mut stats, mut stale)) = crate::stats_cache::read_cached_stats() else {
+        if !fallback.is_empty() {
+            writeln!(out, "{}", fallback)?;
+        }
+        return Ok(());
+    };
+    if stale && refresh {
+        if let Some(fresh) = crate::stats_cache::refresh_stats() {
+            stats = fresh;
+            stale = false;
+        }
+    }
+    crate::date_range::apply_to_stats(&mut stats, range);
+
+    let mut line = render(&template, &stats);
+    if stale {
+        line.push_str(&stale_suffix);
+    }
+    writeln!(out, "{}", line)
+}
+
+fn print_help() {
+    println!("Usage: opencode-stats-tui status [--format FMT] [--no-refresh] [--stale-suffix TEXT] [--fallback TEXT]");
+    println!("Prints one line from the stats cache, refreshing it when storage changed since.");
+    println!();
+    println!("Placeholders: {{today.FIELD}} and {{total.FIELD}}, plus {{total.days}}.");
+    println!("Fields: cost, tokens, input, output, reasoning, cache_read, cache_write, messages,");
+    println!("        prompts, sessions, additions, deletions, active");
+    println!(
+        "Use {{{{ and }}}} for literal braces. Default: \"{}\"",
+        DEFAULT_FORMAT
+    );
+    println!(
+        "With --no-refresh an outdated cache is printed with the stale suffix (default \"{}\").",
+        DEFAULT_STALE_SUFFIX
+    );
+}
+
+// ============================================================================
+// Format string
+// ============================================================================
+
+#[derive(Debug, Clone, Copy)]
+enum Scope {
+    Today,
+    Total,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Cost,
+    Tokens,
+    Input,
+    Output,
+    Reasoning,
+    CacheRead,
+    CacheWrite,
+    Messages,
+    Prompts,
+    Sessions,
+    Additions,
+    Deletions,
+    Active,
+    Days,
+}
+
+impl Field {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "cost" => Self::Cost,
+            "tokens" => Self::Tokens,
+            "input" => Self::Input,
+            "output" => Self::Output,
+            "reasoning" => Self::Reasoning,
+            "cache_read" => Self::CacheRead,
+            "cache_write" => Self::CacheWrite,
+            "messages" => Self::Messages,
+            "prompts" => Self::Prompts,
+            "sessions" => Self::Sessions,
+            "additions" => Self::Additions,
+            "deletions" => Self::Deletions,
+            "active" => Self::Active,
+            "days" => Self::Days,
+            _ => return None,
+        })
+    }
+}
+
+enum Piece {
+    Text(String),
+    Value(Scope, Field),
+}
+
+fn parse_format(fmt: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = fmt.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let (scope, field) = name
+                    .split_once('.')
+                    .and_then(|(scope, field)| {
+                        let scope = match scope {
+                            "today" => Scope::Today,
+                            "total" => Scope::Total,
+                            _ => return None,
+                        };
+                        let field = Field::parse(field)?;
+                        // Day count only makes sense for the whole range
+                        (!matches!((scope, field), (Scope::Today, Field::Days)))
+                            .then_some((scope, field))
+                    })
+                    .ok_or_else(|| format!("unknown placeholder {{{}}}", name))?;
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(Piece::Value(scope, field));
+            }
+            '}' => return Err("unmatched '}' in format (use '}}')".to_string()),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    Ok(pieces)
+}
+
+/// Values shared by `DayStat` and `Totals`.
+struct Figures {
+    cost: f64,
+    tokens: Tokens,
+    messages: u64,
+    prompts: u64,
+    sessions: usize,
+    additions: u64,
+    deletions: u64,
+    active_ms: i64,
+}
+
+impl Figures {
+    fn from_day(day: Option<&DayStat>) -> Self {
+        let Some(day) = day else {
+            return Self {
+                cost: 0.0,
+                tokens: Tokens::default(),
+                messages: 0,
+                prompts: 0,
+                sessions: 0,
+                additions: 0,
+                deletions: 0,
+                active_ms: 0,
+            };
+        };
+        Self {
+            cost: day.display_cost(),
+            tokens: day.tokens,
+            messages: day.messages,
+            prompts: day.prompts,
+            sessions: day.sessions.len(),
+            additions: day.diffs.additions,
+            deletions: day.diffs.deletions,
+            active_ms: day.sessions.values().map(|s| s.active_duration_ms).sum(),
+        }
+    }
+
+    fn from_totals(stats: &Stats) -> Self {
+        let totals = &stats.totals;
+        Self {
+            cost: totals.display_cost(),
+            tokens: totals.tokens,
+            messages: totals.messages,
+            prompts: totals.prompts,
+            sessions: totals.sessions.len(),
+            additions: totals.diffs.additions,
+            deletions: totals.diffs.deletions,
+            active_ms: stats
+                .per_day
+                .values()
+                .flat_map(|d| d.sessions.values())
+                .map(|s| s.active_duration_ms)
+                .sum(),
+        }
+    }
+
+    fn value(&self, field: Field, days: usize) -> String {
+        match field {
+            Field::Cost => format!("{:.2}", self.cost),
+            Field::Tokens => format_number(self.tokens.total()),
+            Field::Input => format_number(self.tokens.input),
+            Field::Output => format_number(self.tokens.output),
+            Field::Reasoning => format_number(self.tokens.reasoning),
+            Field::CacheRead => format_number(self.tokens.cache_read),
+            Field::CacheWrite => format_number(self.tokens.cache_write),
+            Field::Messages => format_number(self.messages),
+            Field::Prompts => format_number(self.prompts),
+            Field::Sessions => self.sessions.to_string(),
+            Field::Additions => format_number(self.additions),
+            Field::Deletions => format_number(self.deletions),
+            Field::Active => format_active_duration(self.active_ms),
+            Field::Days => days.to_string(),
+        }
+    }
+}
+
+fn render(template: &[Piece], stats: &Stats) -> String {
+    let today_key = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let today = Figures::from_day(stats.per_day.get(&today_key));
+    let total = Figures::from_totals(stats);
+    let days = stats.per_day.len();
+
+    let mut line = String::new();
+    for piece in template {
+        match piece {
+            Piece::Text(text) => line.push_str(text),
+            Piece::Value(Scope::Today, field) => line.push_str(&today.value(*field, days)),
+            Piece::Value(Scope::Total, field) => line.push_str(&total.value(*field, days)),
+        }
+    }
+    line
+}
@@ -0,0 +1,356 @@
+//! Storage backends behind one interface.
+//!
+//! opencode and kilo keep their data either as JSON files under `storage/` or
+//! in an SQLite database, and transcripts imported from other tools are held
+//! in memory. Each layout implements `StorageBackend`; the loaders in
+//! `stats.rs` and the cache only go through the registry below, so one layout
+//! can change without touching the others.
+
+mod json;
+mod memory;
+mod sqlite;
+#[cfg(test)]
+pub(crate) mod tests;
+
+pub(crate) use memory::MemoryBackend;
+
+/// How much of each part to load.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PartDetail {
+    /// Everything the session chat shows, tool output and errors included.
+    Full,
+    /// Without tool output and errors, which aggregation never reads; they
+    /// hold whole file reads and command logs.
+    Summary,
+}
+
+use crate::stats::{FileDiff, LenientString, LenientU64, Message, PartData};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static BACKENDS: OnceLock<Vec<Box<dyn StorageBackend>>> = OnceLock::new();
+
+/// Id, title and parent of one session.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionRecord {
+    pub(crate) id: Box<str>,
+    pub(crate) title: String,
+    pub(crate) parent_id: Option<Box<str>>,
+}
+
+/// One place sessions, messages, parts and diffs are read from.
+///
+/// Message paths are whatever the backend hands out from `list_messages`:
+/// real files for JSON storage, virtual `db://` or `import://` paths
+/// otherwise. They are only ever passed back to the backend that owns them.
+pub(crate) trait StorageBackend: Send + Sync {
+    /// Storage root the data belongs to, used for root labels.
+    fn root(&self) -> Option<&Path> {
+        None
+    }
+
+    fn list_sessions(&self) -> Vec<SessionRecord>;
+
+    fn list_messages(&self) -> Vec<PathBuf>;
+
+    /// True for a path this backend handed out from `list_messages`.
+    fn owns_message(&self, path: &Path) -> bool;
+
+    fn load_message(&self, path: &Path) -> Option<Message>;
+
+    /// Parts of whichever of `message_ids` this backend holds, in order.
+    fn load_parts(
+        &self,
+        message_ids: &[&str],
+        detail: PartDetail,
+    ) -> FxHashMap<Box<str>, Vec<PartData>>;
+
+    /// Per-file diffs by session id.
+    fn load_diffs(&self) -> FxHashMap<String, Vec<FileDiff>>;
+
+    /// Line totals by session id, for sessions recorded without file diffs.
+    fn load_diff_totals(&self) -> FxHashMap<String, (u64, u64)> {
+        FxHashMap::default()
+    }
+
+    /// Directories the live watcher should observe.
+    fn watch_paths(&self) -> Vec<PathBuf>;
+
+    /// Files whose mtime and size identify the stored state, for cache validation.
+    fn tracked_files(&self) -> Vec<PathBuf>;
+
+    /// Paths whose mtime moves whenever data is written, cheap enough to
+    /// stat on every shell prompt.
+    fn change_markers(&self) -> Vec<PathBuf> {
+        self.tracked_files()
+    }
+
+    /// True when a file reported by the watcher belongs to this backend.
+    fn owns_change(&self, path: &Path) -> bool;
+
+    /// True when each changed file is one message, part, session or diff, so
+    /// updates can be applied per file; otherwise a change means a rescan.
+    fn is_incremental(&self) -> bool {
+        false
+    }
+}
+
+/// Backends for the configured roots: databases first, then JSON storage for
+/// roots without one, then imported transcripts.
+pub(crate) fn backends() -> &'static [Box<dyn StorageBackend>] {
+    BACKENDS.get_or_init(|| {
+        let roots = crate::stats::get_storage_roots();
+        let mut out: Vec<Box<dyn StorageBackend>> = Vec::with_capacity(roots.len() + 1);
+        let mut db_idx = 0;
+        for root in &roots {
+            for name in ["opencode.db", "kilo.db"] {
+                let db = root.join(name);
+                if db.exists() {
+                    out.push(Box::new(sqlite::SqliteBackend::new(root, db, db_idx)));
+                    db_idx += 1;
+                }
+            }
+        }
+        for root in roots.iter().filter(|r| !sqlite::root_has_db(r)) {
+            out.push(Box::new(json::JsonBackend::new(root)));
+        }
+        out.push(Box::new(crate::import::ImportBackend));
+        out
+    })
+}
+
+#[inline]
+fn message_owner(path: &Path) -> Option<&'static dyn StorageBackend> {
+    message_owner_in(backends(), path)
+}
+
+#[inline]
+fn message_owner_in<'a>(
+    set: &'a [Box<dyn StorageBackend>],
+    path: &Path,
+) -> Option<&'a dyn StorageBackend> {
+    set.iter()
+        .find(|b| b.owns_message(path))
+        .map(|b| b.as_ref())
+}
+
+#[inline]
+fn change_owner(path: &Path) -> Option<&'static dyn StorageBackend> {
+    backends()
+        .iter()
+        .find(|b| b.owns_change(path))
+        .map(|b| b.as_ref())
+}
+
+// ============================================================================
+// Registry-wide access
+// ============================================================================
+//
+// Each `*_in` function reads from an explicit backend set, so a fixture can
+// stand in for the configured roots; the plain versions use the registry.
+
+/// Sessions from every backend; the first record for an id wins.
+pub(crate) fn list_sessions_in(set: &[Box<dyn StorageBackend>]) -> Vec<SessionRecord> {
+    set.iter().flat_map(|b| b.list_sessions()).collect()
+}
+
+pub(crate) fn list_messages() -> Vec<PathBuf> {
+    list_messages_in(backends())
+}
+
+pub(crate) fn list_messages_in(set: &[Box<dyn StorageBackend>]) -> Vec<PathBuf> {
+    set.iter().flat_map(|b| b.list_messages()).collect()
+}
+
+pub(crate) fn load_message(path: &Path) -> Option<Message> {
+    load_message_in(backends(), path)
+}
+
+pub(crate) fn load_message_in(set: &[Box<dyn StorageBackend>], path: &Path) -> Option<Message> {
+    message_owner_in(set, path)?.load_message(path)
+}
+
+/// Parts for `message_ids` from every backend; the first backend holding a message wins.
+pub(crate) fn load_parts(
+    message_ids: &[&str],
+    detail: PartDetail,
+) -> FxHashMap<Box<str>, Vec<PartData>> {
+    load_parts_in(backends(), message_ids, detail)
+}
+
+pub(crate) fn load_parts_in(
+    set: &[Box<dyn StorageBackend>],
+    message_ids: &[&str],
+    detail: PartDetail,
+) -> FxHashMap<Box<str>, Vec<PartData>> {
+    let mut out: FxHashMap<Box<str>, Vec<PartData>> = FxHashMap::default();
+    if message_ids.is_empty() {
+        return out;
+    }
+    for backend in set {
+        for (id, parts) in backend.load_parts(message_ids, detail) {
+            out.entry(id).or_insert(parts);
+        }
+    }
+    out
+}
+
+pub(crate) fn load_diffs() -> FxHashMap<String, Vec<FileDiff>> {
+    load_diffs_in(backends())
+}
+
+pub(crate) fn load_diffs_in(set: &[Box<dyn StorageBackend>]) -> FxHashMap<String, Vec<FileDiff>> {
+    let mut out: FxHashMap<String, Vec<FileDiff>> = FxHashMap::default();
+    for backend in set {
+        for (id, diffs) in backend.load_diffs() {
+            out.entry(id).or_insert(diffs);
+        }
+    }
+    out
+}
+
+/// Line totals per session: summed from `diffs`, else as recorded by a backend.
+pub(crate) fn load_diff_totals(
+    diffs: &FxHashMap<String, Vec<FileDiff>>,
+) -> FxHashMap<String, (u64, u64)> {
+    load_diff_totals_in(backends(), diffs)
+}
+
+pub(crate) fn load_diff_totals_in(
+    set: &[Box<dyn StorageBackend>],
+    diffs: &FxHashMap<String, Vec<FileDiff>>,
+) -> FxHashMap<String, (u64, u64)> {
+    let mut totals: FxHashMap<String, (u64, u64)> = diffs
+        .iter()
+        .map(|(id, diffs)| {
+            let adds: u64 = diffs.iter().map(|d| d.additions).sum();
+            let dels: u64 = diffs.iter().map(|d| d.deletions).sum();
+            (id.clone(), (adds, dels))
+        })
+        .collect();
+    for backend in set {
+        for (id, counts) in backend.load_diff_totals() {
+            totals.entry(id).or_insert(counts);
+        }
+    }
+    totals
+}
+
+/// Directories to watch, falling back to the roots when nothing exists yet.
+pub(crate) fn watch_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for path in backends().iter().flat_map(|b| b.watch_paths()) {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    if paths.is_empty() {
+        paths = crate::stats::get_storage_roots();
+    }
+    paths
+}
+
+pub(crate) fn tracked_files() -> Vec<PathBuf> {
+    backends().iter().flat_map(|b| b.tracked_files()).collect()
+}
+
+/// Latest mtime among every backend's change markers.
+pub(crate) fn last_modified() -> Option<std::time::SystemTime> {
+    backends()
+        .iter()
+        .flat_map(|b| b.change_markers())
+        .filter_map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+/// Root of the backend that owns a message path.
+pub(crate) fn message_root(path: &Path) -> Option<&'static Path> {
+    message_owner(path)?.root()
+}
+
+/// True when the changed file belongs to any backend.
+#[inline]
+pub(crate) fn owns_change(path: &Path) -> bool {
+    change_owner(path).is_some()
+}
+
+/// True when the changed file belongs to a backend that must be rescanned.
+#[inline]
+pub(crate) fn needs_rescan(path: &Path) -> bool {
+    change_owner(path).is_some_and(|b| !b.is_incremental())
+}
+
+/// True when the message path can be re-read on its own after a change.
+#[inline]
+pub(crate) fn is_incremental_message(path: &Path) -> bool {
+    message_owner(path).is_some_and(|b| b.is_incremental())
+}
+
+/// Backends owning any of `changed` that can only be rescanned.
+pub(crate) fn rescan_targets(changed: &[PathBuf]) -> Vec<&'static dyn StorageBackend> {
+    let mut seen: FxHashSet<*const dyn StorageBackend> = FxHashSet::default();
+    changed
+        .iter()
+        .filter_map(|p| change_owner(p))
+        .filter(|b| !b.is_incremental() && seen.insert(*b as *const dyn StorageBackend))
+        .collect()
+}
+
+// ============================================================================
+// Shared record formats
+// ============================================================================
+
+#[derive(Deserialize, Default, Clone)]
+struct SessionDiffEntry {
+    file: Option<LenientString>,
+    additions: Option<LenientU64>,
+    deletions: Option<LenientU64>,
+    status: Option<LenientString>,
+}
+
+#[derive(Deserialize)]
+struct SessionData {
+    id: Option<LenientString>,
+    title: Option<LenientString>,
+    #[serde(rename = "parentID")]
+    parent_id: Option<LenientString>,
+}
+
+impl From<SessionData> for SessionRecord {
+    fn from(session: SessionData) -> Self {
+        Self {
+            id: session.id.map(|s| s.0).unwrap_or_default().into_boxed_str(),
+            title: session.title.map(|s| s.0).unwrap_or_default(),
+            parent_id: session
+                .parent_id
+                .map(|s| s.0.into_boxed_str())
+                .filter(|p| !p.is_empty()),
+        }
+    }
+}
+
+/// Sorted file diffs from a serialized `session_diff` list.
+fn parse_diffs(json: &[u8]) -> Option<Vec<FileDiff>> {
+    let entries = serde_json::from_slice::<Vec<SessionDiffEntry>>(json).ok()?;
+    let mut diffs: Vec<FileDiff> = entries
+        .into_iter()
+        .map(|item| FileDiff {
+            path: item
+                .file
+                .map(|s| s.0)
+                .unwrap_or_else(|| "unknown".into())
+                .into_boxed_str(),
+            additions: item.additions.map(|v| *v).unwrap_or(0),
+            deletions: item.deletions.map(|v| *v).unwrap_or(0),
+            status: item
+                .status
+                .map(|s| s.0)
+                .unwrap_or_else(|| "modified".into())
+                .into_boxed_str(),
+        })
+        .collect();
+    crate::stats::sort_file_diffs(&mut diffs);
+    Some(diffs)
+}
@@ -0,0 +1,173 @@
+//! JSON file layout: `<root>/storage/{session,message,part,session_diff}`.
+//!
+//! Sessions and messages sit one directory below their kind (`message/<session
+//! id>/<message id>.json`), parts one directory per message, and diffs one
+//! file per session. Every file is one record, so changes apply per file.
+
+use super::{parse_diffs, PartDetail, SessionData, SessionRecord, StorageBackend};
+use crate::stats::{FileDiff, Message, PartData};
+use rayon::prelude::*;
+use rustc_hash::FxHashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const KINDS: [&str; 4] = ["message", "part", "session", "session_diff"];
+
+pub(super) struct JsonBackend {
+    root: PathBuf,
+    storage: PathBuf,
+}
+
+impl JsonBackend {
+    pub(super) fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            storage: root.join("storage"),
+        }
+    }
+}
+
+#[inline]
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "json")
+}
+
+/// JSON files directly in `dir` or one directory below it.
+fn list_json_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let top_entries: Vec<_> = entries.flatten().collect();
+    top_entries
+        .par_iter()
+        .flat_map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                fs::read_dir(&path)
+                    .map(|sub| {
+                        sub.flatten()
+                            .map(|e| e.path())
+                            .filter(|p| is_json(p))
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default()
+            } else if is_json(&path) {
+                vec![path]
+            } else {
+                Vec::new()
+            }
+        })
+        .collect()
+}
+
+/// Diffs from `<session id>.json` files in a `session_diff` directory.
+pub(super) fn read_session_diffs(dir: &Path) -> Vec<(String, Vec<FileDiff>)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let all_entries: Vec<_> = entries.flatten().collect();
+    all_entries
+        .par_iter()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if !is_json(&path) {
+                return None;
+            }
+            let stem = path.file_stem()?.to_str()?;
+            let diffs = parse_diffs(&fs::read(&path).ok()?)?;
+            Some((stem.to_string(), diffs))
+        })
+        .collect()
+}
+
+impl StorageBackend for JsonBackend {
+    fn root(&self) -> Option<&Path> {
+        Some(&self.root)
+    }
+
+    fn list_sessions(&self) -> Vec<SessionRecord> {
+        list_json_files(&self.storage.join("session"))
+            .par_iter()
+            .filter_map(|path| {
+                let bytes = fs::read(path).ok()?;
+                let session = serde_json::from_slice::<SessionData>(&bytes).ok()?;
+                Some(SessionRecord::from(session))
+            })
+            .collect()
+    }
+
+    fn list_messages(&self) -> Vec<PathBuf> {
+        list_json_files(&self.storage.join("message"))
+    }
+
+    fn owns_message(&self, path: &Path) -> bool {
+        path.starts_with(self.storage.join("message"))
+    }
+
+    fn load_message(&self, path: &Path) -> Option<Message> {
+        let bytes = fs::read(path).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    fn load_parts(
+        &self,
+        message_ids: &[&str],
+        detail: PartDetail,
+    ) -> FxHashMap<Box<str>, Vec<PartData>> {
+        let part_root = self.storage.join("part");
+        if !part_root.exists() {
+            return FxHashMap::default();
+        }
+        message_ids
+            .par_iter()
+            .filter_map(|msg_id| {
+                let Ok(entries) = fs::read_dir(part_root.join(msg_id)) else {
+                    return None;
+                };
+                let mut p_files: Vec<_> = entries.flatten().collect();
+                p_files.sort_by_key(|e| e.path());
+                let parts: Vec<PartData> = p_files
+                    .iter()
+                    .filter_map(|e| PartData::parse(&fs::read(e.path()).ok()?, detail))
+                    .collect();
+                (!parts.is_empty()).then(|| (msg_id.to_string().into_boxed_str(), parts))
+            })
+            .collect()
+    }
+
+    fn load_diffs(&self) -> FxHashMap<String, Vec<FileDiff>> {
+        read_session_diffs(&self.storage.join("session_diff"))
+            .into_iter()
+            .collect()
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        if self.storage.exists() {
+            vec![self.storage.clone()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn tracked_files(&self) -> Vec<PathBuf> {
+        KINDS
+            .par_iter()
+            .flat_map(|kind| list_json_files(&self.storage.join(kind)))
+            .collect()
+    }
+
+    /// Session files are rewritten whenever one of their messages changes.
+    fn change_markers(&self) -> Vec<PathBuf> {
+        let mut paths = list_json_files(&self.storage.join("session"));
+        paths.extend(KINDS.iter().map(|kind| self.storage.join(kind)));
+        paths
+    }
+
+    fn owns_change(&self, path: &Path) -> bool {
+        path.starts_with(&self.storage)
+    }
+
+    fn is_incremental(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,116 @@
+//! Sessions and messages held in memory under caller-chosen virtual paths.
+
+use super::{PartDetail, SessionRecord, StorageBackend};
+use crate::stats::{FileDiff, Message, PartData};
+use rustc_hash::FxHashMap;
+use std::path::{Path, PathBuf};
+
+struct StoredMessage {
+    path: PathBuf,
+    msg: Message,
+    parts: Vec<PartData>,
+}
+
+/// Backend with no files behind it; records are inserted and removed directly.
+#[derive(Default)]
+pub(crate) struct MemoryBackend {
+    sessions: FxHashMap<Box<str>, SessionRecord>,
+    messages: FxHashMap<Box<str>, StoredMessage>,
+    paths: FxHashMap<PathBuf, Box<str>>,
+}
+
+impl MemoryBackend {
+    /// Add a session unless one with the same id is already held.
+    pub(crate) fn insert_session(&mut self, session: SessionRecord) -> bool {
+        if session.id.is_empty() || self.sessions.contains_key(&session.id) {
+            return false;
+        }
+        self.sessions.insert(session.id.clone(), session);
+        true
+    }
+
+    pub(crate) fn remove_session(&mut self, id: &str) {
+        self.sessions.remove(id);
+    }
+
+    /// Add a message under `path` and return its id; the first copy of an id wins.
+    pub(crate) fn insert_message(
+        &mut self,
+        path: PathBuf,
+        msg: Message,
+        parts: Vec<PartData>,
+    ) -> Option<Box<str>> {
+        let id: Box<str> = msg.id.as_ref().filter(|id| !id.is_empty())?.as_str().into();
+        if self.messages.contains_key(&id) {
+            return None;
+        }
+        self.paths.insert(path.clone(), id.clone());
+        self.messages
+            .insert(id.clone(), StoredMessage { path, msg, parts });
+        Some(id)
+    }
+
+    pub(crate) fn remove_message(&mut self, id: &str) {
+        if let Some(stored) = self.messages.remove(id) {
+            self.paths.remove(&stored.path);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn list_sessions(&self) -> Vec<SessionRecord> {
+        self.sessions.values().cloned().collect()
+    }
+
+    fn list_messages(&self) -> Vec<PathBuf> {
+        self.messages.values().map(|m| m.path.clone()).collect()
+    }
+
+    fn owns_message(&self, path: &Path) -> bool {
+        self.paths.contains_key(path)
+    }
+
+    fn load_message(&self, path: &Path) -> Option<Message> {
+        let id = self.paths.get(path)?;
+        self.messages.get(id).map(|m| m.msg.clone())
+    }
+
+    fn load_parts(
+        &self,
+        message_ids: &[&str],
+        detail: PartDetail,
+    ) -> FxHashMap<Box<str>, Vec<PartData>> {
+        message_ids
+            .iter()
+            .filter_map(|id| {
+                let m = self.messages.get(*id)?;
+                let parts = match detail {
+                    PartDetail::Full => m.parts.clone(),
+                    PartDetail::Summary => m.parts.iter().map(PartData::summary).collect(),
+                };
+                Some((Box::<str>::from(*id), parts))
+            })
+            .collect()
+    }
+
+    fn load_diffs(&self) -> FxHashMap<String, Vec<FileDiff>> {
+        FxHashMap::default()
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    fn tracked_files(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    fn owns_change(&self, _path: &Path) -> bool {
+        false
+    }
+}
@@ -0,0 +1,291 @@
+//! SQLite layout: `<root>/opencode.db` or `<root>/kilo.db`.
+//!
+//! Messages are addressed by `db://message/<db index>/message/<id>` paths so
+//! they can share the cache and watcher bookkeeping with JSON files. Writes
+//! only touch the database and its `-wal`/`-shm` files, so a change means a
+//! rescan.
+
+use super::{json, parse_diffs, PartDetail, SessionRecord, StorageBackend};
+use crate::stats::{FileDiff, LenientI64, LenientString, Message, PartData, TimeData};
+use rusqlite::{params, Connection, OpenFlags};
+use rustc_hash::FxHashMap;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+const DB_MESSAGE_PREFIX: &str = "db://message/";
+
+thread_local! {
+    static DB_CONNS: RefCell<Vec<Option<Connection>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[inline]
+pub(super) fn root_has_db(root: &Path) -> bool {
+    root.join("opencode.db").exists() || root.join("kilo.db").exists()
+}
+
+fn open_db(db_path: &Path) -> Option<Connection> {
+    if !db_path.exists() {
+        return None;
+    }
+    let conn = Connection::open_with_flags(
+        db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI,
+    )
+    .ok()?;
+    let _ = conn.busy_timeout(std::time::Duration::from_millis(300));
+    Some(conn)
+}
+
+pub(super) struct SqliteBackend {
+    root: PathBuf,
+    db: PathBuf,
+    idx: usize,
+    prefix: String,
+}
+
+impl SqliteBackend {
+    pub(super) fn new(root: &Path, db: PathBuf, idx: usize) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            db,
+            idx,
+            prefix: format!("{}{}/message/", DB_MESSAGE_PREFIX, idx),
+        }
+    }
+
+    /// Thread-local cached connection to this database.
+    #[inline]
+    fn with_cached_db<T>(&self, f: impl FnOnce(&Connection) -> Option<T>) -> Option<T> {
+        DB_CONNS.with(|slot| {
+            let mut conns = slot.borrow_mut();
+            if conns.len() <= self.idx {
+                conns.resize_with(self.idx + 1, || None);
+            }
+            if conns[self.idx].is_none() {
+                conns[self.idx] = open_db(&self.db);
+            }
+            conns[self.idx].as_ref().and_then(f)
+        })
+    }
+
+    #[inline]
+    fn message_id<'a>(&self, path: &'a Path) -> Option<&'a str> {
+        path.to_str()?.strip_prefix(self.prefix.as_str())
+    }
+}
+
+impl StorageBackend for SqliteBackend {
+    fn root(&self) -> Option<&Path> {
+        Some(&self.root)
+    }
+
+    fn list_sessions(&self) -> Vec<SessionRecord> {
+        let Some(conn) = open_db(&self.db) else {
+            return Vec::new();
+        };
+        let Ok(mut stmt) = conn.prepare("SELECT id, title, parent_id FROM session") else {
+            return Vec::new();
+        };
+        let Ok(rows) = stmt.query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, String>(1).unwrap_or_default(),
+                r.get::<_, Option<String>>(2).unwrap_or(None),
+            ))
+        }) else {
+            return Vec::new();
+        };
+        rows.flatten()
+            .map(|(id, title, parent)| SessionRecord {
+                id: id.into_boxed_str(),
+                title,
+                parent_id: parent.filter(|p| !p.is_empty()).map(String::into_boxed_str),
+            })
+            .collect()
+    }
+
+    fn list_messages(&self) -> Vec<PathBuf> {
+        let Some(conn) = open_db(&self.db) else {
+            return Vec::new();
+        };
+        let Ok(mut stmt) = conn.prepare("SELECT id FROM message") else {
+            return Vec::new();
+        };
+        let Ok(rows) = stmt.query_map([], |r| r.get::<_, String>(0)) else {
+            return Vec::new();
+        };
+        rows.filter_map(|row| row.ok())
+            .map(|id| PathBuf::from(format!("{}{}", self.prefix, id)))
+            .collect()
+    }
+
+    fn owns_message(&self, path: &Path) -> bool {
+        self.message_id(path).is_some()
+    }
+
+    fn load_message(&self, path: &Path) -> Option<Message> {
+        let message_id = self.message_id(path)?;
+        let (row_id, row_session_id, row_time_created, data): (String, String, i64, String) = self
+            .with_cached_db(|conn| {
+                let Ok(mut stmt) = conn.prepare_cached(
+                    "SELECT id, session_id, time_created, data FROM message WHERE id = ?1",
+                ) else {
+                    return None;
+                };
+                stmt.query_row(params![message_id], |r| {
+                    Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?))
+                })
+                .ok()
+            })?;
+
+        let mut msg: Message = serde_json::from_str(&data).ok()?;
+
+        // Populate missing fields from DB row
+        if msg.id.is_none() || msg.id.as_ref().is_some_and(|id| id.0.is_empty()) {
+            msg.id = Some(LenientString(row_id));
+        }
+        if msg.session_id.is_none() || msg.session_id.as_ref().is_some_and(|s| s.0.is_empty()) {
+            msg.session_id = Some(LenientString(row_session_id));
+        }
+        if msg.time.is_none() {
+            msg.time = Some(TimeData {
+                created: Some(LenientI64(row_time_created)),
+                completed: None,
+            });
+        } else if msg.time.as_ref().is_some_and(|t| t.created.is_none()) {
+            if let Some(ref mut time) = msg.time {
+                time.created = Some(LenientI64(row_time_created));
+            }
+        }
+
+        Some(msg)
+    }
+
+    fn load_parts(
+        &self,
+        message_ids: &[&str],
+        detail: PartDetail,
+    ) -> FxHashMap<Box<str>, Vec<PartData>> {
+        let mut result: FxHashMap<Box<str>, Vec<PartData>> = FxHashMap::default();
+        let Some(conn) = open_db(&self.db) else {
+            return result;
+        };
+        for chunk in message_ids.chunks(500) {
+            let placeholders: String = (0..chunk.len())
+                .map(|i| format!("?{}", i + 1))
+                .collect::<Vec<_>>()
+                .join(",");
+            let sql = format!(
+                "SELECT message_id, data FROM part WHERE message_id IN ({}) \
+                 ORDER BY message_id, time_created ASC, id ASC",
+                placeholders
+            );
+            let Ok(mut stmt) = conn.prepare(&sql) else {
+                continue;
+            };
+            let params: Vec<&dyn rusqlite::types::ToSql> = chunk
+                .iter()
+                .map(|s| s as &dyn rusqlite::types::ToSql)
+                .collect();
+            let Ok(rows) = stmt.query_map(params.as_slice(), |r| {
+                Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))
+            }) else {
+                continue;
+            };
+            for (message_id, data) in rows.flatten() {
+                let Some(part) = PartData::parse(data.as_bytes(), detail) else {
+                    continue;
+                };
+                result
+                    .entry(message_id.into_boxed_str())
+                    .or_default()
+                    .push(part);
+            }
+        }
+        result
+    }
+
+    /// `session_diff` files kept next to the database, else the `summary_diffs` column.
+    fn load_diffs(&self) -> FxHashMap<String, Vec<FileDiff>> {
+        let mut out: FxHashMap<String, Vec<FileDiff>> =
+            json::read_session_diffs(&self.root.join("storage").join("session_diff"))
+                .into_iter()
+                .collect();
+        let Some(conn) = open_db(&self.db) else {
+            return out;
+        };
+        let Ok(mut stmt) = conn.prepare(
+            "SELECT id, summary_diffs FROM session WHERE summary_diffs IS NOT NULL AND summary_diffs <> ''",
+        ) else {
+            return out;
+        };
+        let Ok(rows) = stmt.query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, String>(1).unwrap_or_default(),
+            ))
+        }) else {
+            return out;
+        };
+        for (session_id, json) in rows.flatten() {
+            if out.contains_key(&session_id) {
+                continue;
+            }
+            if let Some(diffs) = parse_diffs(json.as_bytes()) {
+                out.insert(session_id, diffs);
+            }
+        }
+        out
+    }
+
+    fn load_diff_totals(&self) -> FxHashMap<String, (u64, u64)> {
+        let Some(conn) = open_db(&self.db) else {
+            return FxHashMap::default();
+        };
+        let Ok(mut stmt) = conn.prepare(
+            "SELECT id, COALESCE(summary_additions, 0), COALESCE(summary_deletions, 0) FROM session",
+        ) else {
+            return FxHashMap::default();
+        };
+        let Ok(rows) = stmt.query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, i64>(1).unwrap_or(0),
+                r.get::<_, i64>(2).unwrap_or(0),
+            ))
+        }) else {
+            return FxHashMap::default();
+        };
+        rows.flatten()
+            .map(|(id, adds, dels)| (id, (adds.max(0) as u64, dels.max(0) as u64)))
+            .collect()
+    }
+
+    fn watch_paths(&self) -> Vec<PathBuf> {
+        self.db
+            .parent()
+            .map(Path::to_path_buf)
+            .into_iter()
+            .collect()
+    }
+
+    fn tracked_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.db.clone()];
+        for ext in ["db-wal", "db-shm"] {
+            let path = self.db.with_extension(ext);
+            if path.exists() {
+                files.push(path);
+            }
+        }
+        files
+    }
+
+    /// The database file or its `-wal`/`-shm` companions.
+    fn owns_change(&self, path: &Path) -> bool {
+        path.parent() == self.db.parent()
+            && path
+                .file_name()
+                .zip(self.db.file_name())
+                .is_some_and(|(f, d)| f.as_encoded_bytes().starts_with(d.as_encoded_bytes()))
+    }
+}
@@ -0,0 +1,328 @@
+//! One fixture loaded through each backend and aggregated by `collect_stats`;
+//! other modules' tests reuse it through the `pub(crate)` helpers.
+
+use super::json::JsonBackend;
+use super::sqlite::SqliteBackend;
+use super::{load_parts_in, MemoryBackend, PartDetail, SessionRecord, StorageBackend};
+use crate::import::Source;
+use crate::stats::{collect_stats_from, get_day, Message, PartData, Stats};
+use chrono::TimeZone;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SONNET: &str = "anthropic/claude-sonnet-4";
+pub(crate) const GPT: &str = "openai/gpt-5";
+
+/// Noon local time on 2025-06-10, plus `days`.
+pub(crate) fn at(days: i64, ms: i64) -> i64 {
+    chrono::Local
+        .with_ymd_and_hms(2025, 6, 10, 12, 0, 0)
+        .unwrap()
+        .timestamp_millis()
+        + days * 86_400_000
+        + ms
+}
+
+fn message(id: &str, session: &str, role: &str, model: Option<&str>, created: i64) -> Value {
+    let mut msg = json!({
+        "id": id,
+        "sessionID": session,
+        "role": role,
+        "agent": "build",
+        "time": { "created": created },
+    });
+    if let Some((provider, model)) = model.and_then(|m| m.split_once('/')) {
+        msg["providerID"] = json!(provider);
+        msg["modelID"] = json!(model);
+    }
+    msg
+}
+
+fn tool(name: &str, status: &str, start: i64, end: i64) -> Value {
+    let mut part = json!({
+        "type": "tool",
+        "tool": name,
+        "state": { "status": status, "time": { "start": start, "end": end } },
+    });
+    if status == "error" {
+        part["state"]["error"] = json!("no such file");
+    }
+    part
+}
+
+/// A message record with its parts.
+type FixtureMessage = (Value, Vec<Value>);
+
+/// Two sessions on two days: a prompt and two sonnet replies with tool
+/// calls, then one gpt reply.
+fn fixture() -> (Vec<(&'static str, &'static str)>, Vec<FixtureMessage>) {
+    let sessions = vec![("s1", "First"), ("s2", "Second")];
+
+    let mut m2 = message("m2", "s1", "assistant", Some(SONNET), at(0, 1_000));
+    m2["time"]["completed"] = json!(at(0, 5_000));
+    m2["tokens"] = json!({ "input": 100, "output": 50, "cache": { "read": 10, "write": 5 } });
+    m2["cost"] = json!(0.25);
+    let m2_parts = vec![
+        tool("bash", "completed", at(0, 1_500), at(0, 2_000)),
+        tool("read", "error", at(0, 2_500), at(0, 2_600)),
+    ];
+
+    let mut m3 = message("m3", "s1", "assistant", Some(SONNET), at(0, 10_000));
+    m3["tokens"] = json!({ "input": 200, "output": 20 });
+    m3["cost"] = json!(0.5);
+
+    let mut m4 = message("m4", "s2", "assistant", Some(GPT), at(2, 0));
+    m4["tokens"] = json!({ "input": 30, "output": 30 });
+    m4["cost"] = json!(0.1);
+
+    let messages = vec![
+        (message("m1", "s1", "user", None, at(0, 0)), Vec::new()),
+        (m2, m2_parts),
+        (m3, Vec::new()),
+        (m4, Vec::new()),
+    ];
+    (sessions, messages)
+}
+
+pub(crate) fn memory_backend() -> MemoryBackend {
+    let (sessions, messages) = fixture();
+    let mut backend = MemoryBackend::default();
+    for (id, title) in sessions {
+        backend.insert_session(SessionRecord {
+            id: id.into(),
+            title: title.to_string(),
+            parent_id: None,
+        });
+    }
+    for (msg, parts) in messages {
+        let path = PathBuf::from(format!("mem://{}", msg["id"].as_str().unwrap()));
+        let parts: Vec<PartData> = parts
+            .into_iter()
+            .map(|p| serde_json::from_value(p).unwrap())
+            .collect();
+        let msg: Message = serde_json::from_value(msg).unwrap();
+        backend.insert_message(path, msg, parts).unwrap();
+    }
+    backend
+}
+
+/// Fresh directory under the system temp dir.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "opencode-stats-test-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_json(path: &Path, value: &Value) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, serde_json::to_vec(value).unwrap()).unwrap();
+}
+
+pub(crate) fn collect(backend: impl StorageBackend + 'static) -> Stats {
+    let set: Vec<Box<dyn StorageBackend>> = vec![Box::new(backend)];
+    collect_stats_from(&set)
+}
+
+pub(crate) fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+/// Figures every backend must produce for the fixture.
+fn check_fixture_stats(stats: &Stats) {
+    let day0 = get_day(Some(at(0, 0)));
+    let day2 = get_day(Some(at(2, 0)));
+
+    let totals = &stats.totals;
+    assert_eq!(totals.messages, 4);
+    assert_eq!(totals.prompts, 1);
+    assert_eq!(totals.sessions.len(), 2);
+    assert_close(totals.cost, 0.85);
+    assert_eq!(totals.tokens.input, 330);
+    assert_eq!(totals.tokens.output, 100);
+    assert_eq!(totals.tokens.cache_read, 10);
+    assert_eq!(totals.tokens.cache_write, 5);
+    assert_eq!(totals.tools.get("bash"), Some(&1));
+    assert_eq!(totals.tools.get("read"), Some(&1));
+    assert_eq!(totals.tool_errors.get("read"), Some(&1));
+    assert_eq!(totals.tool_errors.get("bash"), None);
+
+    assert_eq!(stats.per_day.len(), 2);
+    let first = &stats.per_day[&day0];
+    assert_eq!(first.messages, 3);
+    assert_eq!(first.prompts, 1);
+    assert_close(first.cost, 0.75);
+    assert_eq!(first.sessions.len(), 1);
+    let s1 = &first.sessions["s1"];
+    assert_eq!(s1.messages, 3);
+    assert_eq!(s1.tokens.input, 300);
+    assert!(s1.models.iter().any(|m| &**m == SONNET));
+    let second = &stats.per_day[&day2];
+    assert_eq!(second.messages, 1);
+    assert!(second.sessions.contains_key("s2"));
+
+    assert_eq!(stats.model_usage.len(), 2);
+    let sonnet = &stats.model_usage[0];
+    assert_eq!(&*sonnet.name, SONNET);
+    assert_eq!(&*sonnet.provider, "anthropic");
+    assert_eq!(sonnet.messages, 2);
+    assert_eq!(sonnet.tokens.total(), 385);
+    assert_close(sonnet.cost, 0.75);
+    assert_eq!(sonnet.tools.get("bash"), Some(&1));
+    assert_eq!(sonnet.tool_errors.get("read"), Some(&1));
+    assert_eq!(sonnet.agents.get("build"), Some(&2));
+    assert!(sonnet.sessions.contains("s1") && sonnet.sessions.len() == 1);
+    assert_eq!(sonnet.daily[&day0].messages, 2);
+    assert_eq!(sonnet.daily_tokens[&day0], 385);
+    assert_eq!(sonnet.by_source.len(), 1);
+    assert_eq!(sonnet.by_source[&Source::OpenCode].messages, 2);
+    let gpt = &stats.model_usage[1];
+    assert_eq!(&*gpt.name, GPT);
+    assert_eq!(gpt.messages, 1);
+    assert_close(gpt.cost, 0.1);
+
+    assert_eq!(stats.session_message_files["s1"].len(), 3);
+    assert_eq!(stats.session_message_files["s2"].len(), 1);
+}
+
+#[test]
+fn memory_backend_aggregates_fixture() {
+    let stats = collect(memory_backend());
+    check_fixture_stats(&stats);
+}
+
+#[test]
+fn memory_backend_loads_parts_in_order() {
+    let set: Vec<Box<dyn StorageBackend>> = vec![Box::new(memory_backend())];
+    let parts = load_parts_in(&set, &["m2", "m3", "missing"], PartDetail::Full);
+    let tools: Vec<&str> = parts["m2"]
+        .iter()
+        .filter_map(|p| p.tool.as_deref())
+        .collect();
+    assert_eq!(tools, ["bash", "read"]);
+    assert!(parts["m3"].is_empty());
+    assert!(!parts.contains_key("missing"));
+    assert!(load_parts_in(&set, &[], PartDetail::Full).is_empty());
+}
+
+#[test]
+fn memory_backend_keeps_first_copy_and_removes() {
+    let mut backend = memory_backend();
+    let duplicate: Message = serde_json::from_value(message("m1", "s2", "user", None, 0)).unwrap();
+    assert!(backend
+        .insert_message(PathBuf::from("mem://other"), duplicate, Vec::new())
+        .is_none());
+    assert!(!backend.insert_session(SessionRecord {
+        id: "s1".into(),
+        title: "Again".into(),
+        parent_id: None,
+    }));
+
+    backend.remove_message("m4");
+    backend.remove_session("s2");
+    assert!(!backend.owns_message(Path::new("mem://m4")));
+    assert_eq!(backend.list_messages().len(), 3);
+    assert_eq!(backend.list_sessions().len(), 1);
+
+    let stats = collect(backend);
+    assert_eq!(stats.totals.messages, 3);
+    assert_eq!(stats.model_usage.len(), 1);
+}
+
+#[test]
+fn json_backend_aggregates_fixture() {
+    let root = temp_dir("json");
+    let storage = root.join("storage");
+    let (sessions, messages) = fixture();
+    for (id, title) in sessions {
+        write_json(
+            &storage.join("session/project").join(format!("{}.json", id)),
+            &json!({ "id": id, "title": title }),
+        );
+    }
+    for (msg, parts) in messages {
+        let id = msg["id"].as_str().unwrap().to_string();
+        let session = msg["sessionID"].as_str().unwrap().to_string();
+        write_json(
+            &storage
+                .join("message")
+                .join(&session)
+                .join(format!("{}.json", id)),
+            &msg,
+        );
+        for (i, part) in parts.iter().enumerate() {
+            write_json(
+                &storage.join("part").join(&id).join(format!("p{}.json", i)),
+                part,
+            );
+        }
+    }
+
+    let stats = collect(JsonBackend::new(&root));
+    check_fixture_stats(&stats);
+
+    // Aggregation skips tool errors; the chat loads them
+    let backend = JsonBackend::new(&root);
+    let error = |detail| {
+        let parts = backend.load_parts(&["m2"], detail);
+        parts["m2"][1].state.as_ref().unwrap().error.clone()
+    };
+    assert!(error(PartDetail::Summary).is_none());
+    assert_eq!(error(PartDetail::Full), Some(json!("no such file")));
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn sqlite_backend_aggregates_fixture() {
+    let root = temp_dir("sqlite");
+    let db = root.join("opencode.db");
+    {
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE session (id TEXT, title TEXT, parent_id TEXT, summary_diffs TEXT,
+                 summary_additions INTEGER, summary_deletions INTEGER);
+             CREATE TABLE message (id TEXT, session_id TEXT, time_created INTEGER, data TEXT);
+             CREATE TABLE part (id TEXT, message_id TEXT, time_created INTEGER, data TEXT);",
+        )
+        .unwrap();
+        let (sessions, messages) = fixture();
+        for (id, title) in sessions {
+            conn.execute(
+                "INSERT INTO session (id, title) VALUES (?1, ?2)",
+                (id, title),
+            )
+            .unwrap();
+        }
+        for (msg, parts) in messages {
+            let id = msg["id"].as_str().unwrap();
+            let created = msg["time"]["created"].as_i64().unwrap();
+            conn.execute(
+                "INSERT INTO message VALUES (?1, ?2, ?3, ?4)",
+                (id, msg["sessionID"].as_str(), created, msg.to_string()),
+            )
+            .unwrap();
+            for (i, part) in parts.iter().enumerate() {
+                conn.execute(
+                    "INSERT INTO part VALUES (?1, ?2, ?3, ?4)",
+                    (format!("p{}", i), id, created + i as i64, part.to_string()),
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    // Connections are cached per thread and index, so stay clear of index 0
+    let stats = collect(SqliteBackend::new(&root, db, 7));
+    check_fixture_stats(&stats);
+    let _ = fs::remove_dir_all(&root);
+}
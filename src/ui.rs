@@ -1,14 +1,20 @@
 //! Main UI module with three panels: Stats, Days, Models.
 
 mod days_panel;
-mod helpers;
+pub(crate) mod helpers;
 mod models_panel;
+mod pricing_panel;
+mod range_picker;
 mod stats_panel;
 
+use crate::date_range::DateRange;
+use crate::import::Source;
 use crate::live_watcher::LiveWatcher;
 use crate::overview_stats::OverviewStatsCache;
 use crate::session::SessionModal;
-use crate::stats::{load_session_chat_with_max_ts, DayStat, ModelUsage, ToolUsage, Totals};
+use crate::stats::{
+    load_session_chat_with_max_ts, DayStat, ModelSpeed, ModelUsage, ToolUsage, Totals,
+};
 use crate::stats_cache::StatsCache;
 use crate::theme::Theme;
 use chrono::{Datelike, Timelike};
@@ -17,7 +23,8 @@ use crossterm::event::{
 };
 use helpers::{
     cache_key, calculate_message_rendered_lines, ActivityView, CachedChat, Focus, HeatmapLayout,
-    LeftPanel, ModelTimelineLayout, PanelRects, RightPanel, WeeklyHeatmapLayout,
+    LeftPanel, ModelTimelineLayout, PanelRects, PricingPanel, RangePicker, RankingSort, RightPanel,
+    WeeklyHeatmapLayout,
 };
 use parking_lot::Mutex;
 use ratatui::{
@@ -32,6 +39,11 @@ use std::io;
 use std::path::PathBuf;
 use std::sync::{mpsc, Arc};
 
+const STATUS_MESSAGE_DURATION: std::time::Duration = std::time::Duration::from_secs(4);
+const BUDGET_BANNER_DURATION: std::time::Duration = std::time::Duration::from_secs(15);
+/// Budget bars shown in GENERAL USAGE, most spent first.
+const MAX_BUDGET_ROWS: usize = 4;
+
 pub struct App {
     totals: Totals,
     per_day: FxHashMap<String, DayStat>,
@@ -61,6 +73,9 @@ pub struct App {
     model_usage: Vec<ModelUsage>,
     model_list_state: ListState,
     tool_usage: Vec<ToolUsage>,
+    /// Response speed of each entry in `model_usage`.
+    model_speed: Vec<Option<ModelSpeed>>,
+    ranking_sort: RankingSort,
 
     detail_scroll: u16,
     detail_max_scroll: u16,
@@ -139,22 +154,53 @@ pub struct App {
     last_refresh: Option<std::time::Instant>,
     should_redraw: bool,
     wake_rx: mpsc::Receiver<()>,
+
+    // Transient status bar message (e.g. export result)
+    status_message: Option<(String, std::time::Instant)>,
+
+    // Budgets against unfiltered spend; levels remember the last warning given
+    budgets: Vec<crate::budget::BudgetStatus>,
+    budget_levels: FxHashMap<(crate::budget::Scope, crate::budget::Period), u8>,
+    budget_banner: Option<(String, std::time::Instant)>,
+
+    // Date-range filter: unfiltered data is kept so the window can change
+    date_range: DateRange,
+    range_picker: Option<RangePicker>,
+    pricing_panel: Option<PricingPanel>,
+    all_totals: Totals,
+    all_per_day: FxHashMap<String, DayStat>,
+    all_model_usage: Vec<ModelUsage>,
+    /// Tool whose sessions are shown; `None` combines every source.
+    source_filter: Option<Source>,
+}
+
+/// Source filter, then date window, applied to the unfiltered aggregates.
+fn filter_stats(
+    totals: &Totals,
+    per_day: &FxHashMap<String, DayStat>,
+    model_usage: &[ModelUsage],
+    session_message_files: &FxHashMap<String, FxHashSet<PathBuf>>,
+    source: Option<Source>,
+    range: &DateRange,
+) -> (Totals, FxHashMap<String, DayStat>, Vec<ModelUsage>) {
+    if source.is_none() {
+        return crate::date_range::apply(totals, per_day, model_usage, range);
+    }
+    let (totals, per_day, model_usage) = crate::import::filter_by_source(
+        totals,
+        per_day,
+        model_usage,
+        session_message_files,
+        source,
+    );
+    crate::date_range::apply(&totals, &per_day, &model_usage, range)
 }
 
 /// The main application state.
 impl App {
-    pub fn new() -> Self {
-        let storage_path = crate::stats::get_storage_dirs()
-            .into_iter()
-            .next()
-            .unwrap_or_else(|| {
-                PathBuf::from(std::env::var("XDG_DATA_HOME").unwrap_or_else(|_| {
-                    format!("{}/.local/share", std::env::var("HOME").unwrap_or_default())
-                }))
-                .join("opencode")
-                .join("storage")
-            });
-        let watch_paths = crate::stats::get_watch_paths();
+    pub fn new(date_range: DateRange) -> Self {
+        let storage_path = crate::stats::default_storage_dir();
+        let watch_paths = crate::storage::watch_paths();
 
         let stats_cache = StatsCache::new(storage_path.clone()).ok();
         log::info!("Initialized stats cache for: {}", storage_path.display());
@@ -191,6 +237,10 @@ impl App {
             )
         };
 
+        let (all_totals, all_per_day, all_model_usage) = (totals, per_day, model_usage);
+        let (totals, per_day, model_usage) =
+            crate::date_range::apply(&all_totals, &all_per_day, &all_model_usage, &date_range);
+
         let needs_refresh = Arc::new(Mutex::new(Vec::new()));
         let needs_refresh_clone = needs_refresh.clone();
         let (wake_tx, wake_rx) = mpsc::channel();
@@ -224,15 +274,9 @@ impl App {
             selected_model_index = Some(0);
         }
 
-        let mut tool_usage: Vec<ToolUsage> = totals
-            .tools
-            .iter()
-            .map(|(name, count)| ToolUsage {
-                name: name.clone(),
-                count: *count,
-            })
-            .collect();
-        tool_usage.sort_unstable_by(|a, b| b.count.cmp(&a.count));
+        let tool_usage =
+            ToolUsage::from_counts(&totals.tools, &totals.tool_errors, &totals.tool_durations);
+        let model_speed = model_usage.iter().map(ModelUsage::speed).collect();
 
         let mut app = Self {
             totals,
@@ -250,6 +294,8 @@ impl App {
             model_usage,
             model_list_state,
             tool_usage,
+            model_speed,
+            ranking_sort: RankingSort::Tokens,
             detail_scroll: 0,
             detail_max_scroll: 0,
             model_tool_scroll: 0,
@@ -336,17 +382,63 @@ impl App {
             last_refresh: None,
             should_redraw: true,
             wake_rx,
+
+            status_message: None,
+            budgets: Vec::new(),
+            budget_levels: FxHashMap::default(),
+            budget_banner: None,
+
+            date_range,
+            range_picker: None,
+            pricing_panel: None,
+            all_totals,
+            all_per_day,
+            all_model_usage,
+            source_filter: None,
         };
         // Initialize all cached data and derived values
         app.update_session_list();
         app.precompute_day_strings();
         app.recompute_max_cost_width();
         app.compute_overview_data();
+        // Budgets already past a threshold get their banner on opening
+        if let Some(warning) = app.update_budgets() {
+            app.budget_banner = Some((warning, std::time::Instant::now()));
+        }
 
         app.should_redraw = true;
         app
     }
 
+    /// Re-check budgets and return a warning for each one whose spend crossed
+    /// 80% or 100% since the last check.
+    fn update_budgets(&mut self) -> Option<String> {
+        let today = chrono::Local::now().date_naive();
+        let mut budgets = crate::budget::evaluate(&self.all_per_day, &self.all_model_usage, today);
+        budgets.sort_by(|a, b| b.ratio().total_cmp(&a.ratio()));
+
+        let mut crossed = Vec::new();
+        for b in &budgets {
+            let level = b.level();
+            let prev = self
+                .budget_levels
+                .insert((b.scope.clone(), b.period), level)
+                .unwrap_or(0);
+            if level > prev {
+                crossed.push(format!(
+                    "{} {} at {:.0}% (${:.2} of ${:.2})",
+                    b.scope.label(),
+                    b.period.label(),
+                    b.ratio() * 100.0,
+                    b.spent,
+                    b.limit
+                ));
+            }
+        }
+        self.budgets = budgets;
+        (!crossed.is_empty()).then(|| format!("Budget: {}", crossed.join(" · ")))
+    }
+
     /// Recompute the maximum width of the cost column.
     fn recompute_max_cost_width(&mut self) {
         let mut max_len = 8usize;
@@ -364,24 +456,7 @@ impl App {
     }
 
     fn compute_overview_data(&mut self) {
-        let mut project_counts: FxHashMap<String, usize> = FxHashMap::default();
-        for day_stat in self.per_day.values() {
-            for session in day_stat.sessions.values() {
-                let path = session.path_root.as_ref();
-                let name = if path.is_empty() {
-                    "home".to_string()
-                } else {
-                    path.rsplit('/')
-                        .find(|s| !s.is_empty())
-                        .unwrap_or("home")
-                        .to_string()
-                };
-                *project_counts.entry(name).or_insert(0) += 1;
-            }
-        }
-        let mut projects: Vec<(String, usize)> = project_counts.into_iter().collect();
-        projects.sort_unstable_by(|a, b| b.1.cmp(&a.1));
-        self.overview_projects = projects;
+        self.overview_projects = crate::overview_stats::project_counts(&self.per_day);
 
         // Compute weekly heatmap data: last 7 calendar days x hour bucket.
         self.weekly_heatmap_tokens = [[0u64; 24]; 7];
@@ -766,10 +841,21 @@ impl App {
                 )
             };
 
-            self.totals = totals;
-            self.per_day = per_day;
+            let (filtered_totals, filtered_per_day, filtered_model_usage) = filter_stats(
+                &totals,
+                &per_day,
+                &model_usage,
+                &session_message_files,
+                self.source_filter,
+                &self.date_range,
+            );
+            self.all_totals = totals;
+            self.all_per_day = per_day;
+            self.all_model_usage = model_usage;
+            self.totals = filtered_totals;
+            self.per_day = filtered_per_day;
+            self.model_usage = filtered_model_usage;
             self.session_titles = session_titles;
-            self.model_usage = model_usage;
             self.session_message_files = session_message_files;
             self.parent_map = parent_map;
             self.children_map = children_map;
@@ -777,6 +863,9 @@ impl App {
 
             self.rebuild_day_and_session_lists(is_full_refresh);
             self.update_derived_data();
+            if let Some(warning) = self.update_budgets() {
+                self.budget_banner = Some((warning, std::time::Instant::now()));
+            }
 
             if self.modal.open {
                 if let Some(current) = self.current_chat_session_id.clone() {
@@ -794,6 +883,75 @@ impl App {
         }
     }
 
+    /// Switch the date window and recompute every aggregate from the unfiltered data.
+    fn set_date_range(&mut self, range: DateRange) {
+        if range == self.date_range {
+            return;
+        }
+        self.date_range = range;
+        self.reapply_filters();
+        self.set_status_message(format!("Date range: {}", self.date_range.label()));
+    }
+
+    /// Step through all sources combined, then each source that has sessions.
+    fn cycle_source_filter(&mut self) {
+        let present = crate::import::present_sources(&self.session_message_files);
+        if present.len() < 2 && self.source_filter.is_none() {
+            self.set_status_message("Only one source has sessions".to_string());
+            return;
+        }
+        self.source_filter = match self.source_filter {
+            None => present.first().copied(),
+            Some(current) => present
+                .iter()
+                .skip_while(|&&s| s != current)
+                .nth(1)
+                .copied(),
+        };
+        self.reapply_filters();
+        let label = self.source_filter.map_or("all", |s| s.label());
+        self.set_status_message(format!("Source: {}", label));
+    }
+
+    /// Recompute every aggregate from the unfiltered data after a filter change.
+    fn reapply_filters(&mut self) {
+        let (totals, per_day, model_usage) = filter_stats(
+            &self.all_totals,
+            &self.all_per_day,
+            &self.all_model_usage,
+            &self.session_message_files,
+            self.source_filter,
+            &self.date_range,
+        );
+        self.totals = totals;
+        self.per_day = per_day;
+        self.model_usage = model_usage;
+        self.overview_stats_cache.invalidate();
+
+        match self.model_list_state.selected() {
+            _ if self.model_usage.is_empty() => {
+                self.model_list_state.select(None);
+                self.selected_model_index = None;
+            }
+            Some(i) if i >= self.model_usage.len() => {
+                self.model_list_state.select(Some(0));
+                self.selected_model_index = Some(0);
+            }
+            _ => {}
+        }
+        self.overview_heatmap_selected_day = None;
+        self.model_timeline_selected_day = None;
+        self.overview_project_scroll = 0;
+        self.overview_tool_scroll = 0;
+        self.ranking_scroll = 0;
+        self.chat_cache.clear();
+        self.chat_cache_order.clear();
+        self.current_chat_session_id = None;
+
+        self.rebuild_day_and_session_lists(true);
+        self.update_derived_data();
+    }
+
     /// Rebuild day list and session lists based on current data
     fn rebuild_day_and_session_lists(&mut self, _is_full_refresh: bool) {
         let prev_selected_day = self.selected_day();
@@ -820,17 +978,12 @@ impl App {
 
     /// Update all derived data that affects display formatting
     fn update_derived_data(&mut self) {
-        let mut tool_usage: Vec<ToolUsage> = self
-            .totals
-            .tools
-            .iter()
-            .map(|(name, count)| ToolUsage {
-                name: name.clone(),
-                count: *count,
-            })
-            .collect();
-        tool_usage.sort_unstable_by(|a, b| b.count.cmp(&a.count));
-        self.tool_usage = tool_usage;
+        self.tool_usage = ToolUsage::from_counts(
+            &self.totals.tools,
+            &self.totals.tool_errors,
+            &self.totals.tool_durations,
+        );
+        self.model_speed = self.model_usage.iter().map(ModelUsage::speed).collect();
 
         if !self.model_usage.is_empty() && self.model_list_state.selected().is_none() {
             self.model_list_state.select(Some(0));
@@ -995,6 +1148,23 @@ impl App {
                 self.last_refresh = Some(std::time::Instant::now());
             }
 
+            if self
+                .status_message
+                .as_ref()
+                .is_some_and(|(_, t)| t.elapsed() >= STATUS_MESSAGE_DURATION)
+            {
+                self.status_message = None;
+                self.should_redraw = true;
+            }
+            if self
+                .budget_banner
+                .as_ref()
+                .is_some_and(|(_, t)| t.elapsed() >= BUDGET_BANNER_DURATION)
+            {
+                self.budget_banner = None;
+                self.should_redraw = true;
+            }
+
             let needs_flicker_redraw = ((self.overview_heatmap_flash_time.is_some()
                 || self.weekly_heatmap_flash_time.is_some())
                 && self.left_panel == LeftPanel::Stats)
@@ -1016,7 +1186,18 @@ impl App {
         key: crossterm::event::KeyEvent,
         term_height: u16,
     ) -> io::Result<()> {
-        if (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+        let is_ctrl_c =
+            key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        if self.range_picker.is_some() && !is_ctrl_c {
+            self.handle_range_picker_key(key.code);
+            return Ok(());
+        }
+        if self.pricing_panel.is_some() && !is_ctrl_c {
+            self.handle_pricing_panel_key(key.code);
+            return Ok(());
+        }
+
+        if is_ctrl_c
             || ((key.code == KeyCode::Char('q') || key.code == KeyCode::Char('Q'))
                 && !self.is_active
                 && !self.models_active
@@ -1034,6 +1215,16 @@ impl App {
         }
 
         match key.code {
+            KeyCode::Char('e') => self.export_json(),
+            KeyCode::Char('c') => self.export_csv(),
+            KeyCode::Char('r') => self.open_range_picker(),
+            KeyCode::Char('p') => self.open_pricing_panel(),
+            KeyCode::Char('s') => self.cycle_source_filter(),
+            KeyCode::Char('o') if self.left_panel == LeftPanel::Models => {
+                self.ranking_sort = self.ranking_sort.next();
+                self.ranking_scroll = 0;
+                self.set_status_message(format!("Ranking by {}", self.ranking_sort.label()));
+            }
             KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => {
                 if self.is_active || self.models_active {
                     self.is_active = false;
@@ -1497,6 +1688,9 @@ impl App {
     }
 
     fn handle_mouse_event(&mut self, mouse: MouseEvent, area: Rect) -> bool {
+        if self.range_picker.is_some() || self.pricing_panel.is_some() {
+            return false;
+        }
         match mouse.kind {
             MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
                 let (x, y) = (mouse.column, mouse.row);
@@ -1965,10 +2159,18 @@ impl App {
                 }
             }
         } else {
+            let banner_height = u16::from(self.budget_banner.is_some());
+            let outer = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(banner_height), Constraint::Min(0)])
+                .split(frame.area());
+            if banner_height > 0 {
+                self.render_budget_banner(frame, outer[0]);
+            }
             let main_chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([Constraint::Min(0), Constraint::Length(1)])
-                .split(frame.area());
+                .split(outer[1]);
 
             let horizontal_chunks = Layout::default()
                 .direction(Direction::Horizontal)
@@ -1978,9 +2180,80 @@ impl App {
             self.render_left_panel(frame, horizontal_chunks[0]);
             self.render_right_panel(frame, horizontal_chunks[1]);
             self.render_status_bar(frame, main_chunks[1]);
+
+            if self.range_picker.is_some() {
+                self.render_range_picker(frame, main_chunks[0]);
+            }
+            if self.pricing_panel.is_some() {
+                self.render_pricing_panel(frame, main_chunks[0]);
+            }
         }
     }
 
+    /// Warning row above the panels after a budget threshold is crossed.
+    fn render_budget_banner(&self, frame: &mut Frame, area: Rect) {
+        let Some((msg, _)) = &self.budget_banner else {
+            return;
+        };
+        let colors = self.theme.colors();
+        let over = self.budgets.iter().any(|b| b.level() >= 100);
+        let bg = if over {
+            colors.remove_line
+        } else {
+            colors.cost_estimated
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                format!(" ⚠ {} ", msg),
+                Style::default()
+                    .fg(colors.bg_primary)
+                    .bg(bg)
+                    .add_modifier(Modifier::BOLD),
+            )))
+            .style(Style::default().bg(bg)),
+            area,
+        );
+    }
+
+    /// Show a transient message in the status bar.
+    fn set_status_message(&mut self, msg: String) {
+        self.status_message = Some((msg, std::time::Instant::now()));
+    }
+
+    fn export_view(&self) -> crate::export::StatsView<'_> {
+        crate::export::StatsView {
+            totals: &self.totals,
+            per_day: &self.per_day,
+            session_titles: &self.session_titles,
+            parent_map: &self.parent_map,
+            model_usage: &self.model_usage,
+            session_message_files: &self.session_message_files,
+        }
+    }
+
+    /// Write a JSON export of the loaded stats to the working directory.
+    fn export_json(&mut self) {
+        let doc = crate::export::build_document(&self.export_view());
+        let path = crate::export::default_export_path(".json");
+        let msg = match crate::export::write_json_file(&path, &doc) {
+            Ok(()) => format!("Exported {}", path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        };
+        self.set_status_message(msg);
+    }
+
+    /// Write every CSV table of the loaded stats to the working directory.
+    fn export_csv(&mut self) {
+        let msg = match crate::export::write_all_csv_files(&self.export_view()) {
+            Ok(paths) => match paths.first() {
+                Some(first) => format!("Exported {} and {} more", first.display(), paths.len() - 1),
+                None => "Nothing to export".to_string(),
+            },
+            Err(e) => format!("Export failed: {}", e),
+        };
+        self.set_status_message(msg);
+    }
+
     fn render_status_bar(&self, frame: &mut Frame, area: Rect) {
         let colors = self.theme.colors();
         let k = Style::default()
@@ -1991,7 +2264,43 @@ impl App {
 
         let mut spans: Vec<Span> = Vec::with_capacity(16);
 
-        if self.modal.open {
+        if let Some((msg, _)) = &self.status_message {
+            spans.push(Span::styled(msg.as_str(), k));
+        } else if let Some(picker) = &self.range_picker {
+            let hint = if picker.custom_input.is_some() {
+                "YYYY-MM-DD..YYYY-MM-DD"
+            } else {
+                "↑↓"
+            };
+            spans.extend_from_slice(&[
+                Span::styled(hint, k),
+                Span::styled(
+                    if picker.custom_input.is_some() {
+                        " type range"
+                    } else {
+                        " select"
+                    },
+                    t,
+                ),
+                sep.clone(),
+                Span::styled("Enter", k),
+                Span::styled(" apply", t),
+                sep.clone(),
+                Span::styled("Esc", k),
+                Span::styled(" cancel", t),
+            ]);
+        } else if self.pricing_panel.is_some() {
+            spans.extend_from_slice(&[
+                Span::styled("↑↓", k),
+                Span::styled(" select", t),
+                sep.clone(),
+                Span::styled("Enter", k),
+                Span::styled(" pin mapping", t),
+                sep.clone(),
+                Span::styled("Esc/p", k),
+                Span::styled(" close", t),
+            ]);
+        } else if self.modal.open {
             spans.extend_from_slice(&[
                 Span::styled("←→/Click", k),
                 Span::styled(" column", t),
@@ -2044,6 +2353,26 @@ impl App {
                     Span::styled(" activate", t),
                 ]);
             }
+            spans.extend_from_slice(&[
+                sep.clone(),
+                Span::styled("e/c", k),
+                Span::styled(" export json/csv", t),
+                sep.clone(),
+                Span::styled("r", k),
+                Span::styled(" range", t),
+                sep.clone(),
+                Span::styled("p", k),
+                Span::styled(" pricing", t),
+            ]);
+            if self.source_filter.is_some()
+                || crate::import::present_sources(&self.session_message_files).len() > 1
+            {
+                spans.extend_from_slice(&[
+                    sep.clone(),
+                    Span::styled("s", k),
+                    Span::styled(" source", t),
+                ]);
+            }
             spans.extend_from_slice(&[
                 sep.clone(),
                 Span::styled("Esc/q/Right-click", k),
@@ -2068,7 +2397,7 @@ impl App {
             Style::default().fg(colors.border_default)
         };
 
-        let stats_height = 6;
+        let stats_height = 6 + self.budgets.len().min(MAX_BUDGET_ROWS) as u16;
         let remaining = area.height.saturating_sub(stats_height);
         let model_height = if remaining > 18 {
             let extra = remaining - 18;
@@ -2176,9 +2505,19 @@ impl App {
                 );
             }
             LeftPanel::Days => {
+                // One more row when the session info shows a source line
+                let source_row = self
+                    .session_list_state
+                    .selected()
+                    .and_then(|i| self.session_list.get(i))
+                    .and_then(|s| self.session_source_label(&s.id))
+                    .is_some();
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
-                    .constraints([Constraint::Length(10), Constraint::Min(0)])
+                    .constraints([
+                        Constraint::Length(10 + u16::from(source_row)),
+                        Constraint::Min(0),
+                    ])
                     .split(area);
 
                 self.cached_rects.detail = Some(chunks[0]);
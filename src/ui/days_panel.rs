@@ -197,6 +197,19 @@ impl super::App {
         }
     }
 
+    /// Tool that recorded the session, or its storage root when several are
+    /// configured; `None` for opencode sessions in the only root.
+    pub(super) fn session_source_label(&self, session_id: &str) -> Option<String> {
+        let tool = crate::import::session_source(&self.session_message_files, session_id);
+        let root = (crate::stats::get_labeled_roots().len() > 1)
+            .then(|| crate::stats::session_root_label(&self.session_message_files, session_id))
+            .flatten();
+        match (tool, root) {
+            (crate::import::Source::OpenCode, root) => root.map(str::to_string),
+            (tool, _) => Some(tool.label().to_string()),
+        }
+    }
+
     fn render_session_info(
         &mut self,
         frame: &mut Frame,
@@ -340,7 +353,16 @@ impl super::App {
             ]));
         }
 
-        // Host
+        // Importing tool / storage root when there is more than one
+        if let Some(source) = self.session_source_label(&s.id) {
+            left.push(Line::from(vec![
+                Span::styled("Source:      ", muted),
+                Span::styled(
+                    truncate_with_ellipsis(&source, left_w),
+                    Style::default().fg(colors.host),
+                ),
+            ]));
+        }
         let device = crate::device::get_device_info();
         let label = device.display_label();
         let host_w = (cols[0].width as usize).saturating_sub(13 + label.len() + 3 + 1);
@@ -544,13 +566,29 @@ impl super::App {
             })
             .max()
             .unwrap_or(7);
-        let fixed_w = 3 + 8 + 3 + 8 + 3 + (max_cost_len + 1) + 3 + 8 + 3 + max_models_len + 2;
+        // Tag every row with its tool once any session was imported
+        let sources: Vec<crate::import::Source> = self
+            .session_list
+            .iter()
+            .map(|s| crate::import::session_source(&self.session_message_files, &s.id))
+            .collect();
+        let badge_w = if sources
+            .iter()
+            .any(|&src| src != crate::import::Source::OpenCode)
+        {
+            3
+        } else {
+            0
+        };
+        let fixed_w =
+            badge_w + 3 + 8 + 3 + 8 + 3 + (max_cost_len + 1) + 3 + 8 + 3 + max_models_len + 2;
         let title_w = width.saturating_sub(fixed_w.min(u16::MAX as usize) as u16) as usize;
 
         self.cached_session_items = self
             .session_list
             .iter()
-            .map(|s| {
+            .zip(&sources)
+            .map(|(s, &source)| {
                 let title = self
                     .session_titles
                     .get(&s.id)
@@ -568,7 +606,19 @@ impl super::App {
                     colors.text_primary
                 };
 
+                let badge = if badge_w > 0 {
+                    let color = if source == crate::import::Source::OpenCode {
+                        colors.text_muted
+                    } else {
+                        colors.host
+                    };
+                    Span::styled(format!("{} ", source.badge()), Style::default().fg(color))
+                } else {
+                    Span::raw("")
+                };
+
                 ListItem::new(Line::from(vec![
+                    badge,
                     Span::styled(
                         format!(
                             "{:<1$}",
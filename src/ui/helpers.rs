@@ -1,6 +1,6 @@
 //! UI helper functions and shared types.
 
-use crate::stats::{format_number, ChatMessage, MessageContent};
+use crate::stats::{format_latency, format_number, ChatMessage, MessageContent, ToolUsage};
 use crate::theme::ThemeColors;
 use ratatui::{
     layout::{Alignment, Rect},
@@ -45,6 +45,34 @@ pub enum RightPanel {
     Tools,
 }
 
+/// Order of the MODEL RANKING panel.
+#[derive(PartialEq, Clone, Copy)]
+pub enum RankingSort {
+    Tokens,
+    /// Fastest median output tokens per second first.
+    Throughput,
+    /// Lowest median latency first.
+    Latency,
+}
+
+impl RankingSort {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Tokens => Self::Throughput,
+            Self::Throughput => Self::Latency,
+            Self::Latency => Self::Tokens,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Tokens => "tokens",
+            Self::Throughput => "tok/s",
+            Self::Latency => "latency",
+        }
+    }
+}
+
 #[derive(PartialEq, Clone, Copy)]
 pub enum ActivityView {
     Yearly,
@@ -128,6 +156,22 @@ pub struct ModelTimelineLayout {
     pub bucket_days: i64,
 }
 
+/// State of the date-range picker popup.
+#[derive(Default, Clone)]
+pub struct RangePicker {
+    pub selected: usize,
+    /// Text being typed for a custom `since..until` range.
+    pub custom_input: Option<String>,
+    pub error: bool,
+}
+
+/// State of the pricing diagnostics popup.
+#[derive(Default, Clone)]
+pub struct PricingPanel {
+    pub rows: Vec<crate::pricing::PricingRow>,
+    pub selected: usize,
+}
+
 /// Stat paragraph with label and value.
 pub fn stat_widget(
     label: &str,
@@ -203,6 +247,77 @@ pub fn usage_list_row(
     ])
 }
 
+/// Width of a `tool_usage_row` outside its bar, for `name_width` 13.
+pub const TOOL_ROW_FIXED_WIDTH: u16 = 27;
+
+/// Tool usage rows: name, call bar with failed calls in red, count and
+/// success rate, then total, p50 and p95 duration when calls were timed.
+pub fn tool_usage_lines(
+    tool: &ToolUsage,
+    name_width: usize,
+    max_count: u64,
+    bar_max: usize,
+    colors: &ThemeColors,
+) -> Vec<Line<'static>> {
+    let bar = if max_count > 0 {
+        ((tool.count as f64 / max_count as f64) * bar_max as f64) as usize
+    } else {
+        0
+    };
+    let failed = if tool.count > 0 && tool.errors > 0 {
+        ((tool.errors as f64 / tool.count as f64) * bar as f64).ceil() as usize
+    } else {
+        0
+    };
+    let rate_color = if tool.errors > 0 {
+        colors.remove_line
+    } else {
+        colors.text_muted
+    };
+    let mut lines = vec![Line::from(vec![
+        Span::styled(
+            format!(
+                " {:<1$} ",
+                truncate_with_ellipsis(&tool.name, name_width),
+                name_width
+            ),
+            Style::default().fg(colors.text_primary),
+        ),
+        Span::styled(
+            " ".repeat(bar - failed),
+            Style::default().bg(colors.tools_used),
+        ),
+        Span::styled(" ".repeat(failed), Style::default().bg(colors.remove_line)),
+        Span::styled(
+            " ".repeat(bar_max.saturating_sub(bar)),
+            Style::default().bg(colors.bg_empty),
+        ),
+        Span::styled(
+            format!(" {:>5}", tool.count),
+            Style::default()
+                .fg(colors.tools_used)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(" {:>3.0}%", tool.success_rate().floor()),
+            Style::default().fg(rate_color),
+        ),
+    ])];
+    if let Some(latency) = tool.latency {
+        let label = Style::default().fg(colors.text_muted);
+        let value = Style::default().fg(colors.text_secondary);
+        lines.push(Line::from(vec![
+            Span::styled("   Σ ", label),
+            Span::styled(format_latency(latency.total_ms), value),
+            Span::styled(" · p50 ", label),
+            Span::styled(format_latency(latency.p50_ms), value),
+            Span::styled(" · p95 ", label),
+            Span::styled(format_latency(latency.p95_ms), value),
+        ]));
+    }
+    lines
+}
+
 /// Safe truncate without breaking UTF-8.
 pub fn safe_truncate_plain(s: &str, max_chars: usize) -> Cow<'_, str> {
     let mut count = 0;
@@ -252,7 +367,7 @@ pub fn calculate_message_rendered_lines(msg: &ChatMessage) -> u16 {
                     "assistant" => 8,
                     _ => 6,
                 };
-                let n = text.lines().count();
+                let n = text.text.lines().count();
                 lines += n.min(max_lines) as u16;
                 if n > max_lines {
                     lines += 1;
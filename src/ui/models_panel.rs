@@ -1,8 +1,11 @@
 //! Model usage panel rendering.
 
-use super::helpers::{month_abbr, truncate_with_ellipsis, usage_list_row, UsageRowFormat};
-use crate::cost::{estimate_cost, lookup_pricing};
-use crate::stats::{format_number, format_number_full};
+use super::helpers::{
+    month_abbr, tool_usage_lines, truncate_with_ellipsis, usage_list_row, RankingSort,
+    UsageRowFormat, TOOL_ROW_FIXED_WIDTH,
+};
+use crate::cost::{estimate_model_cost, find_override, lookup_pricing, OverrideKind};
+use crate::stats::{format_latency, format_number, format_number_full, ModelSpeed, ToolUsage};
 use chrono::Datelike;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
@@ -13,6 +16,15 @@ use ratatui::{
 };
 use rustc_hash::FxHashMap;
 
+/// Cost and speed figures of the MODEL INFO box.
+#[derive(Clone, Copy)]
+struct ModelInfoFigures<'a> {
+    cost: f64,
+    /// API cost of the model's tokens at the rates of each day.
+    est: Option<f64>,
+    speed: Option<&'a ModelSpeed>,
+}
+
 impl super::App {
     /// MODEL USAGE left panel.
     pub fn render_model_list(
@@ -128,7 +140,7 @@ impl super::App {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(9),
+                Constraint::Length(10),
                 Constraint::Length(10),
                 Constraint::Min(4),
             ])
@@ -153,7 +165,9 @@ impl super::App {
                 m.cost,
                 m.tokens,
                 m.agents.clone(),
-                m.tools.clone(),
+                ToolUsage::from_counts(&m.tools, &m.tool_errors, &FxHashMap::default()),
+                self.model_speed.get(i).copied().flatten(),
+                estimate_model_cost(m),
             ))
         });
 
@@ -187,16 +201,22 @@ impl super::App {
         let inner = info_block.inner(chunks[0]);
         frame.render_widget(info_block, chunks[0]);
 
-        if let Some((name, sessions, messages, cost, tokens, agents, _)) = &selected_data {
+        if let Some((name, sessions, messages, cost, tokens, agents, _, speed, est)) =
+            &selected_data
+        {
             self.render_model_info(
                 frame,
                 inner,
                 name,
                 *sessions,
                 *messages,
-                *cost,
                 tokens,
                 agents,
+                ModelInfoFigures {
+                    cost: *cost,
+                    est: *est,
+                    speed: speed.as_ref(),
+                },
                 &colors,
                 info_focused,
             );
@@ -208,7 +228,7 @@ impl super::App {
 
         // TOOLS USED
         let tools_focused = is_highlighted && self.right_panel == super::helpers::RightPanel::Tools;
-        let tools = selected_data.as_ref().map(|d| &d.6);
+        let tools = selected_data.as_ref().map(|d| d.6.as_slice());
         self.render_tools_panel(
             frame,
             bottom[0],
@@ -237,18 +257,23 @@ impl super::App {
         model_name: &str,
         sessions: usize,
         messages: u64,
-        cost: f64,
         tokens: &crate::stats::Tokens,
         agents: &FxHashMap<Box<str>, u64>,
+        figures: ModelInfoFigures,
         colors: &crate::theme::ThemeColors,
         focused: bool,
     ) {
-        if inner.height < 3 {
+        if inner.height < 4 {
             return;
         }
+        let ModelInfoFigures { cost, est, speed } = figures;
         let sections = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(1), Constraint::Length(2)])
+            .constraints([
+                Constraint::Min(1),
+                Constraint::Length(1),
+                Constraint::Length(2),
+            ])
             .split(inner);
 
         let mut agent_vec: Vec<_> = agents.iter().collect();
@@ -318,7 +343,6 @@ impl super::App {
         let sep_style = Style::default().fg(sep_color);
         let col_w = cols.get(1).map(|c| c.width as usize).unwrap_or(0);
 
-        let est = estimate_cost(model_name, tokens);
         let savings = est.map(|e| e - cost);
 
         let left = vec![
@@ -359,6 +383,11 @@ impl super::App {
                             .map_or(colors.text_secondary, |_| colors.cost_estimated))
                         .add_modifier(Modifier::BOLD),
                 ),
+                Span::styled(
+                    find_override(model_name)
+                        .map_or(String::new(), |o| format!(" [{}]", o.kind.label())),
+                    Style::default().fg(colors.text_muted),
+                ),
             ]),
             Line::from(vec![
                 Span::styled("Savings   ", muted),
@@ -477,17 +506,29 @@ impl super::App {
             );
         }
 
+        frame.render_widget(
+            Paragraph::new(Line::from(model_speed_spans(speed, colors)))
+                .alignment(Alignment::Center),
+            sections[1],
+        );
+
         let pricing_rows = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Length(1), Constraint::Length(1)])
-            .split(sections[1]);
+            .split(sections[2]);
 
         let sep_color = if focused {
             colors.border_focus
         } else {
             colors.text_muted
         };
-        let label = " OpenRouter Price ";
+        let label = match (find_override(model_name), crate::cost::pricing_source()) {
+            (Some(o), _) => format!(" Price · {} ", o.kind.label()),
+            (None, Some(source)) => {
+                format!(" {} Price · {} ", source.catalog_name(), source.label())
+            }
+            (None, None) => " Price ".to_string(),
+        };
         let sep_w = pricing_rows[0].width as usize;
         let label_len = label.chars().count();
         let pad = 10usize;
@@ -521,6 +562,15 @@ impl super::App {
         colors: &crate::theme::ThemeColors,
     ) -> Vec<Span<'static>> {
         let muted = Style::default().fg(colors.text_secondary);
+        match find_override(model_name).map(|o| o.kind) {
+            Some(OverrideKind::Free) => {
+                return vec![Span::styled("Free (self-hosted or unbilled)", muted)]
+            }
+            Some(OverrideKind::Subscription) => {
+                return vec![Span::styled("Covered by subscription", muted)]
+            }
+            _ => {}
+        }
         let Some(p) = lookup_pricing(model_name) else {
             return vec![Span::styled(
                 "n/a",
@@ -544,29 +594,39 @@ impl super::App {
             ),
         ];
 
-        let extras = [
+        let mut extras = vec![
             (
-                "Cache R",
+                "Cache R".to_string(),
                 p.input_cache_read,
                 Style::default()
                     .fg(colors.cache_read)
                     .add_modifier(Modifier::BOLD),
             ),
             (
-                "Cache W",
+                "Cache W".to_string(),
                 p.input_cache_write,
                 Style::default()
                     .fg(colors.cache_write)
                     .add_modifier(Modifier::BOLD),
             ),
             (
-                "Think",
+                "Think".to_string(),
                 p.reasoning,
                 Style::default()
                     .fg(colors.thinking())
                     .add_modifier(Modifier::BOLD),
             ),
         ];
+        // Long-context input rate, e.g. Gemini 2.5 Pro above 200K
+        if let Some(tier) = p.tiers.first() {
+            extras.push((
+                format!(">{}K In", tier.above_prompt_tokens / 1000),
+                tier.rates.prompt,
+                Style::default()
+                    .fg(colors.token_input())
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
 
         let mut used = spans
             .iter()
@@ -956,7 +1016,7 @@ impl super::App {
         frame: &mut Frame,
         area: Rect,
         border_style: Style,
-        tools: Option<&FxHashMap<Box<str>, u64>>,
+        tools: Option<&[ToolUsage]>,
         colors: &crate::theme::ThemeColors,
         focused: bool,
     ) {
@@ -982,7 +1042,11 @@ impl super::App {
             )
             .title_bottom(
                 Line::from(Span::styled(
-                    if focused { " ↑↓: scroll " } else { " " },
+                    if focused {
+                        " ↑↓: scroll │ o: sort "
+                    } else {
+                        " "
+                    },
                     Style::default().fg(colors.text_secondary),
                 ))
                 .alignment(Alignment::Center),
@@ -1002,41 +1066,15 @@ impl super::App {
             return;
         }
 
-        let mut items: Vec<_> = tools.iter().collect();
-        items.sort_unstable_by(|a, b| b.1.cmp(a.1));
-        let max_count = items.iter().map(|(_, c)| **c).max().unwrap_or(0);
-        let bar_max = inner.width.saturating_sub(22) as usize;
+        let max_count = tools.iter().map(|t| t.count).max().unwrap_or(0);
+        let bar_max = inner.width.saturating_sub(TOOL_ROW_FIXED_WIDTH) as usize;
 
-        self.model_tool_max_scroll = items.len().saturating_sub(inner.height as usize) as u16;
-        self.model_tool_scroll = self.model_tool_scroll.min(self.model_tool_max_scroll);
-
-        let lines: Vec<Line> = items
-            .into_iter()
-            .map(|(name, count)| {
-                let w = if max_count > 0 {
-                    ((*count as f64 / max_count as f64) * bar_max as f64) as usize
-                } else {
-                    0
-                };
-                Line::from(vec![
-                    Span::styled(
-                        format!(" {:<13} ", truncate_with_ellipsis(name, 13)),
-                        Style::default().fg(colors.text_primary),
-                    ),
-                    Span::styled(" ".repeat(w), Style::default().bg(colors.tools_used)),
-                    Span::styled(
-                        " ".repeat(bar_max.saturating_sub(w)),
-                        Style::default().bg(colors.bg_empty),
-                    ),
-                    Span::styled(
-                        format!(" {:>5}", count),
-                        Style::default()
-                            .fg(colors.tools_used)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                ])
-            })
+        let lines: Vec<Line> = tools
+            .iter()
+            .flat_map(|tool| tool_usage_lines(tool, 13, max_count, bar_max, colors))
             .collect();
+        self.model_tool_max_scroll = lines.len().saturating_sub(inner.height as usize) as u16;
+        self.model_tool_scroll = self.model_tool_scroll.min(self.model_tool_max_scroll);
 
         frame.render_widget(
             Paragraph::new(lines).scroll((self.model_tool_scroll, 0)),
@@ -1062,7 +1100,7 @@ impl super::App {
             })
             .title(
                 Line::from(Span::styled(
-                    " MODEL RANKING ",
+                    format!(" MODEL RANKING · {} ", self.ranking_sort.label()),
                     Style::default()
                         .fg(if focused {
                             colors.border_focus
@@ -1084,18 +1122,35 @@ impl super::App {
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
+        let sort = self.ranking_sort;
+        let speed = |idx: usize| self.model_speed.get(idx).copied().flatten();
+        // Bar length and sort key; models without timed responses rank last
+        let metric = |idx: usize, m: &crate::stats::ModelUsage| -> f64 {
+            match sort {
+                RankingSort::Tokens => m.tokens.total() as f64,
+                RankingSort::Throughput => speed(idx).map_or(0.0, |s| s.tps_p50),
+                RankingSort::Latency => speed(idx).map_or(0.0, |s| s.latency.p50_ms as f64),
+            }
+        };
+
         let mut ranked: Vec<_> = self.model_usage.iter().enumerate().collect();
-        ranked.sort_unstable_by(|a, b| b.1.tokens.total().cmp(&a.1.tokens.total()));
+        match sort {
+            RankingSort::Latency => ranked.sort_by(|a, b| {
+                let (sa, sb) = (speed(a.0), speed(b.0));
+                sb.is_some()
+                    .cmp(&sa.is_some())
+                    .then_with(|| metric(a.0, a.1).total_cmp(&metric(b.0, b.1)))
+            }),
+            _ => ranked.sort_by(|a, b| metric(b.0, b.1).total_cmp(&metric(a.0, a.1))),
+        }
 
         self.ranking_max_scroll = ranked.len().saturating_sub(inner.height as usize);
         self.ranking_scroll = self.ranking_scroll.min(self.ranking_max_scroll);
 
-        let max_tok = self
-            .model_usage
+        let max_metric = ranked
             .iter()
-            .map(|m| m.tokens.total())
-            .max()
-            .unwrap_or(0);
+            .map(|(idx, m)| metric(*idx, m))
+            .fold(0.0, f64::max);
         let grand: u64 = self.model_usage.iter().map(|m| m.tokens.total()).sum();
         let max_tok_len = self
             .model_usage
@@ -1103,15 +1158,32 @@ impl super::App {
             .map(|m| format_number(m.tokens.total()).len())
             .max()
             .unwrap_or(1);
+        let speed_col = |idx: usize| -> String {
+            let s = speed(idx);
+            match sort {
+                RankingSort::Latency => s.map(|s| format_latency(s.latency.p50_ms)),
+                _ => s
+                    .filter(|s| s.tps_p50 > 0.0)
+                    .map(|s| format!("{:.0}t/s", s.tps_p50)),
+            }
+            .unwrap_or_else(|| "-".into())
+        };
+        let speed_w = (0..self.model_usage.len())
+            .map(|idx| speed_col(idx).chars().count())
+            .max()
+            .unwrap_or(1);
         let total_w = inner.width as usize;
 
-        let suffix_sample = format!(
-            " {:>5.1}% ({:>w$})",
+        let suffix_w = format!(
+            " {:>sw$} {:>5.1}% ({:>w$})",
+            "",
             100.0,
-            format_number(max_tok),
+            "",
+            sw = speed_w,
             w = max_tok_len
-        );
-        let suffix_w = suffix_sample.chars().count();
+        )
+        .chars()
+        .count();
         let bar_avail = total_w.saturating_sub(suffix_w);
         let in_subpanel = self.models_active
             || self.right_panel == super::helpers::RightPanel::Tools
@@ -1134,14 +1206,16 @@ impl super::App {
                     0.0
                 };
                 let suffix = format!(
-                    " {:>5.1}% ({:>w$})",
+                    " {:>sw$} {:>5.1}% ({:>w$})",
+                    speed_col(*idx),
                     pct,
                     format_number(m.tokens.total()),
+                    sw = speed_w,
                     w = max_tok_len
                 );
                 let bar_max = bar_avail;
-                let bar_w = if max_tok > 0 {
-                    ((m.tokens.total() as f64 / max_tok as f64) * bar_max as f64) as usize
+                let bar_w = if max_metric > 0.0 {
+                    ((metric(*idx, m) / max_metric) * bar_max as f64) as usize
                 } else {
                     0
                 };
@@ -1207,6 +1281,41 @@ impl super::App {
     }
 }
 
+/// Latency, time to first token and throughput summary of a model.
+fn model_speed_spans(
+    speed: Option<&ModelSpeed>,
+    colors: &crate::theme::ThemeColors,
+) -> Vec<Span<'static>> {
+    let label = Style::default().fg(colors.text_secondary);
+    let Some(speed) = speed else {
+        return vec![Span::styled("No timed responses", label)];
+    };
+    let value = Style::default()
+        .fg(colors.text_primary)
+        .add_modifier(Modifier::BOLD);
+    let mut spans = vec![
+        Span::styled("Latency p50 ", label),
+        Span::styled(format_latency(speed.latency.p50_ms), value),
+        Span::styled(" p95 ", label),
+        Span::styled(format_latency(speed.latency.p95_ms), value),
+    ];
+    if let Some(ttft) = speed.ttft {
+        spans.extend([
+            Span::styled(" │ TTFT p50 ", label),
+            Span::styled(format_latency(ttft.p50_ms), value),
+        ]);
+    }
+    if speed.tps_p50 > 0.0 {
+        spans.extend([
+            Span::styled(" │ ", label),
+            Span::styled(format!("{:.0}", speed.tps_p50), value),
+            Span::styled(" tok/s p5 ", label),
+            Span::styled(format!("{:.0}", speed.tps_p5), value),
+        ]);
+    }
+    spans
+}
+
 fn format_compact(n: u64) -> String {
     match n {
         0..=999 => n.to_string(),
@@ -0,0 +1,209 @@
+//! Pricing diagnostics popup: how each model was matched to a catalog entry.
+
+use super::helpers::{truncate_with_ellipsis as truncate, PricingPanel};
+use crate::cost::MatchStrategy;
+use crate::pricing::{diagnose, per_million};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Match, score and rate columns plus the gaps between columns.
+const FIXED_COLS_WIDTH: usize = 2 + 14 + 1 + 5 + 2 + 9 * 2;
+
+impl super::App {
+    pub fn open_pricing_panel(&mut self) {
+        self.pricing_panel = Some(PricingPanel {
+            rows: diagnose(&self.all_model_usage),
+            selected: 0,
+        });
+    }
+
+    pub fn handle_pricing_panel_key(&mut self, code: KeyCode) {
+        let Some(panel) = self.pricing_panel.as_mut() else {
+            return;
+        };
+        let count = panel.rows.len().max(1);
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('p') => self.pricing_panel = None,
+            KeyCode::Up | KeyCode::Char('k') => {
+                panel.selected = panel.selected.checked_sub(1).unwrap_or(count - 1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                panel.selected = (panel.selected + 1) % count;
+            }
+            KeyCode::Enter => self.pin_selected_pricing(),
+            _ => {}
+        }
+    }
+
+    /// Pin the selected model to its matched or closest catalog key.
+    fn pin_selected_pricing(&mut self) {
+        let Some(panel) = self.pricing_panel.as_ref() else {
+            return;
+        };
+        let Some(row) = panel.rows.get(panel.selected) else {
+            return;
+        };
+        let model = row.model.clone();
+        let msg = match (&row.found.key, row.found.strategy) {
+            (_, MatchStrategy::Override) => {
+                format!("{} is already priced by the override file", model)
+            }
+            (None, _) => format!("No catalog entry resembles {}", model),
+            (Some(key), _) => match crate::cost::pin_alias(&model, key) {
+                Ok(path) => {
+                    let msg = format!("Pinned {} to {} in {}", model, key, path.display());
+                    let rows = diagnose(&self.all_model_usage);
+                    let selected = rows.iter().position(|r| r.model == model).unwrap_or(0);
+                    self.pricing_panel = Some(PricingPanel { rows, selected });
+                    self.update_derived_data();
+                    msg
+                }
+                Err(e) => format!("Pin failed: {}", e),
+            },
+        };
+        self.set_status_message(msg);
+    }
+
+    /// Centered popup listing every model with the entry that prices it.
+    pub fn render_pricing_panel(&self, frame: &mut Frame, area: Rect) {
+        let Some(panel) = &self.pricing_panel else {
+            return;
+        };
+        let colors = self.theme.colors();
+
+        let width = area.width.saturating_sub(8).min(130);
+        let height = area.height.saturating_sub(4);
+        let popup = Rect::new(
+            area.x + area.width.saturating_sub(width) / 2,
+            area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height,
+        );
+
+        let title = match crate::cost::pricing_source() {
+            Some(source) => format!(
+                " PRICING MATCHES · {} {} ",
+                source.catalog_name(),
+                source.label()
+            ),
+            None => " PRICING MATCHES ".to_string(),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(
+                Style::default()
+                    .fg(colors.border_focus)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .style(Style::default().bg(colors.bg_primary))
+            .title(
+                Line::from(Span::styled(
+                    title,
+                    Style::default()
+                        .fg(colors.border_focus)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .alignment(Alignment::Center),
+            );
+
+        let inner_w = width.saturating_sub(2) as usize;
+        let flex = inner_w.saturating_sub(FIXED_COLS_WIDTH);
+        let model_w = (flex * 2 / 5).max(8);
+        let key_w = flex.saturating_sub(model_w).max(8);
+        let muted = Style::default().fg(colors.text_secondary);
+
+        let unmatched = panel
+            .rows
+            .iter()
+            .filter(|r| r.found.strategy == MatchStrategy::Unmatched)
+            .count();
+        let mut lines = Vec::with_capacity(panel.rows.len() + 4);
+        lines.push(Line::from(Span::styled(
+            format!(
+                " {} models, {} unmatched · Enter pins the selected model to the key shown",
+                panel.rows.len(),
+                unmatched
+            ),
+            muted,
+        )));
+        lines.push(Line::from(Span::styled(
+            format!(
+                " {:<mw$} {:<14} {:>5}  {:<kw$} {:>8}{:>9}",
+                "MODEL",
+                "MATCH",
+                "SCORE",
+                "KEY",
+                "IN $/M",
+                "OUT $/M",
+                mw = model_w,
+                kw = key_w
+            ),
+            muted.add_modifier(Modifier::BOLD),
+        )));
+
+        // Keep the selection in view
+        let visible = (height as usize).saturating_sub(4).max(1);
+        let offset = panel.selected.saturating_sub(visible - 1);
+        for (i, row) in panel.rows.iter().enumerate().skip(offset).take(visible) {
+            let found = &row.found;
+            let is_unmatched = found.strategy == MatchStrategy::Unmatched;
+            let strategy_color = match found.strategy {
+                MatchStrategy::Unmatched => colors.remove_line,
+                MatchStrategy::Fuzzy | MatchStrategy::ProviderFuzzy => colors.cost_estimated,
+                MatchStrategy::Override | MatchStrategy::Pinned => colors.model,
+                _ => colors.add_line,
+            };
+            let key = match &found.key {
+                Some(key) if is_unmatched => format!("({})", key),
+                Some(key) => key.clone(),
+                None => "-".to_string(),
+            };
+            let (input, output) = match &found.pricing {
+                Some(p) if !is_unmatched => (per_million(p.prompt), per_million(p.completion)),
+                _ => ("-".to_string(), "-".to_string()),
+            };
+            let base = if i == panel.selected {
+                Style::default()
+                    .bg(colors.bg_highlight)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(" {:<w$} ", truncate(&row.model, model_w), w = model_w),
+                    base.fg(colors.text_primary),
+                ),
+                Span::styled(
+                    format!("{:<14} ", found.strategy.label()),
+                    base.fg(strategy_color),
+                ),
+                Span::styled(
+                    format!("{:>4}%  ", found.score),
+                    base.fg(colors.text_secondary),
+                ),
+                Span::styled(
+                    format!("{:<w$} ", truncate(&key, key_w), w = key_w),
+                    base.fg(if is_unmatched {
+                        colors.text_muted
+                    } else {
+                        colors.text_primary
+                    }),
+                ),
+                Span::styled(format!("{:>8}{:>9}", input, output), base.fg(colors.cost())),
+            ]));
+        }
+        if panel.rows.is_empty() {
+            lines.push(Line::from(Span::styled(" No models", muted)));
+        }
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(Paragraph::new(lines).block(block), popup);
+    }
+}
@@ -0,0 +1,179 @@
+//! Date-range picker popup.
+
+use super::helpers::RangePicker;
+use crate::date_range::{DateRange, RangePreset};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+const CUSTOM_LABEL: &str = "Custom…";
+const ITEM_COUNT: usize = RangePreset::ALL.len() + 1;
+
+impl super::App {
+    pub fn open_range_picker(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        let selected = RangePreset::ALL
+            .iter()
+            .position(|p| p.range(today) == self.date_range)
+            .unwrap_or(RangePreset::ALL.len());
+        self.range_picker = Some(RangePicker {
+            selected,
+            custom_input: None,
+            error: false,
+        });
+    }
+
+    pub fn handle_range_picker_key(&mut self, code: KeyCode) {
+        let Some(picker) = self.range_picker.as_mut() else {
+            return;
+        };
+
+        if let Some(input) = picker.custom_input.as_mut() {
+            match code {
+                KeyCode::Esc => picker.custom_input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                    picker.error = false;
+                }
+                KeyCode::Char(c) if c.is_ascii_alphanumeric() || c == '-' || c == '.' => {
+                    input.push(c);
+                    picker.error = false;
+                }
+                KeyCode::Enter => match DateRange::parse_span(input) {
+                    Some(range) => {
+                        self.range_picker = None;
+                        self.set_date_range(range);
+                    }
+                    None => picker.error = true,
+                },
+                _ => {}
+            }
+            return;
+        }
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('r') => self.range_picker = None,
+            KeyCode::Up | KeyCode::Char('k') => {
+                picker.selected = picker.selected.checked_sub(1).unwrap_or(ITEM_COUNT - 1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                picker.selected = (picker.selected + 1) % ITEM_COUNT;
+            }
+            KeyCode::Enter => match RangePreset::ALL.get(picker.selected) {
+                Some(preset) => {
+                    let range = preset.range(chrono::Local::now().date_naive());
+                    self.range_picker = None;
+                    self.set_date_range(range);
+                }
+                None => {
+                    let fmt = |d: Option<chrono::NaiveDate>| {
+                        d.map(|d| d.format("%Y-%m-%d").to_string())
+                            .unwrap_or_default()
+                    };
+                    picker.custom_input = Some(if self.date_range.is_all() {
+                        String::new()
+                    } else {
+                        format!(
+                            "{}..{}",
+                            fmt(self.date_range.since),
+                            fmt(self.date_range.until)
+                        )
+                    });
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Centered popup listing range presets.
+    pub fn render_range_picker(&self, frame: &mut Frame, area: Rect) {
+        let Some(picker) = &self.range_picker else {
+            return;
+        };
+        let colors = self.theme.colors();
+
+        let width = 36u16.min(area.width);
+        let height = (ITEM_COUNT as u16 + 6).min(area.height);
+        let popup = Rect::new(
+            area.x + area.width.saturating_sub(width) / 2,
+            area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height,
+        );
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(
+                Style::default()
+                    .fg(colors.border_focus)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .style(Style::default().bg(colors.bg_primary))
+            .title(
+                Line::from(Span::styled(
+                    " DATE RANGE ",
+                    Style::default()
+                        .fg(colors.border_focus)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .alignment(Alignment::Center),
+            );
+
+        let today = chrono::Local::now().date_naive();
+        let mut lines = Vec::with_capacity(ITEM_COUNT + 4);
+        lines.push(Line::from(Span::styled(
+            format!(" Current: {}", self.date_range.label()),
+            Style::default().fg(colors.text_secondary),
+        )));
+        lines.push(Line::from(""));
+
+        let labels = RangePreset::ALL
+            .iter()
+            .map(|p| (p.label(), Some(p.range(today))))
+            .chain(std::iter::once((CUSTOM_LABEL, None)));
+        for (i, (label, range)) in labels.enumerate() {
+            let is_current = range == Some(self.date_range)
+                || (range.is_none()
+                    && !RangePreset::ALL
+                        .iter()
+                        .any(|p| p.range(today) == self.date_range));
+            let marker = if is_current { "● " } else { "  " };
+            let style = if i == picker.selected {
+                Style::default()
+                    .fg(colors.text_primary)
+                    .bg(colors.bg_highlight)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(colors.text_primary)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(" {}", marker),
+                    Style::default().fg(colors.day_stats),
+                ),
+                Span::styled(format!("{:<28}", label), style),
+            ]));
+        }
+
+        if let Some(input) = &picker.custom_input {
+            lines.push(Line::from(""));
+            let color = if picker.error {
+                colors.remove_line
+            } else {
+                colors.text_primary
+            };
+            lines.push(Line::from(vec![
+                Span::styled(" > ", Style::default().fg(colors.border_focus)),
+                Span::styled(format!("{}▏", input), Style::default().fg(color)),
+            ]));
+        }
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(Paragraph::new(lines).block(block), popup);
+    }
+}
@@ -1,8 +1,8 @@
 //! Stats panel rendering.
 
 use super::helpers::{
-    month_abbr, stat_widget, truncate_with_ellipsis, ActivityView, HeatmapLayout,
-    WeeklyHeatmapLayout,
+    month_abbr, stat_widget, tool_usage_lines, truncate_with_ellipsis, ActivityView, HeatmapLayout,
+    WeeklyHeatmapLayout, TOOL_ROW_FIXED_WIDTH,
 };
 use crate::stats::format_number;
 
@@ -32,6 +32,13 @@ impl super::App {
             colors.border_default
         };
 
+        let mut title = " GENERAL USAGE ".to_string();
+        if !self.date_range.is_all() {
+            title.push_str(&format!("· {} ", self.date_range.label()));
+        }
+        if let Some(source) = self.source_filter {
+            title.push_str(&format!("· {} ", source.label()));
+        }
         let block = Block::default()
             .borders(Borders::ALL)
             .border_style(if is_highlighted {
@@ -41,7 +48,7 @@ impl super::App {
             })
             .title(
                 Line::from(Span::styled(
-                    " GENERAL USAGE ",
+                    title,
                     Style::default()
                         .fg(title_color)
                         .add_modifier(Modifier::BOLD),
@@ -52,6 +59,13 @@ impl super::App {
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(4), Constraint::Min(0)])
+            .split(inner);
+        self.render_budget_bars(frame, rows[1]);
+        let inner = rows[0];
+
         let cols = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
@@ -214,6 +228,57 @@ impl super::App {
         );
     }
 
+    /// One progress bar per budget under the totals, most spent first.
+    fn render_budget_bars(&self, frame: &mut Frame, area: Rect) {
+        if area.height == 0 || self.budgets.is_empty() {
+            return;
+        }
+        let colors = self.theme.colors();
+        let muted = Style::default().fg(colors.text_muted);
+        let shown = &self.budgets[..self.budgets.len().min(area.height as usize)];
+        let label_w = shown
+            .iter()
+            .map(|b| b.scope.label().chars().count() + b.period.label().len() + 1)
+            .max()
+            .unwrap_or(0)
+            .min(area.width as usize / 3);
+
+        let lines: Vec<Line> = shown
+            .iter()
+            .map(|b| {
+                let label = format!("{} {}", b.scope.label(), b.period.label());
+                let amounts = format!(" ${:.2} / ${:.2} ", b.spent, b.limit);
+                let percent = format!("{:>4.0}%", (b.ratio() * 100.0).min(999.0));
+                let bar_w = (area.width as usize)
+                    .saturating_sub(label_w + amounts.len() + percent.len() + 3);
+                let filled = ((b.ratio().min(1.0) * bar_w as f64).round() as usize).min(bar_w);
+                let color = match b.level() {
+                    100 => colors.remove_line,
+                    80 => colors.cost_estimated,
+                    _ => colors.add_line,
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!(
+                            " {:<w$} ",
+                            truncate_with_ellipsis(&label, label_w),
+                            w = label_w
+                        ),
+                        Style::default().fg(colors.text_primary),
+                    ),
+                    Span::styled("█".repeat(filled), Style::default().fg(color)),
+                    Span::styled("░".repeat(bar_w - filled), muted),
+                    Span::styled(amounts, Style::default().fg(colors.cost())),
+                    Span::styled(
+                        percent,
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+
     /// OVERVIEW right panel
     pub fn render_overview_panel(
         &mut self,
@@ -539,6 +604,7 @@ impl super::App {
             grid_start + chrono::Duration::days((total_weeks.saturating_sub(weeks) * 7) as i64);
 
         let mut grid: Vec<[Option<u64>; 7]> = vec![[None; 7]; weeks];
+        let mut over_budget: Vec<[bool; 7]> = vec![[false; 7]; weeks];
         let mut max_tokens: u64 = 1;
 
         for (w, col) in grid.iter_mut().enumerate() {
@@ -547,13 +613,21 @@ impl super::App {
                 if date > today {
                     continue;
                 }
+                let key = date.format("%Y-%m-%d").to_string();
                 let tokens = self
                     .per_day
-                    .get(&date.format("%Y-%m-%d").to_string())
+                    .get(&key)
                     .map(|ds| ds.tokens.total())
                     .unwrap_or(0);
                 *cell = Some(tokens);
                 max_tokens = max_tokens.max(tokens);
+                // Budgets count all sources, whatever the filter
+                if let (Some(limit), Some(ds)) = (
+                    crate::budget::heatmap_day_limit(date),
+                    self.all_per_day.get(&key),
+                ) {
+                    over_budget[w][d] = ds.display_cost() > limit;
+                }
             }
         }
 
@@ -687,7 +761,11 @@ impl super::App {
                 } else {
                     Style::default().bg(bg)
                 };
-                spans.push(Span::styled("  ", style));
+                if over_budget[w][d] {
+                    spans.push(Span::styled("▪ ", style.fg(colors.remove_line)));
+                } else {
+                    spans.push(Span::styled("  ", style));
+                }
             }
             lines.push(Line::from(spans));
         }
@@ -719,6 +797,13 @@ impl super::App {
             " More ",
             Style::default().fg(colors.text_secondary),
         ));
+        if over_budget.iter().flatten().any(|&over| over) {
+            legend.push(Span::styled("  ▪", Style::default().fg(colors.remove_line)));
+            legend.push(Span::styled(
+                " over budget ",
+                Style::default().fg(colors.text_secondary),
+            ));
+        }
 
         // Selected day info
         if let Some(day) = &self.overview_heatmap_selected_day {
@@ -919,47 +1004,22 @@ impl super::App {
             return;
         }
 
-        let visible = inner.height as usize;
-        self.overview_tool_max_scroll = self.tool_usage.len().saturating_sub(visible);
-        self.overview_tool_scroll = self.overview_tool_scroll.min(self.overview_tool_max_scroll);
-
         let max_count = self.tool_usage.iter().map(|t| t.count).max().unwrap_or(0);
-        let name_w = 14;
-        let bar_max = inner.width.saturating_sub(22) as usize;
-
-        let lines: Vec<Line> = self
+        let bar_max = inner.width.saturating_sub(TOOL_ROW_FIXED_WIDTH) as usize;
+        let all_lines: Vec<Line> = self
             .tool_usage
             .iter()
+            .flat_map(|tool| tool_usage_lines(tool, 13, max_count, bar_max, &colors))
+            .collect();
+
+        let visible = inner.height as usize;
+        self.overview_tool_max_scroll = all_lines.len().saturating_sub(visible);
+        self.overview_tool_scroll = self.overview_tool_scroll.min(self.overview_tool_max_scroll);
+
+        let lines: Vec<Line> = all_lines
+            .into_iter()
             .skip(self.overview_tool_scroll)
             .take(visible)
-            .map(|tool| {
-                let bar = if max_count > 0 {
-                    ((tool.count as f64 / max_count as f64) * bar_max as f64) as usize
-                } else {
-                    0
-                };
-                Line::from(vec![
-                    Span::styled(
-                        format!(
-                            " {:<1$} ",
-                            truncate_with_ellipsis(&tool.name, name_w - 1),
-                            name_w - 1
-                        ),
-                        Style::default().fg(colors.text_primary),
-                    ),
-                    Span::styled(" ".repeat(bar), Style::default().bg(colors.tools_used)),
-                    Span::styled(
-                        " ".repeat(bar_max.saturating_sub(bar)),
-                        Style::default().bg(colors.bg_empty),
-                    ),
-                    Span::styled(
-                        format!(" {:>5}", tool.count),
-                        Style::default()
-                            .fg(colors.tools_used)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                ])
-            })
             .collect();
 
         frame.render_widget(Paragraph::new(lines), inner);
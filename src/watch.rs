@@ -0,0 +1,301 @@
+//! NDJSON stream of new and updated assistant messages.
+//!
+//! Every assistant message seen at startup is recorded as a baseline; after
+//! that, one JSON line is printed per message that appears or whose tokens or
+//! cost change. JSON storage reports the exact message files that changed.
+//! Other backends (SQLite, imported transcripts) only report that something
+//! changed, so their messages are re-listed to find new ones and their
+//! still-running messages are re-read.
+
+use crate::cli::invalid_arg;
+use crate::date_range::DateRange;
+use crate::stats::{Message, Tokens};
+use crate::stats_cache::StatsCache;
+use rayon::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Run the `watch` subcommand.
+pub fn run(args: &[String], range: &DateRange) -> io::Result<()> {
+    if let Some(arg) = args.first() {
+        if arg == "-h" || arg == "--help" {
+            println!("Usage: opencode-stats-tui watch");
+            println!(
+                "Prints one JSON line per new or updated assistant message until interrupted."
+            );
+            return Ok(());
+        }
+        return Err(invalid_arg(&format!("unknown argument: {}", arg)));
+    }
+
+    let cache = StatsCache::new(crate::stats::default_storage_dir())
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let stats = cache.load_or_compute();
+    let mut tracker = Tracker::new(stats.parent_map);
+    tracker.seed();
+
+    let mut out = io::stdout().lock();
+    let mut result = Ok(());
+    crate::live_watcher::watch_storage_blocking(POLL_INTERVAL, |files| {
+        if files.is_empty() {
+            return ControlFlow::Continue(());
+        }
+        let candidates = tracker.candidates(&files);
+        let paths: Vec<String> = files
+            .iter()
+            .filter_map(|p| p.to_str().map(ToString::to_string))
+            .collect();
+        // Keeps the parent map current and the status-line cache fresh
+        tracker.parent_map = cache.update_files(paths).parent_map;
+
+        for event in tracker.diff(candidates) {
+            if !range.contains(&event.day) {
+                continue;
+            }
+            let written = serde_json::to_writer(&mut out, &event)
+                .map_err(io::Error::from)
+                .and_then(|_| writeln!(out))
+                .and_then(|_| out.flush());
+            if let Err(e) = written {
+                // A closed pipe (e.g. `| head`) ends the stream quietly
+                if e.kind() != io::ErrorKind::BrokenPipe {
+                    result = Err(e);
+                }
+                return ControlFlow::Break(());
+            }
+        }
+        ControlFlow::Continue(())
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+    result
+}
+
+// ============================================================================
+// Tracking
+// ============================================================================
+
+/// Last seen state of one assistant message.
+struct Seen {
+    path: PathBuf,
+    tokens: Tokens,
+    cost: f64,
+    completed: bool,
+}
+
+struct Tracker {
+    seen: FxHashMap<Box<str>, Seen>,
+    known_paths: FxHashSet<PathBuf>,
+    /// Last project seen per session, for messages that omit their path.
+    projects: FxHashMap<String, String>,
+    parent_map: FxHashMap<Box<str>, Box<str>>,
+}
+
+#[derive(Serialize)]
+struct TokenDelta {
+    input: i64,
+    output: i64,
+    reasoning: i64,
+    cache_read: i64,
+    cache_write: i64,
+    total: i64,
+}
+
+impl TokenDelta {
+    fn between(new: &Tokens, old: &Tokens) -> Self {
+        let d = |a: u64, b: u64| a as i64 - b as i64;
+        Self {
+            input: d(new.input, old.input),
+            output: d(new.output, old.output),
+            reasoning: d(new.reasoning, old.reasoning),
+            cache_read: d(new.cache_read, old.cache_read),
+            cache_write: d(new.cache_write, old.cache_write),
+            total: d(new.total(), old.total()),
+        }
+    }
+}
+
+/// One NDJSON line.
+#[derive(Serialize)]
+struct MessageEvent {
+    event: &'static str,
+    time: i64,
+    day: String,
+    message_id: Box<str>,
+    session_id: String,
+    parent_session_id: Option<Box<str>>,
+    model: Box<str>,
+    agent: Option<String>,
+    project: Option<String>,
+    completed: bool,
+    tokens: TokenDelta,
+    cost: f64,
+}
+
+/// Tokens, cost and completion of an assistant message; `None` for other roles.
+fn assistant_usage(msg: &Message) -> Option<(Tokens, f64, bool)> {
+    if msg.role.as_ref().map(|r| r.0.as_str()) != Some("assistant") {
+        return None;
+    }
+    let tokens = msg.tokens.as_ref().map(Tokens::from).unwrap_or_default();
+    let cost = msg.cost.as_ref().map(|c| **c).unwrap_or(0.0);
+    let completed = msg.time.as_ref().is_some_and(|t| t.completed.is_some());
+    Some((tokens, cost, completed))
+}
+
+fn message_key(msg: &Message, path: &Path) -> Box<str> {
+    match &msg.id {
+        Some(id) if !id.0.is_empty() => id.0.as_str().into(),
+        _ => path.to_string_lossy().into(),
+    }
+}
+
+impl Tracker {
+    fn new(parent_map: FxHashMap<Box<str>, Box<str>>) -> Self {
+        Self {
+            seen: FxHashMap::default(),
+            known_paths: FxHashSet::default(),
+            projects: FxHashMap::default(),
+            parent_map,
+        }
+    }
+
+    /// Record every existing message without emitting events.
+    fn seed(&mut self) {
+        let files = crate::storage::list_messages();
+        let loaded: Vec<_> = files
+            .par_iter()
+            .filter_map(|p| Some((p, crate::storage::load_message(p)?)))
+            .collect();
+        for (path, msg) in loaded {
+            self.note_project(&msg);
+            if let Some((tokens, cost, completed)) = assistant_usage(&msg) {
+                self.seen.insert(
+                    message_key(&msg, path),
+                    Seen {
+                        path: path.clone(),
+                        tokens,
+                        cost,
+                        completed,
+                    },
+                );
+            }
+        }
+        self.known_paths = files.into_iter().collect();
+    }
+
+    fn note_project(&mut self, msg: &Message) -> Option<String> {
+        let session_id = msg.session_id.as_ref()?.0.as_str();
+        let project = msg
+            .path
+            .as_ref()
+            .and_then(|p| p.root.clone().or_else(|| p.cwd.clone()))
+            .filter(|p| !p.is_empty());
+        match project {
+            Some(p) => {
+                self.projects.insert(session_id.to_string(), p.clone());
+                Some(p)
+            }
+            None => self.projects.get(session_id).cloned(),
+        }
+    }
+
+    /// Message paths worth re-reading after `changed` files were touched.
+    fn candidates(&mut self, changed: &[PathBuf]) -> Vec<PathBuf> {
+        let mut out: Vec<PathBuf> = changed
+            .iter()
+            .filter(|p| {
+                p.extension().is_some_and(|e| e == "json")
+                    && crate::storage::is_incremental_message(p)
+                    && p.exists()
+            })
+            .cloned()
+            .collect();
+
+        for backend in crate::storage::rescan_targets(changed) {
+            for path in backend.list_messages() {
+                if self.known_paths.insert(path.clone()) {
+                    out.push(path);
+                }
+            }
+            out.extend(
+                self.seen
+                    .values()
+                    .filter(|s| !s.completed && backend.owns_message(&s.path))
+                    .map(|s| s.path.clone()),
+            );
+        }
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    /// Re-read `paths` and build events for messages that are new or changed.
+    fn diff(&mut self, paths: Vec<PathBuf>) -> Vec<MessageEvent> {
+        let mut loaded: Vec<_> = paths
+            .into_par_iter()
+            .filter_map(|p| {
+                let msg = crate::storage::load_message(&p)?;
+                Some((p, msg))
+            })
+            .collect();
+        loaded.sort_unstable_by_key(|(_, m)| m.time.as_ref().and_then(|t| t.created.map(|v| *v)));
+
+        let mut events = Vec::new();
+        for (path, msg) in loaded {
+            let project = self.note_project(&msg);
+            let Some((tokens, cost, completed)) = assistant_usage(&msg) else {
+                continue;
+            };
+            self.known_paths.insert(path.clone());
+            let key = message_key(&msg, &path);
+            let (event, old_tokens, old_cost) = match self.seen.get(&key) {
+                Some(s) if s.tokens == tokens && s.cost == cost && s.completed == completed => {
+                    continue;
+                }
+                Some(s) => ("update", s.tokens, s.cost),
+                None => ("new", Tokens::default(), 0.0),
+            };
+            self.seen.insert(
+                key.clone(),
+                Seen {
+                    path,
+                    tokens,
+                    cost,
+                    completed,
+                },
+            );
+
+            let session_id = msg
+                .session_id
+                .as_ref()
+                .map(|s| s.0.clone())
+                .unwrap_or_default();
+            let time = msg.time.as_ref().and_then(|t| t.created.map(|v| *v));
+            events.push(MessageEvent {
+                event,
+                time: time.unwrap_or(0),
+                day: crate::stats::get_day(time),
+                message_id: key,
+                parent_session_id: self.parent_map.get(session_id.as_str()).cloned(),
+                session_id,
+                model: crate::stats::get_model_id(&msg),
+                agent: msg
+                    .agent
+                    .as_ref()
+                    .map(|a| a.0.clone())
+                    .filter(|a| !a.is_empty()),
+                project,
+                completed,
+                tokens: TokenDelta::between(&tokens, &old_tokens),
+                cost: cost - old_cost,
+            });
+        }
+        events
+    }
+}